@@ -4,12 +4,14 @@ use crate::fs_util::canonicalize_path;
 use deno_core::error::anyhow;
 use deno_core::error::AnyError;
 use deno_core::error::Context;
+use deno_core::serde::de::DeserializeOwned;
 use deno_core::serde::Deserialize;
 use deno_core::serde::Serialize;
 use deno_core::serde::Serializer;
 use deno_core::serde_json;
 use deno_core::serde_json::json;
 use deno_core::serde_json::Value;
+use log::debug;
 use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::fmt;
@@ -259,10 +261,104 @@ impl Serialize for TsConfig {
   }
 }
 
+/// The file names that are looked for, in order, when no `--config` flag is
+/// given.
+pub const CONFIG_FILE_NAMES: &[&str] = &["deno.json", "deno.jsonc"];
+
+/// A set of files, as paths or glob patterns relative to the config file.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct FilesConfig {
+  pub include: Vec<String>,
+  pub exclude: Vec<String>,
+}
+
+impl FilesConfig {
+  /// Resolves `include` and `exclude` against `base`, expanding glob
+  /// patterns into the paths they currently match.
+  pub fn resolve(
+    &self,
+    base: &Path,
+  ) -> Result<(Vec<PathBuf>, Vec<PathBuf>), AnyError> {
+    Ok((
+      resolve_patterns(&self.include, base)?,
+      resolve_patterns(&self.exclude, base)?,
+    ))
+  }
+}
+
+fn resolve_patterns(
+  patterns: &[String],
+  base: &Path,
+) -> Result<Vec<PathBuf>, AnyError> {
+  let mut paths = Vec::new();
+  for pattern in patterns {
+    let path = base.join(pattern);
+    if !pattern.contains(|c| matches!(c, '*' | '?' | '[')) {
+      paths.push(path);
+      continue;
+    }
+    let pattern_str = path.to_string_lossy();
+    let entries = glob::glob(&pattern_str)
+      .with_context(|| format!("Invalid glob pattern \"{}\"", pattern))?;
+    for entry in entries {
+      paths.push(entry?);
+    }
+  }
+  Ok(paths)
+}
+
+/// Options for `deno fmt`, overriding the defaults of `dprint`'s Deno
+/// preset.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+pub struct FmtOptionsConfig {
+  pub use_tabs: Option<bool>,
+  pub line_width: Option<u32>,
+  pub indent_width: Option<u8>,
+  pub single_quote: Option<bool>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct FmtConfig {
+  pub files: FilesConfig,
+  pub options: FmtOptionsConfig,
+}
+
+/// Selects the rules `deno lint` runs. Rules with any of the `tags` are
+/// enabled (`["recommended"]` if omitted), then `include` adds and `exclude`
+/// removes rules by code.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct LintRulesConfig {
+  pub tags: Option<Vec<String>>,
+  pub include: Option<Vec<String>>,
+  pub exclude: Option<Vec<String>>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct LintConfig {
+  pub files: FilesConfig,
+  pub rules: LintRulesConfig,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct TestConfig {
+  pub files: FilesConfig,
+}
+
 #[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ConfigFileJson {
   pub compiler_options: Option<Value>,
+  pub import_map: Option<String>,
+  pub fmt: Option<Value>,
+  pub lint: Option<Value>,
+  pub test: Option<Value>,
+  pub tasks: Option<Value>,
 }
 
 #[derive(Clone, Debug)]
@@ -293,6 +389,88 @@ impl ConfigFile {
     Self::new(&config_text, &config_path)
   }
 
+  /// Reads the config file given with `--config`, or else the first file
+  /// named like one of `CONFIG_FILE_NAMES` in the current directory or one
+  /// of its ancestors.
+  pub fn discover(
+    maybe_config_path: Option<&String>,
+  ) -> Result<Option<Self>, AnyError> {
+    if let Some(config_path) = maybe_config_path {
+      return Ok(Some(Self::read(config_path)?));
+    }
+    let cwd = std::env::current_dir()?;
+    Self::discover_from(&cwd)
+  }
+
+  fn discover_from(start: &Path) -> Result<Option<Self>, AnyError> {
+    for dir in start.ancestors() {
+      for name in CONFIG_FILE_NAMES {
+        let path = dir.join(name);
+        if path.is_file() {
+          debug!("Found config file: {}", path.display());
+          return Ok(Some(Self::read(&path.to_string_lossy())?));
+        }
+      }
+    }
+    Ok(None)
+  }
+
+  /// The directory containing the config file, which relative paths in it are
+  /// resolved against.
+  pub fn dir(&self) -> &Path {
+    self.path.parent().expect("config file path has a parent")
+  }
+
+  /// Returns the `importMap` path resolved against the config file.
+  pub fn to_import_map_path(&self) -> Option<String> {
+    let import_map = self.json.import_map.as_ref()?;
+    if deno_core::url::Url::parse(import_map).is_ok() {
+      Some(import_map.clone())
+    } else {
+      Some(self.dir().join(import_map).to_string_lossy().to_string())
+    }
+  }
+
+  pub fn to_fmt_config(&self) -> Result<Option<FmtConfig>, AnyError> {
+    self.parse_section(&self.json.fmt, "fmt")
+  }
+
+  pub fn to_lint_config(&self) -> Result<Option<LintConfig>, AnyError> {
+    self.parse_section(&self.json.lint, "lint")
+  }
+
+  pub fn to_test_config(&self) -> Result<Option<TestConfig>, AnyError> {
+    self.parse_section(&self.json.test, "test")
+  }
+
+  /// Returns the command lines of the named tasks for `deno task`.
+  pub fn to_tasks_config(
+    &self,
+  ) -> Result<Option<BTreeMap<String, String>>, AnyError> {
+    self.parse_section(&self.json.tasks, "tasks")
+  }
+
+  fn parse_section<T: DeserializeOwned>(
+    &self,
+    maybe_value: &Option<Value>,
+    name: &str,
+  ) -> Result<Option<T>, AnyError> {
+    match maybe_value {
+      Some(value) => {
+        let section =
+          serde_json::from_value(value.clone()).with_context(|| {
+            format!(
+              "Invalid \"{}\" configuration in \"{}\"",
+              name,
+              self.path.display()
+            )
+          })?;
+        Ok(Some(section))
+      }
+      None => Ok(None),
+    }
+  }
+
   pub fn new(text: &str, path: &Path) -> Result<Self, AnyError> {
     let jsonc = match jsonc_parser::parse_to_serde_value(text) {
       Ok(None) => json!({}),
@@ -446,6 +624,109 @@ mod tests {
     assert!(ConfigFile::new(config_text, &config_path).is_err());
   }
 
+  #[test]
+  fn test_parse_tool_config() {
+    let config_text = r#"{
+      "importMap": "./import_map.json",
+      "fmt": {
+        "files": { "include": ["src/"], "exclude": ["src/testdata/"] },
+        "options": { "lineWidth": 100, "singleQuote": true }
+      },
+      "lint": {
+        "rules": { "tags": ["recommended"], "exclude": ["no-explicit-any"] }
+      },
+      "test": {
+        "files": { "include": ["tests/"] }
+      }
+    }"#;
+    let config_path = PathBuf::from("/deno/deno.json");
+    let config_file = ConfigFile::new(config_text, &config_path).unwrap();
+
+    let fmt_config = config_file.to_fmt_config().unwrap().unwrap();
+    assert_eq!(fmt_config.files.include, vec!["src/".to_string()]);
+    assert_eq!(fmt_config.files.exclude, vec!["src/testdata/".to_string()]);
+    assert_eq!(
+      fmt_config.options,
+      FmtOptionsConfig {
+        line_width: Some(100),
+        single_quote: Some(true),
+        ..Default::default()
+      }
+    );
+
+    let lint_config = config_file.to_lint_config().unwrap().unwrap();
+    assert_eq!(lint_config.files, FilesConfig::default());
+    assert_eq!(
+      lint_config.rules,
+      LintRulesConfig {
+        tags: Some(vec!["recommended".to_string()]),
+        include: None,
+        exclude: Some(vec!["no-explicit-any".to_string()]),
+      }
+    );
+
+    let test_config = config_file.to_test_config().unwrap().unwrap();
+    assert_eq!(test_config.files.include, vec!["tests/".to_string()]);
+
+    assert_eq!(
+      config_file.to_import_map_path(),
+      Some(
+        Path::new("/deno")
+          .join("./import_map.json")
+          .to_string_lossy()
+          .to_string()
+      )
+    );
+  }
+
+  #[test]
+  fn test_parse_tool_config_unknown_option() {
+    let config_text = r#"{ "fmt": { "options": { "semiColons": false } } }"#;
+    let config_path = PathBuf::from("/deno/deno.json");
+    let config_file = ConfigFile::new(config_text, &config_path).unwrap();
+    let error = config_file.to_fmt_config().err().unwrap();
+    assert!(error.to_string().contains("Invalid \"fmt\" configuration"));
+    assert!(config_file.to_lint_config().unwrap().is_none());
+  }
+
+  #[test]
+  fn test_discover_config_file() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let nested = temp_dir.path().join("a").join("b");
+    std::fs::create_dir_all(&nested).unwrap();
+    assert!(ConfigFile::discover_from(&nested).unwrap().is_none());
+
+    std::fs::write(
+      temp_dir.path().join("deno.jsonc"),
+      r#"{ "importMap": "map.json" }"#,
+    )
+    .unwrap();
+    let config_file = ConfigFile::discover_from(&nested).unwrap().unwrap();
+    assert_eq!(config_file.json.import_map, Some("map.json".to_string()));
+  }
+
+  #[test]
+  fn test_files_config_resolve() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    std::fs::write(temp_dir.path().join("a_test.ts"), "").unwrap();
+    std::fs::write(temp_dir.path().join("b_test.ts"), "").unwrap();
+    std::fs::write(temp_dir.path().join("mod.ts"), "").unwrap();
+    let files_config = FilesConfig {
+      include: vec!["*_test.ts".to_string()],
+      exclude: vec!["fixtures".to_string()],
+    };
+    let (mut include, exclude) = files_config.resolve(temp_dir.path()).unwrap();
+    include.sort();
+    assert_eq!(
+      include,
+      vec![
+        temp_dir.path().join("a_test.ts"),
+        temp_dir.path().join("b_test.ts"),
+      ]
+    );
+    assert_eq!(exclude, vec![temp_dir.path().join("fixtures")]);
+  }
+
   #[test]
   fn test_tsconfig_merge_user_options() {
     let mut tsconfig = TsConfig::new(json!({
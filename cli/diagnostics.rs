@@ -13,6 +13,8 @@ use std::fmt;
 const MAX_SOURCE_LINE_LENGTH: usize = 150;
 
 const UNSTABLE_DENO_PROPS: &[&str] = &[
+  "Child",
+  "ChildStatus",
   "CompilerOptions",
   "CreateHttpClientOptions",
   "DatagramConn",
@@ -30,6 +32,8 @@ const UNSTABLE_DENO_PROPS: &[&str] = &[
   "MacOSSignal",
   "Metrics",
   "OpMetrics",
+  "Pty",
+  "PtySize",
   "RecordType",
   "RequestEvent",
   "ResolveDnsOptions",
@@ -37,6 +41,8 @@ const UNSTABLE_DENO_PROPS: &[&str] = &[
   "SetRawOptions",
   "Signal",
   "SignalStream",
+  "SpawnOptions",
+  "SpawnOutput",
   "StartTlsOptions",
   "SystemCpuInfo",
   "SystemMemoryInfo",
@@ -65,10 +71,14 @@ const UNSTABLE_DENO_PROPS: &[&str] = &[
   "signal",
   "signals",
   "sleepSync",
+  "spawn",
+  "spawnChild",
+  "spawnSync",
   "startTls",
   "systemCpuInfo",
   "systemMemoryInfo",
   "umask",
+  "upgradeWebSocket",
   "utime",
   "utimeSync",
 ];
@@ -96,7 +96,7 @@ declare namespace Deno {
   export const noColor: boolean;
 
   export interface TestDefinition {
-    fn: () => void | Promise<void>;
+    fn: (t: TestContext) => void | Promise<void>;
     name: string;
     ignore?: boolean;
     /** If at least one test has `only` set to true, only run tests that have
@@ -115,6 +115,39 @@ declare namespace Deno {
     sanitizeExit?: boolean;
   }
 
+  export interface TestStepDefinition {
+    fn: (t: TestContext) => void | Promise<void>;
+    name: string;
+    ignore?: boolean;
+  }
+
+  /** The context passed to a test function, used to run nested test steps. */
+  export interface TestContext {
+    /** Run a sub step of the parent test or step. Steps of the same parent
+     * must be awaited one after another. If a step fails, the parent fails
+     * once its function has returned. Resolves to `true` if the step passed.
+     *
+     * ```ts
+     * Deno.test("database", async (t) => {
+     *   await t.step("insert", () => {});
+     *   await t.step("query", async (t) => {
+     *     await t.step("by id", () => {});
+     *   });
+     * });
+     * ```
+     */
+    step(t: TestStepDefinition): Promise<boolean>;
+
+    /** Run a sub step of the parent test or step. Steps of the same parent
+     * must be awaited one after another. If a step fails, the parent fails
+     * once its function has returned. Resolves to `true` if the step passed.
+     */
+    step(
+      name: string,
+      fn: (t: TestContext) => void | Promise<void>,
+    ): Promise<boolean>;
+  }
+
   /** Register a test which will be run when `deno test` is used on the command
    * line and the containing module looks like a test module.
    * `fn` can be async if required.
@@ -166,7 +199,55 @@ declare namespace Deno {
    * });
    * ```
    * */
-  export function test(name: string, fn: () => void | Promise<void>): void;
+  export function test(
+    name: string,
+    fn: (t: TestContext) => void | Promise<void>,
+  ): void;
+
+  export interface BenchDefinition {
+    fn: () => void | Promise<void>;
+    name: string;
+    ignore?: boolean;
+    /** If at least one benchmark has `only` set to true, only run benchmarks
+     * that have `only` set to true and fail the bench suite. */
+    only?: boolean;
+    /** The number of iterations to run before measuring. Defaults to as many
+     * as fit in 100 milliseconds. */
+    warmup?: number;
+    /** The number of samples to measure. Each sample is the mean time of an
+     * iteration over a batch of iterations, which lasts at least 0.1
+     * milliseconds for fast functions. Defaults to as many samples as fit in
+     * 500 milliseconds, but at least 10. */
+    n?: number;
+  }
+
+  /** Register a benchmark which will be run when `deno bench` is used on the
+   * command line and the containing module looks like a bench module.
+   * `fn` can be async if required.
+   *
+   * ```ts
+   * Deno.bench({
+   *   name: "sort 1000 numbers",
+   *   n: 1000,
+   *   fn() {
+   *     Array.from({ length: 1000 }, () => Math.random()).sort();
+   *   },
+   * });
+   * ```
+   */
+  export function bench(b: BenchDefinition): void;
+
+  /** Register a benchmark which will be run when `deno bench` is used on the
+   * command line and the containing module looks like a bench module.
+   * `fn` can be async if required.
+   *
+   * ```ts
+   * Deno.bench("encode text", () => {
+   *   new TextEncoder().encode("hello world");
+   * });
+   * ```
+   */
+  export function bench(name: string, fn: () => void | Promise<void>): void;
 
   /** Exit the Deno process with optional exit code. If no exit code is supplied
    * then Deno will exit with return code of 0.
@@ -2123,6 +2204,8 @@ declare namespace Deno {
    *
    * Details of the spawned process are returned.
    *
+   * Subprocesses that are still running when the program exits are killed.
+   *
    * Requires `allow-run` permission. */
   export function run<T extends RunOptions = RunOptions>(opt: T): Process<T>;
 
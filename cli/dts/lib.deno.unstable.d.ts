@@ -981,6 +981,87 @@ declare namespace Deno {
     hostname?: string;
     /** Server certificate file. */
     certFile?: string;
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * PEM formatted client certificate chain, presented to servers that ask
+     * for a client certificate. Requires `privateKey` to be set as well.
+     */
+    certChain?: string;
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * PEM formatted (RSA or PKCS8) private key of the client certificate.
+     */
+    privateKey?: string;
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * Application-Layer Protocol Negotiation (ALPN) protocols to offer to the
+     * server, in order of preference. When specified, the TLS handshake is
+     * completed before the connection is returned and the protocol chosen by
+     * the server is available as `alpnProtocol`.
+     */
+    alpnProtocols?: string[];
+  }
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * Details of a TLS session, as negotiated during the handshake. */
+  export interface TlsHandshakeInfo {
+    /** The protocol version in use, e.g. `"TLSv1.3"`. */
+    protocolVersion: string | null;
+    /** The cipher suite in use, e.g. `"TLS13_AES_256_GCM_SHA384"` or
+     * `"TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256"`. */
+    cipherSuite: string | null;
+    /** The protocol agreed on through ALPN, or `null` if there was none. */
+    alpnProtocol: string | null;
+    /** The host name sent by the client through SNI, or `null` if the client
+     * didn't send one. */
+    sniHostname: string | null;
+    /** The DER encoded certificate chain presented by the peer, leaf first.
+     * Empty if the peer did not present a certificate. */
+    peerCertificates: Uint8Array[];
+  }
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * A TLS connection opened with `Deno.connectTls()` or `Deno.startTls()`, or
+   * accepted by a listener created with `Deno.listenTls()`. */
+  export interface TlsConn extends Conn {
+    /** The ALPN protocol the server selected during the handshake, or `null`
+     * if no protocols were offered or none was agreed on. On the server side
+     * of a connection it is `null` until `handshake()` has resolved.
+     * `Deno.serveHttp()` completes the handshake itself before choosing
+     * between HTTP/1.1 and HTTP/2. */
+    readonly alpnProtocol: string | null;
+    /** Completes the TLS handshake if necessary and resolves to the details
+     * of the negotiated session.
+     *
+     * ```ts
+     * const conn = await Deno.connectTls({
+     *   hostname: "deno.land",
+     *   port: 443,
+     * }) as Deno.TlsConn;
+     * const { protocolVersion, cipherSuite } = await conn.handshake();
+     * console.log(protocolVersion, cipherSuite);
+     * ```
+     */
+    handshake(): Promise<TlsHandshakeInfo>;
+    /** Completes the TLS handshake if necessary and resolves to the DER
+     * encoded certificate chain presented by the peer, leaf first. The chain
+     * is empty if the peer did not present a certificate.
+     *
+     * ```ts
+     * const listener = Deno.listenTls({
+     *   port: 443,
+     *   certFile: "./server.crt",
+     *   keyFile: "./server.key",
+     *   clientCaFile: "./client_ca.pem",
+     *   requireClientCert: true,
+     * });
+     * const conn = await listener.accept();
+     * const [clientCert] = await conn.peerCertificates();
+     * ```
+     */
+    peerCertificates(): Promise<Uint8Array[]>;
   }
 
   /** **UNSTABLE**: new API, yet to be vetted.
@@ -1002,7 +1083,49 @@ declare namespace Deno {
   export function startTls(
     conn: Conn,
     options?: StartTlsOptions,
-  ): Promise<Conn>;
+  ): Promise<TlsConn>;
+
+  export interface ConnectTlsOptions {
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * PEM formatted client certificate chain, presented to servers that ask
+     * for a client certificate. Requires `privateKey` to be set as well.
+     */
+    certChain?: string;
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * PEM formatted (RSA or PKCS8) private key of the client certificate.
+     */
+    privateKey?: string;
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * Application-Layer Protocol Negotiation (ALPN) protocols to offer to the
+     * server, in order of preference. When specified, the TLS handshake is
+     * completed before the connection is returned and the protocol chosen by
+     * the server is available as `alpnProtocol`.
+     */
+    alpnProtocols?: string[];
+  }
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * Establishes a secure connection over TLS (transport layer security) while
+   * negotiating an application protocol with the server.
+   *
+   * ```ts
+   * const conn = await Deno.connectTls({
+   *   hostname: "deno.land",
+   *   port: 443,
+   *   alpnProtocols: ["h2", "http/1.1"],
+   * });
+   * console.log(conn.alpnProtocol); // "h2"
+   * ```
+   *
+   * Requires `allow-net` permission.
+   */
+  export function connectTls(
+    options: ConnectTlsOptions & { alpnProtocols: string[] },
+  ): Promise<TlsConn>;
 
   export interface ListenTlsOptions {
     /** **UNSTABLE**: new API, yet to be vetted.
@@ -1010,10 +1133,55 @@ declare namespace Deno {
      * Application-Layer Protocol Negotiation (ALPN) protocols to announce to
      * the client. If not specified, no ALPN extension will be included in the
      * TLS handshake.
+     *
+     * Announcing `"h2"` lets `Deno.serveHttp()` speak HTTP/2 with clients that
+     * select it; other connections are served over HTTP/1.1.
      */
     alpnProtocols?: string[];
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * Path to a PEM file with the certificate authorities that client
+     * certificates are verified against. Clients without a certificate are
+     * still accepted unless `requireClientCert` is set.
+     */
+    clientCaFile?: string;
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * Reject clients that don't present a certificate signed by one of the
+     * authorities in `clientCaFile`. Defaults to `false`.
+     */
+    requireClientCert?: boolean;
   }
 
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * A listener created with `Deno.listenTls()` that yields `TlsConn`s. */
+  export interface TlsListener extends Listener {
+    accept(): Promise<TlsConn>;
+    [Symbol.asyncIterator](): AsyncIterableIterator<TlsConn>;
+  }
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * Listen for mutually authenticated TLS connections: clients have to
+   * present a certificate issued by one of the authorities in `clientCaFile`.
+   *
+   * ```ts
+   * const listener = Deno.listenTls({
+   *   port: 443,
+   *   certFile: "./server.crt",
+   *   keyFile: "./server.key",
+   *   clientCaFile: "./client_ca.pem",
+   *   requireClientCert: true,
+   * });
+   * ```
+   *
+   * Requires `allow-net` and `allow-read` permissions.
+   */
+  export function listenTls(
+    options: ListenTlsOptions & { clientCaFile: string },
+  ): TlsListener;
+
   /** **UNSTABLE**: The `signo` argument may change to require the Deno.Signal
    * enum.
    *
@@ -1032,6 +1200,181 @@ declare namespace Deno {
    * Requires `allow-run` permission. */
   export function kill(pid: number, signo: number): void;
 
+  export interface RunOptions {
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * Start the subprocess as the leader of a new process group. On Unix,
+     * `Deno.kill(-p.pid, signo)` then signals it along with all of its
+     * descendants, and closing it kills the whole group unless its status
+     * was already collected with `p.status()`. */
+    processGroup?: boolean;
+  }
+
+  /** **UNSTABLE**: new API, yet to be vetted. */
+  export interface SpawnOptions {
+    /** Arguments to pass to the command. */
+    args?: string[];
+    /** The working directory of the child. Defaults to the current one. */
+    cwd?: string | URL;
+    /** Start the child with an empty environment instead of inheriting the
+     * one of the current process. Variables in `env` are still set. */
+    clearEnv?: boolean;
+    /** Environment variables to set for the child. */
+    env?: Record<string, string>;
+    /** The user id to run the child as. Only supported on Unix. */
+    uid?: number;
+    /** The group id to run the child as. Only supported on Unix. */
+    gid?: number;
+    /** Start the child as the leader of a new process group, so that it can be
+     * signaled along with all of its descendants. Timeouts, aborts and closing
+     * the child then kill the whole group. */
+    processGroup?: boolean;
+    /** Attach the child to a new pseudo-terminal, which serves as its stdin,
+     * stdout and stderr and is exposed as `child.pty`. With `true` the pty
+     * has the size of Deno's own terminal and follows its resizes while the
+     * child runs. The child leads a new session, and with it a process group.
+     * Only supported on Unix and by `Deno.spawnChild()`. */
+    pty?: boolean | PtySize;
+    /** Kill the child with `SIGKILL` when the signal is aborted. */
+    signal?: AbortSignal;
+    /** Kill the child with `SIGKILL` if it is still running after this many
+     * milliseconds. */
+    timeout?: number;
+    /** Defaults to `"null"`. */
+    stdin?: "piped" | "inherit" | "null";
+    /** Defaults to `"piped"`. */
+    stdout?: "piped" | "inherit" | "null";
+    /** Defaults to `"piped"`. */
+    stderr?: "piped" | "inherit" | "null";
+  }
+
+  /** **UNSTABLE**: new API, yet to be vetted. */
+  export interface PtySize {
+    columns: number;
+    rows: number;
+  }
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * The master side of the pseudo-terminal a child process is attached to.
+   * Reading returns what the child writes to its terminal, writing feeds its
+   * input. Reads end once the child and its descendants have exited. */
+  export class Pty implements Reader, Writer, Closer {
+    readonly rid: number;
+    read(p: Uint8Array): Promise<number | null>;
+    write(p: Uint8Array): Promise<number>;
+    /** Changes the size of the terminal, which notifies the child through
+     * `SIGWINCH`. */
+    resize(size: PtySize): void;
+    close(): void;
+  }
+
+  /** **UNSTABLE**: new API, yet to be vetted. */
+  export interface ChildStatus {
+    success: boolean;
+    /** The exit code, or `128 + signal` if the child was killed by a signal. */
+    code: number;
+    /** The signal that killed the child, if any. */
+    signal: number | null;
+  }
+
+  /** **UNSTABLE**: new API, yet to be vetted. */
+  export interface SpawnOutput {
+    status: ChildStatus;
+    /** The collected stdout, or `null` if it wasn't piped. */
+    stdout: Uint8Array | null;
+    /** The collected stderr, or `null` if it wasn't piped. */
+    stderr: Uint8Array | null;
+  }
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * A child process started with `Deno.spawnChild()`. Closing it kills the
+   * child if it is still running, as does the program exiting. */
+  export class Child {
+    readonly rid: number;
+    readonly pid: number;
+    readonly stdin: (Writer & Closer) | null;
+    readonly stdout: (Reader & Closer) | null;
+    readonly stderr: (Reader & Closer) | null;
+    /** The pty the child is attached to, if it was spawned with `pty`. */
+    readonly pty: Pty | null;
+    /** Resolves once the child has exited. Rejects if the child is closed
+     * while still running. */
+    readonly status: Promise<ChildStatus>;
+    /** Waits for the child to exit and collects all of its piped stdout and
+     * stderr. */
+    output(): Promise<SpawnOutput>;
+    /** Sends a signal to the child. With `group`, the signal goes to the
+     * process group the child was started as the leader of. Neither is
+     * possible once the child has exited and its status was collected, as its
+     * pid and group id may then be reused. Signaling a group is only
+     * supported on Unix, on Windows only the child is signaled. */
+    kill(signo: number, options?: { group?: boolean }): void;
+    /** Releases the child's resource, killing it if it is still running. */
+    close(): void;
+  }
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * Spawns a child process. Unlike `Deno.run()`, stdout and stderr are piped
+   * and stdin is ignored by default.
+   *
+   * ```ts
+   * const child = Deno.spawnChild("deno", {
+   *   args: ["run", "server.ts"],
+   *   stdout: "inherit",
+   *   stderr: "inherit",
+   * });
+   * setTimeout(() => child.kill(Deno.Signal.SIGTERM), 1000);
+   * const status = await child.status;
+   * child.close();
+   * ```
+   *
+   * Requires `allow-run` permission. */
+  export function spawnChild(
+    command: string | URL,
+    options?: SpawnOptions,
+  ): Child;
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * Runs a command to completion and collects its output. Its stdin can't be
+   * piped, use `Deno.spawnChild()` to write to the child.
+   *
+   * ```ts
+   * const { status, stdout } = await Deno.spawn("git", {
+   *   args: ["rev-parse", "HEAD"],
+   *   timeout: 5000,
+   * });
+   * if (status.success) {
+   *   console.log(new TextDecoder().decode(stdout!));
+   * }
+   * ```
+   *
+   * Requires `allow-run` permission. */
+  export function spawn(
+    command: string | URL,
+    options?: Omit<SpawnOptions, "pty">,
+  ): Promise<SpawnOutput>;
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * Synchronously runs a command to completion and collects its output,
+   * blocking the event loop until the child has exited. Its stdin can't be
+   * piped, and the `signal` and `timeout` options are not supported.
+   *
+   * ```ts
+   * const { stdout } = Deno.spawnSync("git", { args: ["rev-parse", "HEAD"] });
+   * export const revision = new TextDecoder().decode(stdout!).trim();
+   * ```
+   *
+   * Requires `allow-run` permission. */
+  export function spawnSync(
+    command: string | URL,
+    options?: Omit<SpawnOptions, "signal" | "timeout" | "pty">,
+  ): SpawnOutput;
+
   /**  **UNSTABLE**: New API, yet to be vetted.  Additional consideration is still
    * necessary around the permissions required.
    *
@@ -1070,6 +1413,13 @@ declare namespace Deno {
     /** A certificate authority to use when validating TLS certificates. Certificate data must be PEM encoded.
      */
     caData?: string;
+    /** PEM formatted client certificate chain, presented to servers that ask
+     * for a client certificate. Requires `privateKey` to be set as well.
+     */
+    certChain?: string;
+    /** PEM formatted (RSA or PKCS8) private key of the client certificate.
+     */
+    privateKey?: string;
   }
 
   /** **UNSTABLE**: New API, yet to be vetted.
@@ -1166,6 +1516,38 @@ declare namespace Deno {
 
     nextRequest(): Promise<RequestEvent | null>;
     close(): void;
+    /** Stops accepting new requests on the connection, waits for the
+     * responses already in flight to be written and then closes it. If
+     * `timeout` (in milliseconds) is given, the connection is closed once it
+     * has passed even if responses are still in flight.
+     *
+     * Together with closing the listener, this drains a server when the
+     * process is asked to terminate:
+     *
+     * ```ts
+     * const listener = Deno.listen({ port: 8000 });
+     * const httpConns = new Set<Deno.HttpConn>();
+     *
+     * Deno.signal(Deno.Signal.SIGTERM).then(async () => {
+     *   listener.close();
+     *   await Promise.all(
+     *     [...httpConns].map((httpConn) => httpConn.shutdown({ timeout: 10000 })),
+     *   );
+     * });
+     *
+     * for await (const conn of listener) {
+     *   (async () => {
+     *     const httpConn = Deno.serveHttp(conn);
+     *     httpConns.add(httpConn);
+     *     for await (const { respondWith } of httpConn) {
+     *       respondWith(new Response("Hello World"));
+     *     }
+     *     httpConns.delete(httpConn);
+     *   })();
+     * }
+     * ```
+     */
+    shutdown(options?: { timeout?: number }): Promise<void>;
   }
 
   /** **UNSTABLE**: new API, yet to be vetted.
@@ -1183,9 +1565,60 @@ declare namespace Deno {
    *
    * If `httpConn.nextRequest()` encounters an error or returns `null`
    * then the underlying HttpConn resource is closed automatically.
+   *
+   * Responses with a compressible `Content-Type` are compressed with gzip or
+   * brotli when the request's `Accept-Encoding` allows it. Set
+   * `Cache-Control: no-transform` on a response to send it uncompressed.
    */
   export function serveHttp(conn: Conn): HttpConn;
 
+  export interface WebSocketUpgrade {
+    response: Response;
+    socket: WebSocket;
+  }
+
+  export interface UpgradeWebSocketOptions {
+    protocol?: string;
+  }
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * Used to upgrade an incoming HTTP request to a WebSocket.
+   *
+   * Given a request, returns a pair of WebSocket and Response. The original
+   * request must be responded to with the returned response for the websocket
+   * upgrade to be successful.
+   *
+   * ```ts
+   * const conn = await Deno.connect({ port: 80, hostname: "127.0.0.1" });
+   * const httpConn = Deno.serveHttp(conn);
+   * const e = await httpConn.nextRequest();
+   * if (e) {
+   *   const { socket, response } = Deno.upgradeWebSocket(e.request);
+   *   socket.onopen = () => {
+   *     socket.send("Hello World!");
+   *   };
+   *   socket.onmessage = (e) => {
+   *     console.log(e.data);
+   *     socket.close();
+   *   };
+   *   socket.onclose = () => console.log("WebSocket has been closed.");
+   *   socket.onerror = (e) => console.error("WebSocket error:", e);
+   *   e.respondWith(response);
+   * }
+   * ```
+   *
+   * If the request body is disturbed (read from) before the upgrade is
+   * completed, upgrading fails.
+   *
+   * This operation does not yet consume the request or open the websocket. This
+   * only happens once the returned response has been passed to `respondWith`.
+   */
+  export function upgradeWebSocket(
+    request: Request,
+    options?: UpgradeWebSocketOptions,
+  ): WebSocketUpgrade;
+
   /** **UNSTABLE**: New option, yet to be vetted. */
   export interface TestDefinition {
     /** Specifies the permissions that should be used to run the test.
@@ -12,6 +12,8 @@ use deno_core::url::Url;
 use deno_runtime::permissions::PermissionsOptions;
 use log::debug;
 use log::Level;
+use regex::Regex;
+use std::convert::TryFrom;
 use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::str::FromStr;
@@ -31,8 +33,45 @@ lazy_static::lazy_static! {
   );
 }
 
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub enum TestReporterKind {
+  Pretty,
+  Junit,
+  Tap,
+  Json,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CoverageThresholdKind {
+  Lines,
+  Branches,
+  Functions,
+}
+
+/// A minimum coverage percentage, for all files together or for each file
+/// whose url matches `pattern`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CoverageThreshold {
+  pub kind: CoverageThresholdKind,
+  pub pattern: Option<String>,
+  pub percent: f64,
+}
+
+impl Default for TestReporterKind {
+  fn default() -> Self {
+    TestReporterKind::Pretty
+  }
+}
+
 #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 pub enum DenoSubcommand {
+  Bench {
+    include: Option<Vec<String>>,
+    filter: Option<String>,
+    json: bool,
+    baseline: Option<PathBuf>,
+    save_baseline: Option<PathBuf>,
+  },
   Bundle {
     source_file: String,
     out_file: Option<PathBuf>,
@@ -55,6 +94,10 @@ pub enum DenoSubcommand {
     include: Vec<String>,
     exclude: Vec<String>,
     lcov: bool,
+    html: Option<PathBuf>,
+    #[serde(skip)]
+    thresholds: Vec<CoverageThreshold>,
+    merge_out: Option<PathBuf>,
   },
   Doc {
     private: bool,
@@ -104,6 +147,13 @@ pub enum DenoSubcommand {
     include: Option<Vec<String>>,
     filter: Option<String>,
     concurrent_jobs: usize,
+    reporter: TestReporterKind,
+    reporter_output: Option<PathBuf>,
+    shard: Option<(usize, usize)>,
+    shuffle: Option<u32>,
+  },
+  Task {
+    task: Option<String>,
   },
   Types,
   Upgrade {
@@ -152,6 +202,7 @@ pub struct Flags {
   pub no_check: bool,
   pub prompt: bool,
   pub no_remote: bool,
+  pub permission_log: Option<PathBuf>,
   pub reload: bool,
   pub repl: bool,
   pub seed: Option<u64>,
@@ -323,10 +374,14 @@ pub fn flags_from_vec(args: Vec<String>) -> clap::Result<Flags> {
     run_parse(&mut flags, m);
   } else if let Some(m) = matches.subcommand_matches("fmt") {
     fmt_parse(&mut flags, m);
+  } else if let Some(m) = matches.subcommand_matches("task") {
+    task_parse(&mut flags, m);
   } else if let Some(m) = matches.subcommand_matches("types") {
     types_parse(&mut flags, m);
   } else if let Some(m) = matches.subcommand_matches("cache") {
     cache_parse(&mut flags, m);
+  } else if let Some(m) = matches.subcommand_matches("bench") {
+    bench_parse(&mut flags, m);
   } else if let Some(m) = matches.subcommand_matches("coverage") {
     coverage_parse(&mut flags, m);
   } else if let Some(m) = matches.subcommand_matches("info") {
@@ -342,7 +397,7 @@ pub fn flags_from_vec(args: Vec<String>) -> clap::Result<Flags> {
   } else if let Some(m) = matches.subcommand_matches("completions") {
     completions_parse(&mut flags, m);
   } else if let Some(m) = matches.subcommand_matches("test") {
-    test_parse(&mut flags, m);
+    test_parse(&mut flags, m)?;
   } else if let Some(m) = matches.subcommand_matches("upgrade") {
     upgrade_parse(&mut flags, m);
   } else if let Some(m) = matches.subcommand_matches("doc") {
@@ -400,6 +455,7 @@ If the flag is set, restrict these messages to errors.",
         )
         .global(true),
     )
+    .subcommand(bench_subcommand())
     .subcommand(bundle_subcommand())
     .subcommand(cache_subcommand())
     .subcommand(compile_subcommand())
@@ -414,6 +470,7 @@ If the flag is set, restrict these messages to errors.",
     .subcommand(lint_subcommand())
     .subcommand(repl_subcommand())
     .subcommand(run_subcommand())
+    .subcommand(task_subcommand())
     .subcommand(test_subcommand())
     .subcommand(types_subcommand())
     .subcommand(upgrade_subcommand())
@@ -421,6 +478,69 @@ If the flag is set, restrict these messages to errors.",
     .after_help(ENV_VARIABLES_HELP)
 }
 
+fn bench_subcommand<'a, 'b>() -> App<'a, 'b> {
+  runtime_args(SubCommand::with_name("bench"), true, false)
+    .setting(AppSettings::TrailingVarArg)
+    .arg(
+      Arg::with_name("filter")
+        .set(ArgSettings::AllowLeadingHyphen)
+        .long("filter")
+        .takes_value(true)
+        .help("Run benchmarks with this string or pattern in the name"),
+    )
+    .arg(
+      Arg::with_name("json")
+        .long("json")
+        .help("Output the results in JSON format")
+        .takes_value(false),
+    )
+    .arg(
+      Arg::with_name("baseline")
+        .long("baseline")
+        .takes_value(true)
+        .require_equals(true)
+        .value_name("FILE")
+        .help("Compare the results with a previously saved baseline"),
+    )
+    .arg(
+      Arg::with_name("save-baseline")
+        .long("save-baseline")
+        .takes_value(true)
+        .require_equals(true)
+        .value_name("FILE")
+        .help("Save the results as a baseline for later comparisons"),
+    )
+    .arg(
+      Arg::with_name("files")
+        .help("List of file names to run")
+        .takes_value(true)
+        .multiple(true),
+    )
+    .arg(script_arg().last(true))
+    .about("Run benchmarks")
+    .long_about(
+      "Run benchmarks using Deno's built-in bench tool.
+
+Evaluate the given modules, run all benchmarks declared with 'Deno.bench()'
+and report their timings to standard output:
+
+  deno bench src/fetch_bench.ts src/signal_bench.ts
+
+Directory arguments are expanded to all contained files matching the glob
+{*_,*.,}bench.{js,mjs,ts,jsx,tsx}:
+
+  deno bench src/
+
+Each benchmark is warmed up and then sampled repeatedly, and the minimum,
+maximum, mean, 75th and 99th percentile time per iteration are reported.
+
+Save the results and compare later runs against them:
+
+  deno bench --save-baseline=baseline.json
+  deno bench --baseline=baseline.json",
+    )
+}
+
 fn bundle_subcommand<'a, 'b>() -> App<'a, 'b> {
   compile_args(SubCommand::with_name("bundle"))
     .arg(
@@ -561,6 +681,21 @@ Write a report using the lcov format:
 Generate html reports from lcov:
 
   genhtml -o html_cov cov.lcov
+
+Or write an html report directly, with a page for every source file:
+
+  deno coverage --html=html_cov cov_profile
+
+Fail when less than 80% of all lines are covered, or when a file whose url
+matches the pattern 'src/core/' has less than 95% of its lines covered:
+
+  deno coverage --threshold-lines=80 --threshold-lines=src/core/=95 cov_profile
+
+Profiles of the same module, for example from several test shards, are merged
+before reporting. Write the merged profile to a single file to aggregate it
+with later runs:
+
+  deno coverage --merge-out=merged.json cov_profile
 ",
     )
     .arg(
@@ -597,6 +732,35 @@ Generate html reports from lcov:
         .help("Output coverage report in lcov format")
         .takes_value(false),
     )
+    .arg(
+      Arg::with_name("html")
+        .long("html")
+        .takes_value(true)
+        .require_equals(true)
+        .value_name("DIR")
+        .conflicts_with("lcov")
+        .help("Write an html coverage report to the given directory"),
+    )
+    .arg(
+      Arg::with_name("merge-out")
+        .long("merge-out")
+        .takes_value(true)
+        .require_equals(true)
+        .value_name("FILE")
+        .help("Write the merged coverage profile to the given file"),
+    )
+    .arg(threshold_arg(
+      "threshold-lines",
+      "Fail if a lower percentage of lines is covered",
+    ))
+    .arg(threshold_arg(
+      "threshold-branches",
+      "Fail if a lower percentage of branches is covered",
+    ))
+    .arg(threshold_arg(
+      "threshold-functions",
+      "Fail if a lower percentage of functions is covered",
+    ))
     .arg(
       Arg::with_name("files")
         .takes_value(true)
@@ -605,6 +769,18 @@ Generate html reports from lcov:
     )
 }
 
+fn threshold_arg<'a, 'b>(name: &'a str, help: &'a str) -> Arg<'a, 'b> {
+  Arg::with_name(name)
+    .long(name)
+    .takes_value(true)
+    .multiple(true)
+    .number_of_values(1)
+    .require_equals(true)
+    .value_name("[regex=]percent")
+    .validator(|val: String| parse_threshold(&val).map(|_| ()))
+    .help(help)
+}
+
 fn doc_subcommand<'a, 'b>() -> App<'a, 'b> {
   SubCommand::with_name("doc")
     .about("Show documentation for a module")
@@ -755,6 +931,7 @@ Ignore formatting a file by adding an ignore comment at the top of the file:
         .require_equals(true)
         .help("Ignore formatting particular source files"),
     )
+    .arg(config_arg())
     .arg(
       Arg::with_name("files")
         .takes_value(true)
@@ -932,6 +1109,7 @@ Ignore linting a file by adding an ignore comment at the top of the file:
         .help("Output lint result in JSON format")
         .takes_value(false),
     )
+    .arg(config_arg())
     .arg(
       Arg::with_name("files")
         .takes_value(true)
@@ -1037,6 +1215,59 @@ fn test_subcommand<'a, 'b>() -> App<'a, 'b> {
           Err(_) => Err("jobs should be a number".to_string()),
         }),
     )
+    .arg(
+      Arg::with_name("shard")
+        .long("shard")
+        .takes_value(true)
+        .require_equals(true)
+        .value_name("INDEX/COUNT")
+        .conflicts_with("watch")
+        .validator(|val: String| match parse_shard(&val) {
+          Some(_) => Ok(()),
+          None => Err(
+            "shard should be INDEX/COUNT, with 1 <= INDEX <= COUNT".to_string(),
+          ),
+        })
+        .help("Only run the INDEX-th of COUNT partitions of the test modules"),
+    )
+    .arg(
+      Arg::with_name("shuffle")
+        .long("shuffle")
+        .takes_value(true)
+        .require_equals(true)
+        .min_values(0)
+        .max_values(1)
+        .value_name("NUMBER")
+        .validator(|val: String| match val.parse::<u32>() {
+          Ok(_) => Ok(()),
+          Err(_) => {
+            Err("shuffle should be a 32-bit unsigned number".to_string())
+          }
+        })
+        .help("Shuffle the order in which the tests are run")
+        .long_help(
+          "Shuffle the order in which the test modules and the tests within
+them are run. The seed used is printed when tests fail, and can be passed
+to reproduce the order. Defaults to the value of --seed or a random seed.",
+        ),
+    )
+    .arg(
+      Arg::with_name("reporter")
+        .long("reporter")
+        .takes_value(true)
+        .require_equals(true)
+        .possible_values(&["pretty", "junit", "tap", "json"])
+        .default_value("pretty")
+        .help("Select the format of the test report"),
+    )
+    .arg(
+      Arg::with_name("reporter-output")
+        .long("reporter-output")
+        .takes_value(true)
+        .require_equals(true)
+        .value_name("FILE")
+        .help("Write the test report to a file instead of stdout"),
+    )
     .arg(
       Arg::with_name("files")
         .help("List of file names to run")
@@ -1065,6 +1296,41 @@ Directory arguments are expanded to all contained files matching the glob
     )
 }
 
+fn task_subcommand<'a, 'b>() -> App<'a, 'b> {
+  SubCommand::with_name("task")
+    .setting(AppSettings::TrailingVarArg)
+    .arg(config_arg())
+    .arg(
+      Arg::with_name("task")
+        .help("Task to be executed")
+        .takes_value(true)
+        .required(false),
+    )
+    .arg(
+      Arg::with_name("task_args")
+        .help("Additional arguments passed to the task")
+        .takes_value(true)
+        .multiple(true)
+        .allow_hyphen_values(true),
+    )
+    .about("Run a task defined in the configuration file")
+    .long_about(
+      "Run a task defined in the 'tasks' section of the configuration file.
+
+  deno task build
+  deno task test --fail-fast
+
+Tasks are command lines that are run with a small built-in shell, so they
+work the same on every platform. It supports '&&', '||' and ';', pipes,
+quoting, 'NAME=value' environment variable assignments and '$NAME'
+expansion. Additional arguments are appended to the task's command line.
+
+List the available tasks:
+
+  deno task",
+    )
+}
+
 fn types_subcommand<'a, 'b>() -> App<'a, 'b> {
   SubCommand::with_name("types")
     .about("Print runtime TypeScript declarations")
@@ -1213,6 +1479,19 @@ fn permission_args<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
         .long("prompt")
         .help("Fallback to prompt if required permission wasn't passed"),
     )
+    .arg(
+      Arg::with_name("permission-log")
+        .long("permission-log")
+        .takes_value(true)
+        .require_equals(true)
+        .value_name("FILE")
+        .help("Log every permission check to a file, as lines of JSON")
+        .long_help(
+          "Log every permission check to a file, as lines of JSON. Each line
+records the permission kind, the descriptor as shown in permission prompts,
+whether access was granted, and the module and stack the check came from.",
+        ),
+    )
 }
 
 fn runtime_args<'a, 'b>(
@@ -1408,7 +1687,13 @@ fn config_arg<'a, 'b>() -> Arg<'a, 'b> {
     .short("c")
     .long("config")
     .value_name("FILE")
-    .help("Load tsconfig.json configuration file")
+    .help("Load configuration file")
+    .long_help(
+      "Load configuration file. Besides TypeScript compiler options, the
+configuration file may contain 'fmt', 'lint' and 'test' settings and an
+'importMap'. If omitted, a 'deno.json' or 'deno.jsonc' file in the current
+directory or one of its parents is used.",
+    )
     .takes_value(true)
 }
 
@@ -1418,6 +1703,31 @@ fn no_remote_arg<'a, 'b>() -> Arg<'a, 'b> {
     .help("Do not resolve remote modules")
 }
 
+fn bench_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
+  runtime_args_parse(flags, matches, true, false);
+
+  let filter = matches.value_of("filter").map(String::from);
+  let json = matches.is_present("json");
+  let baseline = matches.value_of("baseline").map(PathBuf::from);
+  let save_baseline = matches.value_of("save-baseline").map(PathBuf::from);
+
+  if let Some(script_arg) = matches.values_of("script_arg") {
+    flags.argv.extend(script_arg.map(String::from));
+  }
+
+  let include = matches
+    .values_of("files")
+    .map(|files| files.map(String::from).collect());
+
+  flags.subcommand = DenoSubcommand::Bench {
+    include,
+    filter,
+    json,
+    baseline,
+    save_baseline,
+  };
+}
+
 fn bundle_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
   compile_args_parse(flags, matches);
 
@@ -1502,13 +1812,63 @@ fn coverage_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
     None => vec![],
   };
   let lcov = matches.is_present("lcov");
+  let html = matches.value_of("html").map(PathBuf::from);
+  let merge_out = matches.value_of("merge-out").map(PathBuf::from);
+  let mut thresholds = Vec::new();
+  for (name, kind) in &[
+    ("threshold-lines", CoverageThresholdKind::Lines),
+    ("threshold-branches", CoverageThresholdKind::Branches),
+    ("threshold-functions", CoverageThresholdKind::Functions),
+  ] {
+    if let Some(values) = matches.values_of(name) {
+      for value in values {
+        let (pattern, percent) = parse_threshold(value).unwrap();
+        thresholds.push(CoverageThreshold {
+          kind: *kind,
+          pattern,
+          percent,
+        });
+      }
+    }
+  }
   flags.subcommand = DenoSubcommand::Coverage {
     files,
     ignore,
     include,
     exclude,
     lcov,
+    html,
+    thresholds,
+    merge_out,
+  };
+}
+
+/// Parses a coverage threshold of the form "[PATTERN=]PERCENT", where
+/// PATTERN must be a valid regular expression.
+fn parse_threshold(value: &str) -> Result<(Option<String>, f64), String> {
+  let invalid = || {
+    "threshold should be a percentage between 0 and 100, optionally prefixed with a url pattern and '='"
+      .to_string()
+  };
+  let mut parts = value.rsplitn(2, '=');
+  let percent = parts
+    .next()
+    .and_then(|percent| percent.trim_end_matches('%').parse::<f64>().ok())
+    .ok_or_else(invalid)?;
+  if !(0.0..=100.0).contains(&percent) {
+    return Err(invalid());
+  }
+  let pattern = match parts.next() {
+    Some(pattern) if pattern.is_empty() => return Err(invalid()),
+    Some(pattern) => {
+      Regex::new(pattern).map_err(|err| {
+        format!("threshold pattern should be a valid regex: {}", err)
+      })?;
+      Some(pattern.to_string())
+    }
+    None => None,
   };
+  Ok((pattern, percent))
 }
 
 fn doc_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
@@ -1560,6 +1920,7 @@ fn eval_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
 }
 
 fn fmt_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
+  config_arg_parse(flags, matches);
   flags.watch = matches.is_present("watch");
   let files = match matches.values_of("files") {
     Some(f) => f.map(PathBuf::from).collect(),
@@ -1626,6 +1987,7 @@ fn lsp_parse(flags: &mut Flags, _matches: &clap::ArgMatches) {
 }
 
 fn lint_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
+  config_arg_parse(flags, matches);
   let files = match matches.values_of("files") {
     Some(f) => f.map(PathBuf::from).collect(),
     None => vec![],
@@ -1676,7 +2038,10 @@ fn run_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
   flags.subcommand = DenoSubcommand::Run { script };
 }
 
-fn test_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
+fn test_parse(
+  flags: &mut Flags,
+  matches: &clap::ArgMatches,
+) -> clap::Result<()> {
   runtime_args_parse(flags, matches, true, true);
 
   let no_run = matches.is_present("no-run");
@@ -1722,6 +2087,33 @@ fn test_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
     None
   };
 
+  let reporter = match matches.value_of("reporter") {
+    Some("junit") => TestReporterKind::Junit,
+    Some("tap") => TestReporterKind::Tap,
+    Some("json") => TestReporterKind::Json,
+    _ => TestReporterKind::Pretty,
+  };
+  let reporter_output = matches.value_of("reporter-output").map(PathBuf::from);
+  let shard = matches.value_of("shard").and_then(parse_shard);
+  let shuffle = if matches.is_present("shuffle") {
+    match matches.value_of("shuffle") {
+      Some(value) => Some(value.parse::<u32>().unwrap()),
+      // The seed is also used by the tests in JavaScript, where it has to be
+      // exact as a number.
+      None => match flags.seed {
+        Some(seed) => Some(u32::try_from(seed).map_err(|_| {
+          clap::Error::with_description(
+            "--seed should be a 32-bit unsigned number when used as the shuffle seed",
+            clap::ErrorKind::InvalidValue,
+          )
+        })?),
+        None => Some(rand::random()),
+      },
+    }
+  } else {
+    None
+  };
+
   flags.coverage_dir = matches.value_of("coverage").map(String::from);
   flags.subcommand = DenoSubcommand::Test {
     no_run,
@@ -1732,6 +2124,32 @@ fn test_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
     filter,
     allow_none,
     concurrent_jobs,
+    reporter,
+    reporter_output,
+    shard,
+    shuffle,
+  };
+  Ok(())
+}
+
+/// Parses a 1-based "INDEX/COUNT" shard specification.
+fn parse_shard(value: &str) -> Option<(usize, usize)> {
+  let mut parts = value.splitn(2, '/');
+  let index = parts.next()?.parse::<usize>().ok()?;
+  let count = parts.next()?.parse::<usize>().ok()?;
+  if index == 0 || index > count {
+    return None;
+  }
+  Some((index, count))
+}
+
+fn task_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
+  config_arg_parse(flags, matches);
+  if let Some(task_args) = matches.values_of("task_args") {
+    flags.argv.extend(task_args.map(String::from));
+  }
+  flags.subcommand = DenoSubcommand::Task {
+    task: matches.value_of("task").map(String::from),
   };
 }
 
@@ -1830,6 +2248,9 @@ fn permission_args_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
   if matches.is_present("prompt") {
     flags.prompt = true;
   }
+  if let Some(permission_log) = matches.value_of("permission-log") {
+    flags.permission_log = Some(PathBuf::from(permission_log));
+  }
 }
 
 fn runtime_args_parse(
@@ -2302,6 +2723,68 @@ mod tests {
     );
   }
 
+  #[test]
+  fn fmt_with_config() {
+    let r = flags_from_vec(svec!["deno", "fmt", "--config", "deno.json"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Fmt {
+          ignore: vec![],
+          check: false,
+          files: vec![],
+          ext: "ts".to_string(),
+        },
+        config_path: Some("deno.json".to_string()),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn task() {
+    let r = flags_from_vec(svec!["deno", "task", "build"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Task {
+          task: Some("build".to_string()),
+        },
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "task",
+      "--config",
+      "deno.json",
+      "test",
+      "--fail-fast",
+      "foo"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Task {
+          task: Some("test".to_string()),
+        },
+        argv: svec!["--fail-fast", "foo"],
+        config_path: Some("deno.json".to_string()),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "task"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Task { task: None },
+        ..Flags::default()
+      }
+    );
+  }
+
   #[test]
   fn lsp() {
     let r = flags_from_vec(svec!["deno", "lsp"]);
@@ -2378,6 +2861,21 @@ mod tests {
         ..Flags::default()
       }
     );
+
+    let r = flags_from_vec(svec!["deno", "lint", "-c", "deno.jsonc"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Lint {
+          files: vec![],
+          rules: false,
+          json: false,
+          ignore: vec![],
+        },
+        config_path: Some("deno.jsonc".to_string()),
+        ..Flags::default()
+      }
+    );
   }
 
   #[test]
@@ -2700,6 +3198,26 @@ mod tests {
     );
   }
 
+  #[test]
+  fn permission_log() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--permission-log=permissions.jsonl",
+      "script.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        permission_log: Some(PathBuf::from("permissions.jsonl")),
+        subcommand: DenoSubcommand::Run {
+          script: "script.ts".to_string(),
+        },
+        ..Flags::default()
+      }
+    );
+  }
+
   #[test]
   fn allow_write_allowlist() {
     use tempfile::TempDir;
@@ -2792,6 +3310,53 @@ mod tests {
     assert!(r.is_err());
   }
 
+  #[test]
+  fn bench_with_flags() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "bench",
+      "--filter",
+      "sort",
+      "--json",
+      "--baseline=base.json",
+      "--save-baseline=new.json",
+      "--allow-read",
+      "dir1/",
+      "--",
+      "arg1"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Bench {
+          include: Some(svec!["dir1/"]),
+          filter: Some("sort".to_string()),
+          json: true,
+          baseline: Some(PathBuf::from("base.json")),
+          save_baseline: Some(PathBuf::from("new.json")),
+        },
+        allow_read: Some(vec![]),
+        argv: svec!["arg1"],
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "bench"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Bench {
+          include: None,
+          filter: None,
+          json: false,
+          baseline: None,
+          save_baseline: None,
+        },
+        ..Flags::default()
+      }
+    );
+  }
+
   #[test]
   fn bundle() {
     let r = flags_from_vec(svec!["deno", "bundle", "source.ts"]);
@@ -3379,6 +3944,10 @@ mod tests {
           quiet: false,
           include: Some(svec!["dir1/", "dir2/"]),
           concurrent_jobs: 1,
+          reporter: TestReporterKind::Pretty,
+          reporter_output: None,
+          shard: None,
+          shuffle: None,
         },
         unstable: true,
         coverage_dir: Some("cov".to_string()),
@@ -3390,6 +3959,89 @@ mod tests {
     );
   }
 
+  #[test]
+  fn test_reporter() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "test",
+      "--reporter=junit",
+      "--reporter-output=report.xml"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Test {
+          no_run: false,
+          doc: false,
+          fail_fast: false,
+          filter: None,
+          allow_none: false,
+          quiet: false,
+          include: None,
+          concurrent_jobs: 1,
+          reporter: TestReporterKind::Junit,
+          reporter_output: Some(PathBuf::from("report.xml")),
+          shard: None,
+          shuffle: None,
+        },
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "test", "--reporter=xml"]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn test_shard_and_shuffle() {
+    let r =
+      flags_from_vec(svec!["deno", "test", "--shard=2/3", "--shuffle=42"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Test {
+          no_run: false,
+          doc: false,
+          fail_fast: false,
+          filter: None,
+          allow_none: false,
+          quiet: false,
+          include: None,
+          concurrent_jobs: 1,
+          reporter: TestReporterKind::Pretty,
+          reporter_output: None,
+          shard: Some((2, 3)),
+          shuffle: Some(42),
+        },
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "test", "--seed=7", "--shuffle"]);
+    let flags = r.unwrap();
+    assert_eq!(flags.seed, Some(7));
+    assert!(matches!(
+      flags.subcommand,
+      DenoSubcommand::Test {
+        shuffle: Some(7),
+        ..
+      }
+    ));
+
+    let r = flags_from_vec(svec!["deno", "test", "--shuffle=4294967296"]);
+    assert!(r.is_err());
+
+    let r =
+      flags_from_vec(svec!["deno", "test", "--seed=4294967296", "--shuffle"]);
+    assert!(r.is_err());
+
+    for shard in &["0/3", "4/3", "1", "a/b", "1/0"] {
+      let r =
+        flags_from_vec(svec!["deno", "test", format!("--shard={}", shard)]);
+      assert!(r.is_err(), "{}", shard);
+    }
+  }
+
   #[test]
   fn run_with_cafile() {
     let r = flags_from_vec(svec![
@@ -3656,6 +4308,115 @@ mod tests {
           include: vec![r"^file:".to_string()],
           exclude: vec![r"test\.(js|mjs|ts|jsx|tsx)$".to_string()],
           lcov: false,
+          html: None,
+          thresholds: vec![],
+          merge_out: None,
+        },
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn coverage_html() {
+    let r = flags_from_vec(svec!["deno", "coverage", "--html=html_cov", "cov"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Coverage {
+          files: vec![PathBuf::from("cov")],
+          ignore: vec![],
+          include: vec![r"^file:".to_string()],
+          exclude: vec![r"test\.(js|mjs|ts|jsx|tsx)$".to_string()],
+          lcov: false,
+          html: Some(PathBuf::from("html_cov")),
+          thresholds: vec![],
+          merge_out: None,
+        },
+        ..Flags::default()
+      }
+    );
+
+    let r =
+      flags_from_vec(svec!["deno", "coverage", "--lcov", "--html=out", "cov"]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn coverage_thresholds() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "coverage",
+      "--threshold-lines=80",
+      "--threshold-lines=src/core/=95.5%",
+      "--threshold-functions=50",
+      "cov"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Coverage {
+          files: vec![PathBuf::from("cov")],
+          ignore: vec![],
+          include: vec![r"^file:".to_string()],
+          exclude: vec![r"test\.(js|mjs|ts|jsx|tsx)$".to_string()],
+          lcov: false,
+          html: None,
+          thresholds: vec![
+            CoverageThreshold {
+              kind: CoverageThresholdKind::Lines,
+              pattern: None,
+              percent: 80.0,
+            },
+            CoverageThreshold {
+              kind: CoverageThresholdKind::Lines,
+              pattern: Some("src/core/".to_string()),
+              percent: 95.5,
+            },
+            CoverageThreshold {
+              kind: CoverageThresholdKind::Functions,
+              pattern: None,
+              percent: 50.0,
+            },
+          ],
+          merge_out: None,
+        },
+        ..Flags::default()
+      }
+    );
+
+    for threshold in &["101", "-1", "abc", "=50", "src/=", "src/(=50"] {
+      let r = flags_from_vec(svec![
+        "deno",
+        "coverage",
+        format!("--threshold-branches={}", threshold),
+        "cov"
+      ]);
+      assert!(r.is_err(), "{}", threshold);
+    }
+  }
+
+  #[test]
+  fn coverage_merge_out() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "coverage",
+      "--merge-out=merged.json",
+      "shard1",
+      "shard2"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Coverage {
+          files: vec![PathBuf::from("shard1"), PathBuf::from("shard2")],
+          ignore: vec![],
+          include: vec![r"^file:".to_string()],
+          exclude: vec![r"test\.(js|mjs|ts|jsx|tsx)$".to_string()],
+          lcov: false,
+          html: None,
+          thresholds: vec![],
+          merge_out: Some(PathBuf::from("merged.json")),
         },
         ..Flags::default()
       }
@@ -858,7 +858,7 @@ impl Inner {
 
     // TODO(lucacasonato): handle error properly
     let text_edits = tokio::task::spawn_blocking(move || {
-      let config = get_typescript_config();
+      let config = get_typescript_config(&Default::default());
       match format_file(&file_path, &file_text, config) {
         Ok(new_text) => {
           Some(text::get_edits(&file_text, &new_text, line_index))
@@ -38,14 +38,17 @@ mod tsc;
 mod unix_util;
 mod version;
 
+use crate::config_file::ConfigFile;
+use crate::config_file::FilesConfig;
 use crate::file_fetcher::File;
 use crate::file_watcher::ResolutionResult;
+use crate::flags::CoverageThreshold;
 use crate::flags::DenoSubcommand;
 use crate::flags::Flags;
+use crate::flags::TestReporterKind;
 use crate::fmt_errors::PrettyJsError;
 use crate::media_type::MediaType;
 use crate::module_graph::GraphBuilder;
-use crate::module_graph::Module;
 use crate::module_loader::CliModuleLoader;
 use crate::program_state::ProgramState;
 use crate::source_maps::apply_source_map;
@@ -61,6 +64,7 @@ use deno_core::serde_json::json;
 use deno_core::v8_set_flags;
 use deno_core::ModuleSpecifier;
 use deno_runtime::ops::worker_host::CreateWebWorkerCb;
+use deno_runtime::permission_log;
 use deno_runtime::permissions::Permissions;
 use deno_runtime::web_worker::WebWorker;
 use deno_runtime::web_worker::WebWorkerOptions;
@@ -68,7 +72,6 @@ use deno_runtime::worker::MainWorker;
 use deno_runtime::worker::WorkerOptions;
 use log::debug;
 use log::info;
-use std::collections::HashSet;
 use std::env;
 use std::io::Read;
 use std::io::Write;
@@ -233,6 +236,7 @@ pub fn create_main_worker(
     ops::runtime_compiler::init(js_runtime);
 
     if enable_testing {
+      ops::bench::init(js_runtime);
       ops::testing::init(js_runtime);
     }
 
@@ -476,8 +480,33 @@ async fn lsp_command() -> Result<(), AnyError> {
   lsp::start().await
 }
 
+/// Combines the files given on the command line with the `files` of a config
+/// file section. Files on the command line take precedence over the
+/// section's `include`, while its `exclude` always applies.
+fn resolve_files_config(
+  files: Vec<PathBuf>,
+  mut ignore: Vec<PathBuf>,
+  maybe_files_config: Option<(&ConfigFile, &FilesConfig)>,
+) -> Result<(Vec<PathBuf>, Vec<PathBuf>), AnyError> {
+  let (config_file, files_config) = match maybe_files_config {
+    Some(files_config) => files_config,
+    None => return Ok((files, ignore)),
+  };
+  let (config_files, config_ignore) =
+    files_config.resolve(config_file.dir())?;
+  ignore.extend(config_ignore);
+  if !files.is_empty() {
+    return Ok((files, ignore));
+  }
+  // An empty list would otherwise mean the current directory.
+  if config_files.is_empty() && !files_config.include.is_empty() {
+    return Err(generic_error("No target files found."));
+  }
+  Ok((config_files, ignore))
+}
+
 async fn lint_command(
-  _flags: Flags,
+  flags: Flags,
   files: Vec<PathBuf>,
   list_rules: bool,
   ignore: Vec<PathBuf>,
@@ -488,7 +517,18 @@ async fn lint_command(
     return Ok(());
   }
 
-  tools::lint::lint_files(files, ignore, json).await
+  let maybe_config_file = ConfigFile::discover(flags.config_path.as_ref())?;
+  let lint_config = match &maybe_config_file {
+    Some(config_file) => config_file.to_lint_config()?.unwrap_or_default(),
+    None => Default::default(),
+  };
+  let (files, ignore) = resolve_files_config(
+    files,
+    ignore,
+    maybe_config_file.as_ref().map(|c| (c, &lint_config.files)),
+  )?;
+
+  tools::lint::lint_files(files, ignore, json, lint_config.rules).await
 }
 
 async fn cache_command(
@@ -750,11 +790,23 @@ async fn format_command(
   check: bool,
   ext: String,
 ) -> Result<(), AnyError> {
+  let maybe_config_file = ConfigFile::discover(flags.config_path.as_ref())?;
+  let fmt_config = match &maybe_config_file {
+    Some(config_file) => config_file.to_fmt_config()?.unwrap_or_default(),
+    None => Default::default(),
+  };
+
   if args.len() == 1 && args[0].to_string_lossy() == "-" {
-    return tools::fmt::format_stdin(check, ext);
+    return tools::fmt::format_stdin(check, ext, fmt_config.options);
   }
 
-  tools::fmt::format(args, ignore, check, flags.watch).await?;
+  let (args, ignore) = resolve_files_config(
+    args,
+    ignore,
+    maybe_config_file.as_ref().map(|c| (c, &fmt_config.files)),
+  )?;
+  tools::fmt::format(args, ignore, check, flags.watch, fmt_config.options)
+    .await?;
   Ok(())
 }
 
@@ -922,6 +974,7 @@ async fn run_command(flags: Flags, script: String) -> Result<(), AnyError> {
   Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn coverage_command(
   flags: Flags,
   files: Vec<PathBuf>,
@@ -929,6 +982,9 @@ async fn coverage_command(
   include: Vec<String>,
   exclude: Vec<String>,
   lcov: bool,
+  html: Option<PathBuf>,
+  thresholds: Vec<CoverageThreshold>,
+  merge_out: Option<PathBuf>,
 ) -> Result<(), AnyError> {
   if files.is_empty() {
     println!("No matching coverage profiles found");
@@ -942,10 +998,77 @@ async fn coverage_command(
     include,
     exclude,
     lcov,
+    html,
+    thresholds,
+    merge_out,
   )
   .await
 }
 
+async fn task_command(
+  flags: Flags,
+  task: Option<String>,
+) -> Result<(), AnyError> {
+  let config_file = match ConfigFile::discover(flags.config_path.as_ref())? {
+    Some(config_file) => config_file,
+    None => {
+      return Err(generic_error(
+        "No config file found. Create a \"deno.json\" file with a \"tasks\" section or pass \"--config\".",
+      ))
+    }
+  };
+  let exit_code = tools::task::execute_task(&config_file, task, &flags.argv)?;
+  if exit_code != 0 {
+    std::process::exit(exit_code);
+  }
+  Ok(())
+}
+
+async fn bench_command(
+  flags: Flags,
+  include: Option<Vec<String>>,
+  filter: Option<String>,
+  json: bool,
+  baseline: Option<PathBuf>,
+  save_baseline: Option<PathBuf>,
+) -> Result<(), AnyError> {
+  let program_state = ProgramState::build(flags.clone()).await?;
+  let include = include.unwrap_or_else(|| vec![".".to_string()]);
+  let cwd = std::env::current_dir().expect("No current directory");
+
+  let permissions = Permissions::from_options(&flags.clone().into());
+  let lib = if flags.unstable {
+    module_graph::TypeLib::UnstableDenoWindow
+  } else {
+    module_graph::TypeLib::DenoWindow
+  };
+
+  let bench_modules = test_runner::collect_test_module_specifiers(
+    include,
+    &[],
+    &cwd,
+    tools::bench::is_supported,
+  )?;
+
+  let failed = tools::bench::run_benchmarks(
+    program_state,
+    permissions,
+    lib,
+    bench_modules,
+    filter,
+    json,
+    baseline,
+    save_baseline,
+  )
+  .await?;
+
+  if failed {
+    std::process::exit(1);
+  }
+
+  Ok(())
+}
+
 #[allow(clippy::too_many_arguments)]
 async fn test_command(
   flags: Flags,
@@ -957,6 +1080,10 @@ async fn test_command(
   allow_none: bool,
   filter: Option<String>,
   concurrent_jobs: usize,
+  reporter: TestReporterKind,
+  reporter_output: Option<PathBuf>,
+  shard: Option<(usize, usize)>,
+  shuffle: Option<u32>,
 ) -> Result<(), AnyError> {
   if let Some(ref coverage_dir) = flags.coverage_dir {
     std::fs::create_dir_all(&coverage_dir)?;
@@ -970,7 +1097,24 @@ async fn test_command(
 
   let program_state = ProgramState::build(flags.clone()).await?;
 
-  let include = include.unwrap_or_else(|| vec![".".to_string()]);
+  let test_config = match &program_state.maybe_config_file {
+    Some(config_file) => config_file.to_test_config()?.unwrap_or_default(),
+    None => Default::default(),
+  };
+  let (config_include, ignore) = match &program_state.maybe_config_file {
+    Some(config_file) => test_config.files.resolve(config_file.dir())?,
+    None => (Vec::new(), Vec::new()),
+  };
+  let include = include.unwrap_or_else(|| {
+    if test_config.files.include.is_empty() {
+      vec![".".to_string()]
+    } else {
+      config_include
+        .iter()
+        .map(|path| path.to_string_lossy().to_string())
+        .collect()
+    }
+  });
   let cwd = std::env::current_dir().expect("No current directory");
 
   let permissions = Permissions::from_options(&flags.clone().into());
@@ -989,16 +1133,17 @@ async fn test_command(
 
     let paths_to_watch: Vec<_> = include.iter().map(PathBuf::from).collect();
 
-    // TODO(caspervonb) clean this up.
     let resolver = |changed: Option<Vec<PathBuf>>| {
       let doc_modules_result = test_runner::collect_test_module_specifiers(
         include.clone(),
+        &ignore,
         &cwd,
         fs_util::is_supported_ext,
       );
 
       let test_modules_result = test_runner::collect_test_module_specifiers(
         include.clone(),
+        &ignore,
         &cwd,
         tools::test_runner::is_supported,
       );
@@ -1011,19 +1156,8 @@ async fn test_command(
       let files_changed = changed.is_some();
       async move {
         let doc_modules = if doc { doc_modules_result? } else { Vec::new() };
-
         let test_modules = test_modules_result?;
 
-        let mut paths_to_watch = paths_to_watch_clone;
-        let mut modules_to_reload = if files_changed {
-          Vec::new()
-        } else {
-          test_modules
-            .iter()
-            .filter_map(|url| deno_core::resolve_url(url.as_str()).ok())
-            .collect()
-        };
-
         let mut builder = GraphBuilder::new(
           handler,
           program_state.maybe_import_map.clone(),
@@ -1034,80 +1168,47 @@ async fn test_command(
         }
         let graph = builder.get_graph();
 
-        for specifier in doc_modules {
-          if let Ok(path) = specifier.to_file_path() {
-            paths_to_watch.push(path);
-          }
-        }
-
-        for specifier in test_modules {
-          fn get_dependencies<'a>(
-            graph: &'a module_graph::Graph,
-            module: &'a Module,
-            // This needs to be accessible to skip getting dependencies if they're already there,
-            // otherwise this will cause a stack overflow with circular dependencies
-            output: &mut HashSet<&'a ModuleSpecifier>,
-          ) -> Result<(), AnyError> {
-            for dep in module.dependencies.values() {
-              if let Some(specifier) = &dep.maybe_code {
-                if !output.contains(specifier) {
-                  output.insert(specifier);
-
-                  get_dependencies(
-                    &graph,
-                    graph.get_specifier(specifier)?,
-                    output,
-                  )?;
-                }
-              }
-              if let Some(specifier) = &dep.maybe_type {
-                if !output.contains(specifier) {
-                  output.insert(specifier);
-
-                  get_dependencies(
-                    &graph,
-                    graph.get_specifier(specifier)?,
-                    output,
-                  )?;
-                }
-              }
-            }
-
-            Ok(())
-          }
-
-          // This test module and all it's dependencies
-          let mut modules = HashSet::new();
-          modules.insert(&specifier);
-          get_dependencies(
+        let (test_paths_to_watch, affected_test_modules) =
+          test_runner::get_affected_test_modules(
             &graph,
-            graph.get_specifier(&specifier)?,
-            &mut modules,
+            &test_modules,
+            changed.as_deref().unwrap_or_default(),
           )?;
 
-          paths_to_watch.extend(
-            modules
-              .iter()
-              .filter_map(|specifier| specifier.to_file_path().ok()),
-          );
+        let mut paths_to_watch = paths_to_watch_clone;
+        paths_to_watch.extend(test_paths_to_watch);
+        paths_to_watch.extend(
+          doc_modules
+            .iter()
+            .filter_map(|specifier| specifier.to_file_path().ok()),
+        );
 
-          if let Some(changed) = &changed {
-            for path in changed.iter().filter_map(|path| {
-              deno_core::resolve_url_or_path(&path.to_string_lossy()).ok()
-            }) {
-              if modules.contains(&&path) {
-                modules_to_reload.push(specifier);
-                break;
-              }
-            }
-          }
-        }
+        // Only the test modules that depend on a changed file are run again,
+        // while doc tests are extracted from the changed files themselves.
+        let (doc_modules_to_reload, test_modules_to_reload) = match &changed {
+          Some(changed) => (
+            doc_modules
+              .into_iter()
+              .filter(|specifier| {
+                specifier
+                  .to_file_path()
+                  .map_or(false, |path| changed.contains(&path))
+              })
+              .collect(),
+            affected_test_modules,
+          ),
+          None => (doc_modules, test_modules),
+        };
 
-        Ok((paths_to_watch, modules_to_reload))
+        Ok((
+          paths_to_watch,
+          (doc_modules_to_reload, test_modules_to_reload),
+        ))
       }
       .map(move |result| {
         if files_changed
-          && matches!(result, Ok((_, ref modules)) if modules.is_empty())
+          && matches!(result, Ok((_, (ref doc_modules, ref test_modules)))
+            if doc_modules.is_empty() && test_modules.is_empty())
         {
           ResolutionResult::Ignore
         } else {
@@ -1127,21 +1228,38 @@ async fn test_command(
       })
     };
 
+    let mut is_rerun = false;
     file_watcher::watch_func(
       resolver,
-      |modules_to_reload| {
+      |(doc_modules_to_reload, test_modules_to_reload)| {
+        if is_rerun {
+          let count =
+            doc_modules_to_reload.len() + test_modules_to_reload.len();
+          info!(
+            "{} Running {} affected test module{}\n",
+            colors::intense_blue("Watcher"),
+            count,
+            if count == 1 { "" } else { "s" },
+          );
+        }
+        is_rerun = true;
+
         test_runner::run_tests(
           program_state.clone(),
           permissions.clone(),
           lib.clone(),
-          modules_to_reload.clone(),
-          modules_to_reload,
+          doc_modules_to_reload,
+          test_modules_to_reload,
           no_run,
           fail_fast,
           quiet,
           true,
           filter.clone(),
           concurrent_jobs,
+          reporter.clone(),
+          reporter_output.clone(),
+          None,
+          shuffle,
         )
         .map(|res| res.map(|_| ()))
       },
@@ -1152,6 +1270,7 @@ async fn test_command(
     let doc_modules = if doc {
       test_runner::collect_test_module_specifiers(
         include.clone(),
+        &ignore,
         &cwd,
         fs_util::is_supported_ext,
       )?
@@ -1161,6 +1280,7 @@ async fn test_command(
 
     let test_modules = test_runner::collect_test_module_specifiers(
       include.clone(),
+      &ignore,
       &cwd,
       tools::test_runner::is_supported,
     )?;
@@ -1177,6 +1297,10 @@ async fn test_command(
       allow_none,
       filter,
       concurrent_jobs,
+      reporter,
+      reporter_output,
+      shard,
+      shuffle,
     )
     .await?;
 
@@ -1216,6 +1340,14 @@ fn get_subcommand(
   flags: Flags,
 ) -> Pin<Box<dyn Future<Output = Result<(), AnyError>>>> {
   match flags.clone().subcommand {
+    DenoSubcommand::Bench {
+      include,
+      filter,
+      json,
+      baseline,
+      save_baseline,
+    } => bench_command(flags, include, filter, json, baseline, save_baseline)
+      .boxed_local(),
     DenoSubcommand::Bundle {
       source_file,
       out_file,
@@ -1246,8 +1378,13 @@ fn get_subcommand(
       include,
       exclude,
       lcov,
-    } => coverage_command(flags, files, ignore, include, exclude, lcov)
-      .boxed_local(),
+      html,
+      thresholds,
+      merge_out,
+    } => coverage_command(
+      flags, files, ignore, include, exclude, lcov, html, thresholds, merge_out,
+    )
+    .boxed_local(),
     DenoSubcommand::Fmt {
       check,
       files,
@@ -1275,6 +1412,7 @@ fn get_subcommand(
     } => lint_command(flags, files, rules, ignore, json).boxed_local(),
     DenoSubcommand::Repl => run_repl(flags).boxed_local(),
     DenoSubcommand::Run { script } => run_command(flags, script).boxed_local(),
+    DenoSubcommand::Task { task } => task_command(flags, task).boxed_local(),
     DenoSubcommand::Test {
       no_run,
       doc,
@@ -1284,6 +1422,10 @@ fn get_subcommand(
       allow_none,
       filter,
       concurrent_jobs,
+      reporter,
+      reporter_output,
+      shard,
+      shuffle,
     } => test_command(
       flags,
       include,
@@ -1294,6 +1436,10 @@ fn get_subcommand(
       allow_none,
       filter,
       concurrent_jobs,
+      reporter,
+      reporter_output,
+      shard,
+      shuffle,
     )
     .boxed_local(),
     DenoSubcommand::Completions { buf } => {
@@ -1371,5 +1517,9 @@ pub fn main() {
 
   logger::init(flags.log_level);
 
+  if let Some(permission_log) = &flags.permission_log {
+    unwrap_or_exit(permission_log::enable(permission_log));
+  }
+
   unwrap_or_exit(tokio_util::run_basic(get_subcommand(flags)));
 }
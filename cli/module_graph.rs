@@ -1314,6 +1314,29 @@ impl Graph {
     }
   }
 
+  /// Return the specifiers of all the modules that the module depends on,
+  /// both directly and transitively, including type only dependencies.
+  pub fn get_dependencies(
+    &self,
+    specifier: &ModuleSpecifier,
+  ) -> Result<HashSet<ModuleSpecifier>, AnyError> {
+    let mut dependencies = HashSet::new();
+    let mut pending = vec![specifier.clone()];
+    while let Some(specifier) = pending.pop() {
+      let module = self.get_specifier(&specifier)?;
+      for dep in module.dependencies.values() {
+        for dep_specifier in dep.maybe_code.iter().chain(dep.maybe_type.iter())
+        {
+          if dependencies.insert(dep_specifier.clone()) {
+            pending.push(dep_specifier.clone());
+          }
+        }
+      }
+    }
+
+    Ok(dependencies)
+  }
+
   /// Consume graph and return list of all module specifiers contained in the
   /// graph.
   pub fn get_modules(&self) -> Vec<ModuleSpecifier> {
@@ -2409,6 +2432,49 @@ pub mod tests {
     assert!(h.version_calls[1].1 == ver0 || h.version_calls[1].1 == ver1);
   }
 
+  #[tokio::test]
+  async fn test_graph_get_dependencies() {
+    let specifier = resolve_url_or_path("file:///a.ts").unwrap();
+    let graph = setup_memory(
+      specifier.clone(),
+      map!(
+        "/a.ts" => r#"
+        import { b } from "./b.ts";
+        import type { D } from "./d.ts";
+
+        console.log(b);
+      "#,
+        "/b.ts" => r#"
+        import { c } from "./c.ts";
+        export const b = c;
+      "#,
+        "/c.ts" => r#"
+        export * from "./b.ts";
+        export const c = "c";
+      "#,
+        "/d.ts" => r#"
+        export interface D {}
+      "#
+      ),
+    )
+    .await;
+    let mut dependencies: Vec<String> = graph
+      .get_dependencies(&specifier)
+      .expect("should have dependencies")
+      .iter()
+      .map(|s| s.to_string())
+      .collect();
+    dependencies.sort();
+    assert_eq!(
+      dependencies,
+      vec!["file:///b.ts", "file:///c.ts", "file:///d.ts"]
+    );
+    let dependencies = graph
+      .get_dependencies(&resolve_url_or_path("file:///d.ts").unwrap())
+      .expect("should have dependencies");
+    assert!(dependencies.is_empty());
+  }
+
   #[tokio::test]
   async fn test_graph_emit() {
     let specifier = resolve_url_or_path("file:///a.ts").unwrap();
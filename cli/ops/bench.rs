@@ -0,0 +1,63 @@
+// Copyright 2018-2021 the Deno authors. All rights reserved. MIT license.
+
+use crate::tools::bench::BenchEvent;
+use crate::tools::bench::BenchMessage;
+use deno_core::error::AnyError;
+use deno_core::JsRuntime;
+use deno_core::ModuleSpecifier;
+use deno_core::OpState;
+use serde::Deserialize;
+use std::sync::mpsc::Sender;
+use std::time::Instant;
+
+pub fn init(rt: &mut JsRuntime) {
+  rt.op_state()
+    .borrow_mut()
+    .put::<BenchStartTime>(BenchStartTime(Instant::now()));
+
+  super::reg_sync(rt, "op_bench_now", op_bench_now);
+  super::reg_sync(rt, "op_post_bench_message", op_post_bench_message);
+}
+
+struct BenchStartTime(Instant);
+
+/// Returns the number of nanoseconds since the worker was created. Unlike
+/// `performance.now()` this is never reduced in precision, as the timings of
+/// benchmarks are meaningless without it.
+fn op_bench_now(
+  state: &mut OpState,
+  _args: (),
+  _: (),
+) -> Result<f64, AnyError> {
+  let start_time = state.borrow::<BenchStartTime>();
+  Ok(start_time.0.elapsed().as_nanos() as f64)
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PostBenchMessageArgs {
+  message: BenchMessage,
+}
+
+fn op_post_bench_message(
+  state: &mut OpState,
+  args: PostBenchMessageArgs,
+  _: (),
+) -> Result<bool, AnyError> {
+  let origin = state.borrow::<ModuleSpecifier>().to_string();
+  let message = args.message;
+
+  let event = BenchEvent { origin, message };
+
+  // Test workers do not have a bench channel, so messages are dropped there.
+  let sender = match state.try_borrow::<Sender<BenchEvent>>() {
+    Some(sender) => sender.clone(),
+    None => return Ok(false),
+  };
+
+  if sender.send(event).is_err() {
+    Ok(false)
+  } else {
+    Ok(true)
+  }
+}
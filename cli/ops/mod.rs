@@ -1,5 +1,6 @@
 // Copyright 2018-2021 the Deno authors. All rights reserved. MIT license.
 
+pub mod bench;
 pub mod errors;
 pub mod runtime_compiler;
 pub mod testing;
@@ -5,10 +5,12 @@ use deno_core::error::AnyError;
 use deno_core::JsRuntime;
 use deno_core::ModuleSpecifier;
 use deno_core::OpState;
+use deno_runtime::metrics::RuntimeMetrics;
 use deno_runtime::ops::worker_host::create_worker_permissions;
 use deno_runtime::ops::worker_host::PermissionsArg;
 use deno_runtime::permissions::Permissions;
 use serde::Deserialize;
+use std::collections::BTreeMap;
 use std::sync::mpsc::Sender;
 use uuid::Uuid;
 
@@ -20,6 +22,7 @@ pub fn init(rt: &mut JsRuntime) {
     op_restore_test_permissions,
   );
   super::reg_sync(rt, "op_post_test_message", op_post_test_message);
+  super::reg_sync(rt, "op_get_test_pending_ops", op_get_test_pending_ops);
 }
 
 #[derive(Clone)]
@@ -89,3 +92,26 @@ fn op_post_test_message(
     Ok(true)
   }
 }
+
+/// Returns the number of dispatched async ops that have not completed yet, by
+/// op name, for the op sanitizer to attribute leaks to a test. Unlike
+/// `op_metrics` this does not require `--unstable`.
+fn op_get_test_pending_ops(
+  state: &mut OpState,
+  _args: (),
+  _: (),
+) -> Result<BTreeMap<&'static str, u64>, AnyError> {
+  let metrics = state.borrow::<RuntimeMetrics>();
+  let pending_ops = metrics
+    .ops
+    .iter()
+    .map(|(name, metrics)| {
+      (
+        *name,
+        metrics.ops_dispatched_async - metrics.ops_completed_async,
+      )
+    })
+    .filter(|(_, pending)| *pending > 0)
+    .collect();
+  Ok(pending_ops)
+}
@@ -96,15 +96,16 @@ impl ProgramState {
       None
     };
 
-    let maybe_config_file =
-      if let Some(config_path) = flags.config_path.as_ref() {
-        Some(ConfigFile::read(config_path)?)
-      } else {
-        None
-      };
+    let maybe_config_file = ConfigFile::discover(flags.config_path.as_ref())?;
 
+    // The `--import-map` flag takes precedence over the config file.
+    let maybe_import_map_path = flags.import_map_path.clone().or_else(|| {
+      maybe_config_file
+        .as_ref()
+        .and_then(|config_file| config_file.to_import_map_path())
+    });
     let maybe_import_map: Option<ImportMap> =
-      match flags.import_map_path.as_ref() {
+      match maybe_import_map_path.as_ref() {
         None => None,
         Some(import_map_url) => {
           let import_map_specifier =
@@ -0,0 +1,31 @@
+Deno.bench({
+  name: "sync",
+  warmup: 5,
+  n: 20,
+  fn() {
+    Array.from({ length: 100 }, (_, i) => i).reverse();
+  },
+});
+
+Deno.bench({
+  name: "async",
+  warmup: 5,
+  n: 20,
+  async fn() {
+    await Promise.resolve();
+  },
+});
+
+Deno.bench({
+  name: "ignored",
+  ignore: true,
+  fn() {},
+});
+
+Deno.bench({
+  name: "fail",
+  n: 1,
+  fn() {
+    throw new Error("broken");
+  },
+});
@@ -0,0 +1,7 @@
+// V8 counts offsets in UTF-16 code units, which differ from the UTF-8 byte
+// offsets of the source after these strings.
+const GREETINGS = ["こんにちは", "さようなら", "ありがとう", "おやすみなさい"];
+
+export function greet(formal: boolean): string {
+  return formal ? GREETINGS[0] : GREETINGS[1];
+}
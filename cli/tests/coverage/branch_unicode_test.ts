@@ -0,0 +1,5 @@
+import { greet } from "./branch_unicode.ts";
+
+Deno.test("greet", function () {
+  greet(true);
+});
@@ -215,6 +215,46 @@ mod integration {
     assert_eq!("Bye cached code", deno_out);
   }
 
+  #[test]
+  fn permission_log() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let log_path = temp_dir.path().join("permissions.jsonl");
+    let output = util::deno_cmd()
+      .current_dir(util::root_path())
+      .arg("run")
+      .arg(format!("--permission-log={}", log_path.display()))
+      .arg("--allow-read")
+      .arg("cli/tests/permission_log.ts")
+      .output()
+      .unwrap();
+    assert!(output.status.success());
+
+    let specifier =
+      url::Url::from_file_path(util::tests_path().join("permission_log.ts"))
+        .unwrap()
+        .to_string();
+    let log = fs::read_to_string(&log_path).unwrap();
+    let entries: Vec<serde_json::Value> = log
+      .lines()
+      .map(|line| serde_json::from_str(line).unwrap())
+      .filter(|entry: &serde_json::Value| entry["specifier"] == *specifier)
+      .collect();
+    assert_eq!(entries.len(), 3, "{}", log);
+
+    for entry in &entries[..2] {
+      assert_eq!(entry["kind"], "read");
+      assert!(entry["descriptor"].as_str().unwrap().ends_with("hello.txt"));
+      assert_eq!(entry["granted"], true);
+    }
+    let stack = entries[0]["stack"].as_array().unwrap();
+    assert!(stack[0].as_str().unwrap().starts_with("readHello ("));
+    assert!(stack[1].as_str().unwrap().starts_with(&specifier));
+
+    assert_eq!(entries[2]["kind"], "env");
+    assert_eq!(entries[2]["descriptor"], "HOME");
+    assert_eq!(entries[2]["granted"], false);
+  }
+
   #[test]
   fn no_color() {
     let output = util::deno_cmd()
@@ -1174,6 +1214,10 @@ mod integration {
       )
       .expect("error writing file");
       assert_contains!(stderr_lines.next().unwrap(), "Restarting");
+      assert_contains!(
+        stderr_lines.next().unwrap(),
+        "Running 1 affected test module"
+      );
       assert_contains!(stdout_lines.next().unwrap(), "running 1 test");
       assert_contains!(stdout_lines.next().unwrap(), "FAILED");
       while !stdout_lines.next().unwrap().contains("test result") {}
@@ -2674,6 +2718,132 @@ mod integration {
       exit_code: 0,
       output: "test/quiet_test.out",
     });
+
+    itest!(sanitizer {
+      args: "test --allow-read test/sanitizer_test.ts",
+      exit_code: 1,
+      output: "test/sanitizer.out",
+    });
+
+    itest!(shuffle {
+      args: "test --shuffle=42 test/shuffle_test.ts",
+      exit_code: 1,
+      output: "test/shuffle.out",
+    });
+
+    itest!(steps {
+      args: "test test/steps_test.ts",
+      exit_code: 1,
+      output: "test/steps.out",
+    });
+
+    itest!(reporter_junit {
+      args: "test --quiet --reporter=junit test/reporter_test.ts",
+      exit_code: 1,
+      output: "test/reporter_junit.out",
+    });
+
+    itest!(reporter_tap {
+      args: "test --quiet --reporter=tap test/reporter_test.ts",
+      exit_code: 1,
+      output: "test/reporter_tap.out",
+    });
+
+    itest!(reporter_json {
+      args: "test --quiet --reporter=json test/reporter_test.ts",
+      exit_code: 1,
+      output: "test/reporter_json.out",
+    });
+
+    itest!(reporter_steps_junit {
+      args: "test --quiet --reporter=junit test/steps_test.ts",
+      exit_code: 1,
+      output: "test/reporter_steps_junit.out",
+    });
+
+    itest!(reporter_steps_json {
+      args: "test --quiet --reporter=json test/steps_test.ts",
+      exit_code: 1,
+      output: "test/reporter_steps_json.out",
+    });
+
+    #[test]
+    fn reporter_output() {
+      let temp_dir = TempDir::new().expect("tempdir fail");
+      let report_path = temp_dir.path().join("report.xml");
+      let status = util::deno_cmd()
+        .current_dir(util::tests_path())
+        .arg("test")
+        .arg("--quiet")
+        .arg("--reporter=junit")
+        .arg(format!("--reporter-output={}", report_path.display()))
+        .arg("test/reporter_test.ts")
+        .stdout(std::process::Stdio::null())
+        .spawn()
+        .unwrap()
+        .wait()
+        .unwrap();
+      assert!(!status.success());
+      let report = std::fs::read_to_string(report_path).unwrap();
+      assert!(report.starts_with("<?xml"));
+      assert!(report.contains("<testcase name=\"pass\""));
+    }
+  }
+
+  mod bench {
+    use super::*;
+
+    itest!(basic {
+      args: "bench bench/basic_bench.ts",
+      exit_code: 1,
+      output: "bench/basic.out",
+    });
+
+    itest!(filter {
+      args: "bench --filter=sync bench/basic_bench.ts",
+      exit_code: 0,
+      output_str: Some("[WILDCARD]running 1 bench from [WILDCARD]\nbench sync ... 20 samples[WILDCARD]\n\nbench result: ok. 1 measured; 0 failed; 0 ignored; 3 filtered out ([WILDCARD])\n\n"),
+    });
+
+    #[test]
+    fn baseline() {
+      let temp_dir = TempDir::new().expect("tempdir fail");
+      let baseline_path = temp_dir.path().join("baseline.json");
+      let status = util::deno_cmd()
+        .current_dir(util::tests_path())
+        .arg("bench")
+        .arg("--quiet")
+        .arg("--filter=sync")
+        .arg(format!("--save-baseline={}", baseline_path.display()))
+        .arg("bench/basic_bench.ts")
+        .stdout(std::process::Stdio::null())
+        .spawn()
+        .unwrap()
+        .wait()
+        .unwrap();
+      assert!(status.success());
+
+      let output = util::deno_cmd()
+        .current_dir(util::tests_path())
+        .arg("bench")
+        .arg("--quiet")
+        .arg("--json")
+        .arg("--filter=sync")
+        .arg(format!("--baseline={}", baseline_path.display()))
+        .arg("bench/basic_bench.ts")
+        .output()
+        .unwrap();
+      assert!(output.status.success());
+      let report: serde_json::Value =
+        serde_json::from_slice(&output.stdout).unwrap();
+      let bench = &report["benches"][0];
+      assert_eq!(bench["name"], "sync");
+      assert_eq!(bench["result"], "ok");
+      assert_eq!(bench["stats"]["samples"], 20);
+      assert!(bench["change"].is_number());
+      assert_eq!(report["summary"]["measured"], 1);
+      assert_eq!(report["summary"]["filteredOut"], 3);
+    }
   }
 
   #[test]
@@ -3312,6 +3482,49 @@ console.log("finish");
     output_str: Some("Not formatted stdin\n"),
   });
 
+  itest!(fmt_stdin_with_config {
+    args: "fmt --config fmt_with_config/deno.jsonc -",
+    input: Some("function f() {\n  return \"a\"\n}\n"),
+    output_str: Some("function f() {\n    return 'a';\n}\n"),
+  });
+
+  itest!(task_list {
+    args: "task --config task/deno.json",
+    output: "task/list.out",
+  });
+
+  itest!(task_not_found {
+    args: "task --config task/deno.json missing",
+    output: "task/not_found.out",
+    exit_code: 1,
+  });
+
+  itest!(task_echo {
+    args: "task --config task/deno.json echo",
+    output: "task/echo.out",
+  });
+
+  itest!(task_env {
+    args: "task --config task/deno.json env",
+    output: "task/env.out",
+  });
+
+  itest!(task_args {
+    args: "task --config task/deno.json args one --two",
+    output: "task/args.out",
+  });
+
+  itest!(task_fail {
+    args: "task --config task/deno.json fail",
+    output: "task/fail.out",
+    exit_code: 3,
+  });
+
+  itest!(task_fallback {
+    args: "task --config task/deno.json fallback",
+    output: "task/fallback.out",
+  });
+
   itest!(config {
     args: "run --reload --config config.tsconfig.json config.ts",
     exit_code: 1,
@@ -4516,6 +4729,83 @@ console.log("finish");
       assert!(output.status.success());
     }
 
+    #[test]
+    fn branch_unicode() {
+      let tempdir = TempDir::new().expect("tempdir fail");
+      let status = util::deno_cmd()
+        .current_dir(util::root_path())
+        .arg("test")
+        .arg("--quiet")
+        .arg("--unstable")
+        .arg(format!("--coverage={}", tempdir.path().to_str().unwrap()))
+        .arg("cli/tests/coverage/branch_unicode_test.ts")
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::inherit())
+        .status()
+        .expect("failed to spawn test runner");
+
+      assert!(status.success());
+
+      let output = util::deno_cmd()
+        .current_dir(util::root_path())
+        .arg("coverage")
+        .arg("--quiet")
+        .arg("--unstable")
+        .arg(format!("{}/", tempdir.path().to_str().unwrap()))
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::inherit())
+        .output()
+        .expect("failed to spawn coverage reporter");
+
+      let actual =
+        util::strip_ansi_codes(std::str::from_utf8(&output.stdout).unwrap())
+          .to_string();
+
+      let expected = fs::read_to_string(
+        util::root_path()
+          .join("cli/tests/coverage/expected_branch_unicode.out"),
+      )
+      .unwrap();
+
+      if !util::wildcard_match(&expected, &actual) {
+        println!("OUTPUT\n{}\nOUTPUT", actual);
+        println!("EXPECTED\n{}\nEXPECTED", expected);
+        panic!("pattern match failed");
+      }
+
+      assert!(output.status.success());
+
+      let output = util::deno_cmd()
+        .current_dir(util::root_path())
+        .arg("coverage")
+        .arg("--quiet")
+        .arg("--unstable")
+        .arg("--lcov")
+        .arg(format!("{}/", tempdir.path().to_str().unwrap()))
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::inherit())
+        .output()
+        .expect("failed to spawn coverage reporter");
+
+      let actual =
+        util::strip_ansi_codes(std::str::from_utf8(&output.stdout).unwrap())
+          .to_string();
+
+      let expected = fs::read_to_string(
+        util::root_path()
+          .join("cli/tests/coverage/expected_branch_unicode.lcov"),
+      )
+      .unwrap();
+
+      if !util::wildcard_match(&expected, &actual) {
+        println!("OUTPUT\n{}\nOUTPUT", actual);
+        println!("EXPECTED\n{}\nEXPECTED", expected);
+        panic!("pattern match failed");
+      }
+
+      assert!(output.status.success());
+    }
+
     #[test]
     fn complex() {
       let tempdir = TempDir::new().expect("tempdir fail");
@@ -4590,6 +4880,187 @@ console.log("finish");
 
       assert!(output.status.success());
     }
+
+    #[test]
+    fn html() {
+      let tempdir = TempDir::new().expect("tempdir fail");
+      let profile_dir = tempdir.path().join("cov");
+      let html_dir = tempdir.path().join("html");
+      let status = util::deno_cmd()
+        .current_dir(util::root_path())
+        .arg("test")
+        .arg("--quiet")
+        .arg("--unstable")
+        .arg(format!("--coverage={}", profile_dir.to_str().unwrap()))
+        .arg("cli/tests/coverage/complex_test.ts")
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::inherit())
+        .status()
+        .expect("failed to spawn test runner");
+
+      assert!(status.success());
+
+      let status = util::deno_cmd()
+        .current_dir(util::root_path())
+        .arg("coverage")
+        .arg("--quiet")
+        .arg("--unstable")
+        .arg(format!("--html={}", html_dir.to_str().unwrap()))
+        .arg(format!("{}/", profile_dir.to_str().unwrap()))
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::inherit())
+        .status()
+        .expect("failed to spawn coverage reporter");
+
+      assert!(status.success());
+
+      let index = fs::read_to_string(html_dir.join("index.html")).unwrap();
+      assert!(index.contains("<a href=\"complex.ts.html\">complex.ts</a>"));
+      assert!(index.contains("59.46% (22/37)"));
+      assert!(index.contains("50.00% (2/4)"));
+
+      // The page annotates the original TypeScript source.
+      let page = fs::read_to_string(html_dir.join("complex.ts.html")).unwrap();
+      assert!(page.contains("<a href=\"index.html\">All files</a>"));
+      assert!(page.contains("export function complex("));
+      assert!(page
+        .contains("<tr class=\"miss\"><td class=\"line-number\" id=\"L51\">"));
+    }
+
+    #[test]
+    fn merge() {
+      let tempdir = TempDir::new().expect("tempdir fail");
+      let shards = vec![tempdir.path().join("a"), tempdir.path().join("b")];
+      for shard in &shards {
+        let status = util::deno_cmd()
+          .current_dir(util::root_path())
+          .arg("test")
+          .arg("--quiet")
+          .arg("--unstable")
+          .arg(format!("--coverage={}", shard.to_str().unwrap()))
+          .arg("cli/tests/coverage/branch_test.ts")
+          .stdout(std::process::Stdio::piped())
+          .stderr(std::process::Stdio::inherit())
+          .status()
+          .expect("failed to spawn test runner");
+
+        assert!(status.success());
+      }
+
+      let merged = tempdir.path().join("merged.json");
+      let output = util::deno_cmd()
+        .current_dir(util::root_path())
+        .arg("coverage")
+        .arg("--quiet")
+        .arg("--unstable")
+        .arg("--lcov")
+        .arg(format!("--merge-out={}", merged.to_str().unwrap()))
+        .arg(format!("{}/", shards[0].to_str().unwrap()))
+        .arg(format!("{}/", shards[1].to_str().unwrap()))
+        .stderr(std::process::Stdio::inherit())
+        .output()
+        .expect("failed to spawn coverage reporter");
+
+      assert!(output.status.success());
+      let lcov = std::str::from_utf8(&output.stdout).unwrap().to_string();
+      assert_eq!(lcov.matches("SF:").count(), 1);
+      assert!(lcov.contains("FNDA:2,branch\n"));
+      assert!(lcov.contains("BRDA:2,0,0,2\n"));
+      assert!(lcov.contains("DA:3,4\n"));
+
+      // The test module is excluded from the report by default, but not from
+      // the merged profile.
+      let merged_profile = fs::read_to_string(&merged).unwrap();
+      assert!(merged_profile.contains("branch_test.ts"));
+
+      // The merged profile reports the same as the profiles it was made from.
+      let output = util::deno_cmd()
+        .current_dir(util::root_path())
+        .arg("coverage")
+        .arg("--quiet")
+        .arg("--unstable")
+        .arg("--lcov")
+        .arg(merged.to_str().unwrap())
+        .stderr(std::process::Stdio::inherit())
+        .output()
+        .expect("failed to spawn coverage reporter");
+
+      assert!(output.status.success());
+      assert_eq!(std::str::from_utf8(&output.stdout).unwrap(), lcov);
+    }
+
+    #[test]
+    fn thresholds() {
+      let tempdir = TempDir::new().expect("tempdir fail");
+      let status = util::deno_cmd()
+        .current_dir(util::root_path())
+        .arg("test")
+        .arg("--quiet")
+        .arg("--unstable")
+        .arg(format!("--coverage={}", tempdir.path().to_str().unwrap()))
+        .arg("cli/tests/coverage/complex_test.ts")
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::inherit())
+        .status()
+        .expect("failed to spawn test runner");
+
+      assert!(status.success());
+
+      let output = util::deno_cmd()
+        .current_dir(util::root_path())
+        .arg("coverage")
+        .arg("--quiet")
+        .arg("--unstable")
+        .arg("--lcov")
+        .arg("--threshold-lines=50")
+        .arg("--threshold-functions=50")
+        .arg(format!("{}/", tempdir.path().to_str().unwrap()))
+        .output()
+        .expect("failed to spawn coverage reporter");
+
+      assert!(output.status.success());
+
+      let output = util::deno_cmd()
+        .current_dir(util::root_path())
+        .arg("coverage")
+        .arg("--quiet")
+        .arg("--unstable")
+        .arg("--lcov")
+        .arg("--threshold-lines=50")
+        .arg("--threshold-lines=complex\\.ts$=60")
+        .arg(format!("{}/", tempdir.path().to_str().unwrap()))
+        .output()
+        .expect("failed to spawn coverage reporter");
+
+      assert!(!output.status.success());
+      let stderr =
+        util::strip_ansi_codes(std::str::from_utf8(&output.stderr).unwrap())
+          .to_string();
+      assert!(util::wildcard_match(
+        "error: Coverage is below the threshold:\n  file://[WILDCARD]/complex.ts: lines 59.459% (22/37) < 60%\n",
+        &stderr
+      ));
+
+      let output = util::deno_cmd()
+        .current_dir(util::root_path())
+        .arg("coverage")
+        .arg("--quiet")
+        .arg("--unstable")
+        .arg("--lcov")
+        .arg("--threshold-lines=60")
+        .arg(format!("{}/", tempdir.path().to_str().unwrap()))
+        .output()
+        .expect("failed to spawn coverage reporter");
+
+      assert!(!output.status.success());
+      let stderr =
+        util::strip_ansi_codes(std::str::from_utf8(&output.stderr).unwrap())
+          .to_string();
+      assert!(util::wildcard_match(
+        "error: Coverage is below the threshold:\n  all files: lines 59.459% (22/37) < 60%\n",
+        &stderr
+      ));
+    }
   }
 
   mod permissions {
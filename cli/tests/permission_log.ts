@@ -0,0 +1,11 @@
+function readHello() {
+  return Deno.readTextFileSync("cli/tests/hello.txt");
+}
+
+readHello();
+await Deno.readTextFile("cli/tests/hello.txt");
+try {
+  Deno.env.get("HOME");
+} catch {
+  // Denied checks are logged as well.
+}
@@ -0,0 +1,11 @@
+Deno.test("pass", function () {});
+
+Deno.test("fail <xml> & \"quotes\"", function () {
+  throw new Error("broken");
+});
+
+Deno.test({
+  name: "skip",
+  ignore: true,
+  fn() {},
+});
@@ -0,0 +1,25 @@
+let timer: number;
+
+Deno.test("leak timer", function () {
+  timer = setTimeout(() => {}, 100000);
+});
+
+Deno.test({
+  name: "clear timer",
+  sanitizeOps: false,
+  fn() {
+    clearTimeout(timer);
+  },
+});
+
+Deno.test("leak file", function () {
+  Deno.openSync(new URL(import.meta.url));
+});
+
+Deno.test({
+  name: "leak file without sanitizer",
+  sanitizeResources: false,
+  fn() {
+    Deno.openSync(new URL(import.meta.url));
+  },
+});
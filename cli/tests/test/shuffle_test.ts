@@ -0,0 +1,7 @@
+for (let i = 1; i <= 5; i++) {
+  Deno.test(`test ${i}`, () => {
+    if (i === 3) {
+      throw new Error("failure");
+    }
+  });
+}
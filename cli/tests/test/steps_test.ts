@@ -0,0 +1,27 @@
+Deno.test("passing steps", async (t) => {
+  await t.step("step 1", async (t) => {
+    await t.step("inner 1", () => {});
+    await t.step("inner 2", () => {});
+  });
+  await t.step({
+    name: "step 2",
+    ignore: true,
+    fn() {},
+  });
+});
+
+Deno.test("failing step", async (t) => {
+  const passed = await t.step("step 1", async (t) => {
+    await t.step("inner 1", () => {
+      throw new Error("inner failure");
+    });
+  });
+  if (passed) {
+    throw new Error("step 1 should have failed");
+  }
+  await t.step("step 2", () => {});
+});
+
+Deno.test("step not awaited", (t) => {
+  t.step("step 1", () => new Promise((resolve) => setTimeout(resolve, 50)));
+});
@@ -2,7 +2,10 @@
 import {
   assert,
   assertEquals,
+  assertThrows,
   assertThrowsAsync,
+  deferred,
+  fail,
   unitTest,
 } from "./test_util.ts";
 
@@ -202,6 +205,92 @@ unitTest(
   },
 );
 
+unitTest(
+  { perms: { read: true, net: true, run: true } },
+  async function httpServerHttp2OverTls(): Promise<void> {
+    const hostname = "localhost";
+    const port = 4501;
+
+    const listener = Deno.listenTls({
+      hostname,
+      port,
+      certFile: "cli/tests/tls/localhost.crt",
+      keyFile: "cli/tests/tls/localhost.key",
+      alpnProtocols: ["h2", "http/1.1"],
+    });
+    const promise = (async () => {
+      const conn = await listener.accept();
+      const httpConn = Deno.serveHttp(conn);
+      const evt = await httpConn.nextRequest();
+      assert(evt);
+      await evt.respondWith(new Response("Hello World"));
+      assertEquals(await httpConn.nextRequest(), null);
+      listener.close();
+    })();
+
+    const curl = Deno.run({
+      cmd: [
+        "curl",
+        "--silent",
+        "--http2",
+        "--cacert",
+        "cli/tests/tls/RootCA.pem",
+        "--write-out",
+        "\n%{http_version}",
+        `https://${hostname}:${port}/`,
+      ],
+      stdout: "piped",
+    });
+    const [status, output] = await Promise.all([curl.status(), curl.output()]);
+    curl.close();
+    assert(status.success);
+    assertEquals(new TextDecoder().decode(output), "Hello World\n2");
+    await promise;
+  },
+);
+
+unitTest(
+  { perms: { read: true, net: true } },
+  async function httpServerMutualTls(): Promise<void> {
+    const hostname = "localhost";
+    const port = 4501;
+
+    const listener = Deno.listenTls({
+      hostname,
+      port,
+      certFile: "cli/tests/tls/localhost.crt",
+      keyFile: "cli/tests/tls/localhost.key",
+      clientCaFile: "cli/tests/tls/RootCA.pem",
+      requireClientCert: true,
+    });
+    const promise = (async () => {
+      const conn = await listener.accept();
+      // The connection is handed over to the HTTP server, so the certificate
+      // has to be read first.
+      const [clientCert] = await conn.peerCertificates();
+      const httpConn = Deno.serveHttp(conn);
+      const evt = await httpConn.nextRequest();
+      assert(evt);
+      await evt.respondWith(new Response(`${clientCert.byteLength > 0}`));
+      assertEquals(await httpConn.nextRequest(), null);
+      listener.close();
+    })();
+
+    const client = Deno.createHttpClient({
+      caData: Deno.readTextFileSync("cli/tests/tls/RootCA.pem"),
+      certChain: Deno.readTextFileSync("cli/tests/tls/client.crt"),
+      privateKey: Deno.readTextFileSync("cli/tests/tls/client.key"),
+    });
+    const resp = await fetch(`https://${hostname}:${port}/`, {
+      client,
+      headers: { "connection": "close" },
+    });
+    assertEquals(await resp.text(), "true");
+    await promise;
+    client.close();
+  },
+);
+
 unitTest(
   { perms: { net: true } },
   async function httpServerRegressionHang() {
@@ -373,3 +462,186 @@ unitTest(
     await delay(300);
   },
 );
+
+unitTest({ perms: { net: true } }, async function httpServerWebSocket() {
+  const serverClosed = deferred();
+  const promise = (async () => {
+    const listener = Deno.listen({ port: 4501 });
+    const conn = await listener.accept();
+    listener.close();
+    const httpConn = Deno.serveHttp(conn);
+    const event = await httpConn.nextRequest();
+    assert(event);
+    const { request, respondWith } = event;
+    const { response, socket } = Deno.upgradeWebSocket(request);
+    socket.onerror = () => fail();
+    socket.onmessage = (m) => {
+      socket.send(m.data);
+      socket.close();
+    };
+    socket.onclose = () => serverClosed.resolve();
+    await respondWith(response);
+    assertEquals(await httpConn.nextRequest(), null);
+  })();
+
+  const def = deferred();
+  const ws = new WebSocket("ws://localhost:4501");
+  ws.onmessage = (m) => assertEquals(m.data, "foo");
+  ws.onerror = () => fail();
+  ws.onclose = () => def.resolve();
+  ws.onopen = () => ws.send("foo");
+  await def;
+  await promise;
+  await serverClosed;
+});
+
+unitTest(function httpUpgradeWebSocketInvalidRequest() {
+  const request = new Request("http://localhost:4501", {
+    headers: {
+      "connection": "Upgrade",
+      "sec-websocket-key": "dGhlIHNhbXBsZSBub25jZQ==",
+    },
+  });
+  assertThrows(
+    () => Deno.upgradeWebSocket(request),
+    TypeError,
+    "'upgrade' header must be 'websocket'",
+  );
+
+  const upgrade = new Request("http://localhost:4501", {
+    headers: { "connection": "Upgrade", "upgrade": "websocket" },
+  });
+  assertThrows(
+    () => Deno.upgradeWebSocket(upgrade),
+    TypeError,
+    "'sec-websocket-key' header must be set",
+  );
+});
+
+unitTest(
+  { perms: { net: true } },
+  async function httpServerCompressesResponses() {
+    const body = "hello world ".repeat(100);
+    const promise = (async () => {
+      const listener = Deno.listen({ port: 4501 });
+      const conn = await listener.accept();
+      listener.close();
+      const httpConn = Deno.serveHttp(conn);
+      for (const headers of [{}, { "cache-control": "no-transform" }]) {
+        const event = await httpConn.nextRequest();
+        assert(event);
+        await event.respondWith(
+          new Response(body, {
+            headers: { "content-type": "text/plain", ...headers },
+          }),
+        );
+      }
+      httpConn.close();
+    })();
+
+    const conn = await Deno.connect({ port: 4501 });
+    const decoder = new TextDecoder();
+    const request = new TextEncoder().encode(
+      "GET / HTTP/1.1\r\nHost: 127.0.0.1:4501\r\nAccept-Encoding: gzip\r\n\r\n",
+    );
+    const buf = new Uint8Array(4096);
+
+    await conn.write(request);
+    let nread = await conn.read(buf);
+    assert(nread);
+    let head = decoder.decode(buf.subarray(0, nread)).split("\r\n\r\n")[0];
+    assert(head.includes("content-encoding: gzip"));
+    assert(head.includes("vary: Accept-Encoding"));
+    assert(!head.includes("content-length: 1200"));
+
+    await conn.write(request);
+    nread = await conn.read(buf);
+    assert(nread);
+    head = decoder.decode(buf.subarray(0, nread)).split("\r\n\r\n")[0];
+    assert(!head.includes("content-encoding"));
+    assert(head.includes("content-length: 1200"));
+
+    conn.close();
+    await promise;
+  },
+);
+
+unitTest(
+  { perms: { net: true } },
+  async function httpServerCompressedResponseRoundTrip() {
+    const body = "hello world ".repeat(100);
+    const promise = (async () => {
+      const listener = Deno.listen({ port: 4501 });
+      const conn = await listener.accept();
+      listener.close();
+      const httpConn = Deno.serveHttp(conn);
+      const event = await httpConn.nextRequest();
+      assert(event);
+      const stream = new ReadableStream({
+        start(controller) {
+          controller.enqueue(new TextEncoder().encode(body));
+          controller.enqueue(new TextEncoder().encode(body));
+          controller.close();
+        },
+      });
+      await event.respondWith(
+        new Response(stream, { headers: { "content-type": "text/plain" } }),
+      );
+      httpConn.close();
+    })();
+
+    const resp = await fetch("http://127.0.0.1:4501/", {
+      headers: { "accept-encoding": "gzip, br" },
+    });
+    assertEquals(resp.headers.get("vary"), "Accept-Encoding");
+    assertEquals(await resp.text(), body + body);
+    await promise;
+  },
+);
+
+unitTest(
+  { perms: { net: true } },
+  async function httpServerShutdownDrainsInFlightResponses() {
+    const promise = (async () => {
+      const listener = Deno.listen({ port: 4501 });
+      const conn = await listener.accept();
+      listener.close();
+      const httpConn = Deno.serveHttp(conn);
+      const event = await httpConn.nextRequest();
+      assert(event);
+      const shutdown = httpConn.shutdown();
+      await event.respondWith(new Response("hello"));
+      await shutdown;
+      assertEquals(await httpConn.nextRequest(), null);
+    })();
+
+    const resp = await fetch("http://127.0.0.1:4501/");
+    assertEquals(await resp.text(), "hello");
+    await promise;
+  },
+);
+
+unitTest(
+  { perms: { net: true } },
+  async function httpServerShutdownTimeout() {
+    const promise = (async () => {
+      const listener = Deno.listen({ port: 4501 });
+      const conn = await listener.accept();
+      listener.close();
+      const httpConn = Deno.serveHttp(conn);
+      const event = await httpConn.nextRequest();
+      assert(event);
+      await httpConn.shutdown({ timeout: 100 });
+      await assertThrowsAsync(
+        () => event.respondWith(new Response("too late")),
+        Deno.errors.BadResource,
+      );
+    })();
+
+    await assertThrowsAsync(async () => {
+      const resp = await fetch("http://127.0.0.1:4501/");
+      await resp.text();
+    });
+    await promise;
+  },
+);
@@ -0,0 +1,404 @@
+// Copyright 2018-2021 the Deno authors. All rights reserved. MIT license.
+import {
+  assert,
+  assertEquals,
+  assertThrows,
+  assertThrowsAsync,
+  unitTest,
+} from "./test_util.ts";
+
+const decoder = new TextDecoder();
+
+unitTest({ perms: { read: true } }, function spawnPermissions(): void {
+  assertThrows(() => {
+    Deno.spawnChild(Deno.execPath(), { args: ["eval", "1"] });
+  }, Deno.errors.PermissionDenied);
+});
+
+unitTest(
+  { perms: { run: true, read: true } },
+  async function spawnCollectsOutput(): Promise<void> {
+    const { status, stdout, stderr } = await Deno.spawn(Deno.execPath(), {
+      args: [
+        "eval",
+        "console.log('hello'); console.error('world'); Deno.exit(3)",
+      ],
+    });
+    assertEquals(status, { success: false, code: 3, signal: null });
+    assertEquals(decoder.decode(stdout!), "hello\n");
+    assertEquals(decoder.decode(stderr!), "world\n");
+  },
+);
+
+unitTest(
+  { perms: { run: true, read: true } },
+  async function spawnStdoutNull(): Promise<void> {
+    const { status, stdout, stderr } = await Deno.spawn(Deno.execPath(), {
+      args: ["eval", "console.log('hello')"],
+      stdout: "null",
+    });
+    assert(status.success);
+    assertEquals(stdout, null);
+    assertEquals(stderr!.length, 0);
+  },
+);
+
+unitTest(
+  { perms: { run: true, read: true } },
+  async function spawnChildPipedStdin(): Promise<void> {
+    const child = Deno.spawnChild(Deno.execPath(), {
+      args: [
+        "eval",
+        "const buf = new Uint8Array(5); await Deno.stdin.read(buf);" +
+        " await Deno.stdout.write(buf);",
+      ],
+      stdin: "piped",
+    });
+    await child.stdin!.write(new TextEncoder().encode("hello"));
+    child.stdin!.close();
+    const { status, stdout } = await child.output();
+    assert(status.success);
+    assertEquals(decoder.decode(stdout!), "hello");
+    child.close();
+  },
+);
+
+unitTest(
+  { ignore: Deno.build.os === "windows", perms: { run: true, read: true } },
+  async function spawnClearEnv(): Promise<void> {
+    const { stdout } = await Deno.spawn(Deno.execPath(), {
+      args: [
+        "eval",
+        "-p",
+        "JSON.stringify(Deno.env.toObject())",
+      ],
+      clearEnv: true,
+      env: { FOO: "bar" },
+    });
+    assertEquals(JSON.parse(decoder.decode(stdout!)), { FOO: "bar" });
+  },
+);
+
+unitTest(
+  { perms: { run: true, read: true, write: true } },
+  async function spawnCwd(): Promise<void> {
+    const cwd = Deno.makeTempDirSync();
+    const { stdout } = await Deno.spawn(Deno.execPath(), {
+      args: ["eval", "-p", "Deno.cwd()"],
+      cwd,
+    });
+    assertEquals(decoder.decode(stdout!).trim(), Deno.realPathSync(cwd));
+    Deno.removeSync(cwd);
+  },
+);
+
+unitTest(
+  { perms: { run: true, read: true } },
+  async function spawnTimeout(): Promise<void> {
+    const start = Date.now();
+    const { status } = await Deno.spawn(Deno.execPath(), {
+      args: ["eval", "setTimeout(() => {}, 10000)"],
+      timeout: 100,
+    });
+    assert(Date.now() - start < 5000);
+    assert(!status.success);
+    if (Deno.build.os !== "windows") {
+      assertEquals(status.signal, 9);
+      assertEquals(status.code, 137);
+    }
+  },
+);
+
+unitTest(
+  { perms: { run: true, read: true } },
+  async function spawnAbortSignal(): Promise<void> {
+    const controller = new AbortController();
+    const child = Deno.spawnChild(Deno.execPath(), {
+      args: ["eval", "setTimeout(() => {}, 10000)"],
+      signal: controller.signal,
+    });
+    controller.abort();
+    const status = await child.status;
+    assert(!status.success);
+    child.close();
+
+    assertThrows(() => {
+      Deno.spawnChild(Deno.execPath(), {
+        args: ["eval", "1"],
+        signal: controller.signal,
+      });
+    }, DOMException);
+  },
+);
+
+unitTest(
+  { perms: { run: true, read: true } },
+  async function spawnChildKill(): Promise<void> {
+    const child = Deno.spawnChild(Deno.execPath(), {
+      args: ["eval", "setTimeout(() => {}, 10000)"],
+    });
+    child.kill(Deno.build.os === "windows" ? 9 : 15);
+    const status = await child.status;
+    assert(!status.success);
+    child.close();
+  },
+);
+
+unitTest(
+  { perms: { run: true, read: true } },
+  async function spawnChildCloseKillsChild(): Promise<void> {
+    const child = Deno.spawnChild(Deno.execPath(), {
+      args: ["eval", "setTimeout(() => {}, 10000)"],
+      stdout: "inherit",
+      stderr: "inherit",
+    });
+    const status = child.status;
+    // Let the wait start before the child is closed underneath it.
+    await new Promise((resolve) => setTimeout(resolve, 100));
+    child.close();
+    await assertThrowsAsync(() => status, Deno.errors.Interrupted);
+
+    // Signaling a closed child fails instead of hitting a recycled pid.
+    assertThrows(() => child.kill(9), Deno.errors.BadResource);
+  },
+);
+
+unitTest(
+  { perms: { run: true, read: true } },
+  async function spawnOutputNotPiped(): Promise<void> {
+    const child = Deno.spawnChild(Deno.execPath(), {
+      args: ["eval", "1"],
+      stdout: "null",
+      stderr: "null",
+    });
+    await assertThrowsAsync(() => child.output(), TypeError);
+    assert((await child.status).success);
+    child.close();
+  },
+);
+
+unitTest(
+  { ignore: Deno.build.os === "windows", perms: { run: true } },
+  async function spawnUidGid(): Promise<void> {
+    const ids = async (options: Deno.SpawnOptions = {}) => {
+      const { stdout } = await Deno.spawn("id", options);
+      return decoder.decode(stdout!).trim();
+    };
+    const uid = Number(await ids({ args: ["-u"] }));
+    const gid = Number(await ids({ args: ["-g"] }));
+
+    // Switching to the ids we already run as is allowed for everyone.
+    assertEquals(await ids({ args: ["-u"], uid, gid }), `${uid}`);
+    assertEquals(await ids({ args: ["-g"], uid, gid }), `${gid}`);
+  },
+);
+
+async function waitForExit(pid: number): Promise<void> {
+  // Killed processes linger briefly until they are reaped, so poll for a
+  // while. Zombies count as gone.
+  for (let i = 0; i < 50; i++) {
+    const { status, stdout } = await Deno.spawn("ps", {
+      args: ["-o", "stat=", "-p", `${pid}`],
+    });
+    if (!status.success || decoder.decode(stdout!).trim().startsWith("Z")) {
+      return;
+    }
+    await new Promise((resolve) => setTimeout(resolve, 100));
+  }
+  throw new Error(`Process ${pid} is still running`);
+}
+
+unitTest(
+  { ignore: Deno.build.os === "windows", perms: { run: true, read: true } },
+  async function spawnChildProcessGroupKill(): Promise<void> {
+    // The child starts a grandchild and then waits for it.
+    const child = Deno.spawnChild(Deno.execPath(), {
+      args: [
+        "eval",
+        "const p = Deno.run({ cmd: [Deno.execPath(), 'eval', " +
+        "'setTimeout(() => {}, 10000)'] }); console.log(p.pid);" +
+        " await p.status();",
+      ],
+      processGroup: true,
+    });
+    const buf = new Uint8Array(32);
+    const n = await child.stdout!.read(buf);
+    const grandchildPid = Number(decoder.decode(buf.subarray(0, n!)).trim());
+
+    child.kill(9, { group: true });
+    const status = await child.status;
+    assertEquals(status.signal, 9);
+    await waitForExit(grandchildPid);
+
+    assertThrows(() => child.kill(9), TypeError);
+    child.stdout!.close();
+    child.stderr!.close();
+    child.close();
+  },
+);
+
+unitTest(
+  { ignore: Deno.build.os === "windows", perms: { run: true, read: true } },
+  async function spawnChildReapedGroupLeaderNotSignaled(): Promise<void> {
+    const child = Deno.spawnChild(Deno.execPath(), {
+      args: ["eval", ""],
+      processGroup: true,
+      stdout: "null",
+      stderr: "null",
+    });
+    assertEquals((await child.status).code, 0);
+
+    // The group id may already belong to an unrelated group.
+    assertThrows(
+      () => child.kill(9, { group: true }),
+      TypeError,
+      "Child process has already exited",
+    );
+    child.close();
+  },
+);
+
+unitTest(
+  { perms: { run: true, read: true } },
+  async function spawnChildKillGroupWithoutProcessGroup(): Promise<void> {
+    const child = Deno.spawnChild(Deno.execPath(), {
+      args: ["eval", "setTimeout(() => {}, 10000)"],
+    });
+    assertThrows(() => child.kill(9, { group: true }), TypeError);
+    child.close();
+  },
+);
+
+unitTest(
+  { ignore: Deno.build.os === "windows", perms: { run: true, read: true } },
+  async function spawnChildrenKilledOnExit(): Promise<void> {
+    for (const exit of ["Deno.exit(0);", ""]) {
+      const { status, stdout } = await Deno.spawn(Deno.execPath(), {
+        args: [
+          "eval",
+          "--unstable",
+          "const p = Deno.run({ cmd: [Deno.execPath(), 'eval', " +
+          "'setTimeout(() => {}, 10000)'] }); console.log(p.pid);" +
+          " const c = Deno.spawnChild(Deno.execPath(), { args: ['eval', " +
+          "'setTimeout(() => {}, 10000)'], processGroup: true });" +
+          ` console.log(c.pid); ${exit}`,
+        ],
+      });
+      assert(status.success);
+      const pids = decoder.decode(stdout!).trim().split("\n").map(Number);
+      assertEquals(pids.length, 2);
+      for (const pid of pids) {
+        await waitForExit(pid);
+      }
+    }
+  },
+);
+
+unitTest(
+  { ignore: Deno.build.os === "windows", perms: { run: true, read: true } },
+  async function spawnChildPty(): Promise<void> {
+    const child = Deno.spawnChild(Deno.execPath(), {
+      args: [
+        "eval",
+        "--unstable",
+        "const size = () =>" +
+        " JSON.stringify(Deno.consoleSize(Deno.stdout.rid));" +
+        " console.log(Deno.isatty(Deno.stdin.rid), size());" +
+        " await Deno.stdin.read(new Uint8Array(1));" +
+        " console.log(size());",
+      ],
+      pty: { columns: 100, rows: 30 },
+    });
+    assertEquals(child.stdin, null);
+    assertEquals(child.stdout, null);
+    assertEquals(child.stderr, null);
+    const pty = child.pty!;
+
+    let output = "";
+    const buf = new Uint8Array(1024);
+    while (!output.includes("}")) {
+      const n = await pty.read(buf);
+      assert(n !== null);
+      output += decoder.decode(buf.subarray(0, n));
+    }
+    assert(output.includes('true {"columns":100,"rows":30}'));
+
+    pty.resize({ columns: 120, rows: 40 });
+    await pty.write(new TextEncoder().encode("\n"));
+    output = decoder.decode(await Deno.readAll(pty));
+    assert(output.includes('{"columns":120,"rows":40}'));
+
+    assert((await child.status).success);
+    pty.close();
+    child.close();
+  },
+);
+
+unitTest(
+  { perms: { run: true, read: true } },
+  async function spawnPtyInvalidOptions(): Promise<void> {
+    assertThrows(() => {
+      Deno.spawnChild(Deno.execPath(), { pty: true, stdout: "piped" });
+    }, TypeError);
+    await assertThrowsAsync(
+      () => Deno.spawn(Deno.execPath(), { pty: true } as Deno.SpawnOptions),
+      TypeError,
+    );
+    assertThrows(() => {
+      Deno.spawnSync(Deno.execPath(), { pty: true } as Deno.SpawnOptions);
+    }, TypeError);
+  },
+);
+
+unitTest({ perms: { read: true } }, function spawnSyncPermissions(): void {
+  assertThrows(() => {
+    Deno.spawnSync(Deno.execPath(), { args: ["eval", "1"] });
+  }, Deno.errors.PermissionDenied);
+});
+
+unitTest(
+  { perms: { run: true, read: true } },
+  function spawnSyncCollectsOutput(): void {
+    const { status, stdout, stderr } = Deno.spawnSync(Deno.execPath(), {
+      args: [
+        "eval",
+        "console.log('hello'); console.error('world'); Deno.exit(3)",
+      ],
+    });
+    assertEquals(status, { success: false, code: 3, signal: null });
+    assertEquals(decoder.decode(stdout!), "hello\n");
+    assertEquals(decoder.decode(stderr!), "world\n");
+  },
+);
+
+unitTest(
+  { perms: { run: true, read: true } },
+  function spawnSyncStdioNotPiped(): void {
+    const { status, stdout, stderr } = Deno.spawnSync(Deno.execPath(), {
+      args: ["eval", "console.log('hello')"],
+      stdout: "null",
+      stderr: "inherit",
+    });
+    assert(status.success);
+    assertEquals(stdout, null);
+    assertEquals(stderr, null);
+  },
+);
+
+unitTest(
+  { perms: { run: true, read: true } },
+  function spawnSyncInvalidOptions(): void {
+    assertThrows(() => {
+      Deno.spawnSync(Deno.execPath(), { stdin: "piped" });
+    }, TypeError);
+  },
+);
+
+unitTest(
+  { perms: { run: true, read: true } },
+  function spawnSyncNotFound(): void {
+    assertThrows(() => {
+      Deno.spawnSync("this-command-does-not-exist");
+    }, Deno.errors.NotFound);
+  },
+);
@@ -985,3 +985,252 @@ unitTest(
     conn.close();
   },
 );
+
+unitTest(
+  { perms: { read: true, net: true } },
+  async function connectTlsAlpnProtocols(): Promise<void> {
+    const hostname = "localhost";
+    const port = getPort();
+    const listener = Deno.listenTls({
+      hostname,
+      port,
+      certFile: "cli/tests/tls/localhost.crt",
+      keyFile: "cli/tests/tls/localhost.key",
+      alpnProtocols: ["h2", "http/1.1"],
+    });
+    // The server side only makes progress on the handshake while it is read
+    // from, so keep reading until each client hangs up.
+    const serverDone = (async () => {
+      for (let i = 0; i < 3; i++) {
+        const conn = await listener.accept();
+        await conn.read(new Uint8Array(1));
+        conn.close();
+      }
+    })();
+
+    const caFile = "cli/tests/tls/RootCA.pem";
+    const conn1 = await Deno.connectTls({
+      hostname,
+      port,
+      certFile: caFile,
+      alpnProtocols: ["foo", "http/1.1"],
+    });
+    assertStrictEquals(conn1.alpnProtocol, "http/1.1");
+    conn1.close();
+
+    const conn2 = await Deno.connectTls({
+      hostname,
+      port,
+      certFile: caFile,
+      alpnProtocols: ["foo"],
+    });
+    assertStrictEquals(conn2.alpnProtocol, null);
+    conn2.close();
+
+    const tcpConn = await Deno.connect({ hostname, port });
+    const conn3 = await Deno.startTls(tcpConn, {
+      hostname,
+      certFile: caFile,
+      alpnProtocols: ["h2"],
+    });
+    assertStrictEquals(conn3.alpnProtocol, "h2");
+    conn3.close();
+
+    await serverDone;
+    listener.close();
+  },
+);
+
+unitTest(
+  { perms: { read: true, net: true } },
+  async function listenTlsRequireClientCert(): Promise<void> {
+    const hostname = "localhost";
+    const port = getPort();
+    const listener = Deno.listenTls({
+      hostname,
+      port,
+      certFile: "cli/tests/tls/localhost.crt",
+      keyFile: "cli/tests/tls/localhost.key",
+      clientCaFile: "cli/tests/tls/RootCA.pem",
+      requireClientCert: true,
+    });
+
+    const clientCert = await Deno.readTextFile("cli/tests/tls/client.crt");
+    const clientConn = await Deno.connectTls({
+      hostname,
+      port,
+      certFile: "cli/tests/tls/RootCA.pem",
+      certChain: clientCert,
+      privateKey: await Deno.readTextFile("cli/tests/tls/client.key"),
+    });
+    const serverConn = await listener.accept();
+    const [serverCerts, clientCerts] = await Promise.all([
+      serverConn.peerCertificates(),
+      (clientConn as Deno.TlsConn).peerCertificates(),
+    ]);
+
+    // The client presented its own certificate, the server the localhost one.
+    assertEquals(serverCerts.length, 1);
+    assert(serverCerts[0] instanceof Uint8Array);
+    assertEquals(clientCerts.length, 1);
+    assertNotEquals(serverCerts[0], clientCerts[0]);
+
+    clientConn.close();
+    serverConn.close();
+
+    // Clients without a certificate fail the handshake.
+    const anonymousConn = await Deno.connectTls({
+      hostname,
+      port,
+      certFile: "cli/tests/tls/RootCA.pem",
+    });
+    const rejectedConn = await listener.accept();
+    const clientRead = anonymousConn.read(new Uint8Array(1)).catch(() => {});
+    await assertThrowsAsync(() => rejectedConn.peerCertificates());
+    await clientRead;
+    anonymousConn.close();
+    rejectedConn.close();
+
+    listener.close();
+  },
+);
+
+unitTest(
+  { perms: { read: true, net: true } },
+  async function listenTlsOptionalClientCert(): Promise<void> {
+    const hostname = "localhost";
+    const port = getPort();
+    const listener = Deno.listenTls({
+      hostname,
+      port,
+      certFile: "cli/tests/tls/localhost.crt",
+      keyFile: "cli/tests/tls/localhost.key",
+      clientCaFile: "cli/tests/tls/RootCA.pem",
+    });
+
+    const clientConn = await Deno.connectTls({
+      hostname,
+      port,
+      certFile: "cli/tests/tls/RootCA.pem",
+    });
+    const serverConn = await listener.accept();
+    const [serverCerts] = await Promise.all([
+      serverConn.peerCertificates(),
+      (clientConn as Deno.TlsConn).peerCertificates(),
+    ]);
+    assertEquals(serverCerts, []);
+
+    clientConn.close();
+    serverConn.close();
+    listener.close();
+  },
+);
+
+unitTest(
+  { perms: { read: true, net: true } },
+  async function tlsClientCertOptionsInvalid(): Promise<void> {
+    assertThrows(() => {
+      Deno.listenTls({
+        hostname: "localhost",
+        port: getPort(),
+        certFile: "cli/tests/tls/localhost.crt",
+        keyFile: "cli/tests/tls/localhost.key",
+        requireClientCert: true,
+      });
+    }, TypeError);
+
+    const port = getPort();
+    const listener = Deno.listen({ hostname: "localhost", port });
+    await assertThrowsAsync(async () => {
+      await Deno.connectTls({
+        hostname: "localhost",
+        port,
+        certChain: await Deno.readTextFile("cli/tests/tls/client.crt"),
+      });
+    }, TypeError);
+    listener.close();
+  },
+);
+
+unitTest(
+  { perms: { read: true, net: true } },
+  async function tlsHandshakeInfo(): Promise<void> {
+    const hostname = "localhost";
+    const port = getPort();
+    const listener = Deno.listenTls({
+      hostname,
+      port,
+      certFile: "cli/tests/tls/localhost.crt",
+      keyFile: "cli/tests/tls/localhost.key",
+      alpnProtocols: ["h2", "http/1.1"],
+    });
+
+    const clientConn = await Deno.connectTls({
+      hostname,
+      port,
+      certFile: "cli/tests/tls/RootCA.pem",
+      alpnProtocols: ["http/1.1"],
+    });
+    const serverConn = await listener.accept() as Deno.TlsConn;
+    assertStrictEquals(serverConn.alpnProtocol, null);
+    const [serverInfo, clientInfo] = await Promise.all([
+      serverConn.handshake(),
+      clientConn.handshake(),
+    ]);
+    assertStrictEquals(serverConn.alpnProtocol, "http/1.1");
+
+    assertEquals(clientInfo.protocolVersion, "TLSv1.3");
+    assertEquals(serverInfo.protocolVersion, "TLSv1.3");
+    assert(clientInfo.cipherSuite?.startsWith("TLS13_"));
+    assertEquals(serverInfo.cipherSuite, clientInfo.cipherSuite);
+    assertEquals(clientInfo.alpnProtocol, "http/1.1");
+    assertEquals(serverInfo.alpnProtocol, "http/1.1");
+    assertEquals(clientInfo.sniHostname, hostname);
+    assertEquals(serverInfo.sniHostname, hostname);
+
+    // Only the server presents a certificate, which is the one it was
+    // configured with.
+    assertEquals(serverInfo.peerCertificates, []);
+    assertEquals(clientInfo.peerCertificates.length, 1);
+    const pem = await Deno.readTextFile("cli/tests/tls/localhost.crt");
+    const der = atob(
+      pem.replace(/-----(BEGIN|END) CERTIFICATE-----|\s/g, ""),
+    );
+    assertEquals(
+      clientInfo.peerCertificates[0],
+      Uint8Array.from(der, (c) => c.charCodeAt(0)),
+    );
+
+    // Completing the handshake again just returns the same details.
+    assertEquals(await clientConn.handshake(), clientInfo);
+
+    clientConn.close();
+    serverConn.close();
+    listener.close();
+  },
+);
+
+unitTest(
+  { perms: { read: true, net: true } },
+  async function tlsHandshakeClosedConn(): Promise<void> {
+    const hostname = "localhost";
+    const port = getPort();
+    const listener = Deno.listenTls({
+      hostname,
+      port,
+      certFile: "cli/tests/tls/localhost.crt",
+      keyFile: "cli/tests/tls/localhost.key",
+    });
+    const clientConn = await Deno.connectTls({ hostname, port });
+    const serverConn = await listener.accept() as Deno.TlsConn;
+    clientConn.close();
+
+    await assertThrowsAsync(() => serverConn.handshake());
+    serverConn.close();
+    await assertThrowsAsync(
+      () => serverConn.handshake(),
+      Deno.errors.BadResource,
+    );
+    listener.close();
+  },
+);
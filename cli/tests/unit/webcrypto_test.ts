@@ -0,0 +1,376 @@
+// Copyright 2018-2021 the Deno authors. All rights reserved. MIT license.
+import {
+  assert,
+  assertEquals,
+  assertThrowsAsync,
+  unitTest,
+} from "./test_util.ts";
+
+unitTest(async function testHmacSignVerify() {
+  const key = await crypto.subtle.generateKey(
+    { name: "HMAC", hash: "SHA-256" },
+    true,
+    ["sign", "verify"],
+  );
+  assert(key instanceof CryptoKey);
+  assertEquals(key.type, "secret");
+  assertEquals(key.usages, ["sign", "verify"]);
+
+  const data = new TextEncoder().encode("webhook payload");
+  const signature = await crypto.subtle.sign("HMAC", key, data);
+  assertEquals(signature.byteLength, 32);
+  assert(await crypto.subtle.verify("HMAC", key, signature, data));
+  assert(
+    !(await crypto.subtle.verify(
+      "HMAC",
+      key,
+      signature,
+      new TextEncoder().encode("tampered"),
+    )),
+  );
+});
+
+unitTest(async function testCryptoKeyIsResource() {
+  const before = Object.values(Deno.resources())
+    .filter((name) => name == "cryptoKey").length;
+  const key = await crypto.subtle.generateKey(
+    { name: "AES-GCM", length: 128 },
+    false,
+    ["encrypt"],
+  );
+  const after = Object.values(Deno.resources())
+    .filter((name) => name == "cryptoKey").length;
+  assertEquals(after, before + 1);
+  assertEquals(key.type, "secret");
+});
+
+unitTest(async function testHmacImportRawKnownAnswer() {
+  // RFC 4231, test case 2.
+  const key = await crypto.subtle.importKey(
+    "raw",
+    new TextEncoder().encode("Jefe"),
+    { name: "HMAC", hash: "SHA-256" },
+    false,
+    ["sign"],
+  );
+  const signature = await crypto.subtle.sign(
+    "HMAC",
+    key,
+    new TextEncoder().encode("what do ya want for nothing?"),
+  );
+  const hex = Array.from(new Uint8Array(signature))
+    .map((b) => b.toString(16).padStart(2, "0"))
+    .join("");
+  assertEquals(
+    hex,
+    "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+  );
+});
+
+unitTest(async function testEcdsaSignVerify() {
+  const { privateKey, publicKey } = await crypto.subtle.generateKey(
+    { name: "ECDSA", namedCurve: "P-256" },
+    true,
+    ["sign", "verify"],
+  ) as CryptoKeyPair;
+  assertEquals(privateKey.type, "private");
+  assertEquals(publicKey.type, "public");
+  assertEquals(privateKey.usages, ["sign"]);
+  assertEquals(publicKey.usages, ["verify"]);
+
+  const data = new Uint8Array([1, 2, 3, 4]);
+  const algorithm = { name: "ECDSA", hash: "SHA-256" };
+  const signature = await crypto.subtle.sign(algorithm, privateKey, data);
+  assertEquals(signature.byteLength, 64);
+  assert(await crypto.subtle.verify(algorithm, publicKey, signature, data));
+});
+
+unitTest(async function testEcdsaExportImportSpki() {
+  const { privateKey, publicKey } = await crypto.subtle.generateKey(
+    { name: "ECDSA", namedCurve: "P-384" },
+    true,
+    ["sign", "verify"],
+  ) as CryptoKeyPair;
+  const spki = await crypto.subtle.exportKey("spki", publicKey);
+  const imported = await crypto.subtle.importKey(
+    "spki",
+    spki,
+    { name: "ECDSA", namedCurve: "P-384" },
+    true,
+    ["verify"],
+  );
+
+  const data = new Uint8Array([5, 6, 7]);
+  const algorithm = { name: "ECDSA", hash: "SHA-384" };
+  const signature = await crypto.subtle.sign(algorithm, privateKey, data);
+  assert(await crypto.subtle.verify(algorithm, imported, signature, data));
+
+  const jwk = await crypto.subtle.exportKey("jwk", privateKey);
+  assertEquals(jwk.kty, "EC");
+  assertEquals(jwk.crv, "P-384");
+  assert(typeof jwk.d === "string");
+});
+
+unitTest(async function testEcdsaP256SignExportRoundTrip() {
+  const { privateKey, publicKey } = await crypto.subtle.generateKey(
+    { name: "ECDSA", namedCurve: "P-256" },
+    true,
+    ["sign", "verify"],
+  ) as CryptoKeyPair;
+  const data = new Uint8Array([8, 9, 10]);
+  const algorithm = { name: "ECDSA", hash: "SHA-256" };
+
+  const pkcs8 = await crypto.subtle.exportKey("pkcs8", privateKey);
+  const fromPkcs8 = await crypto.subtle.importKey(
+    "pkcs8",
+    pkcs8,
+    { name: "ECDSA", namedCurve: "P-256" },
+    true,
+    ["sign"],
+  );
+  const jwk = await crypto.subtle.exportKey("jwk", fromPkcs8);
+  assertEquals(jwk.crv, "P-256");
+  const fromJwk = await crypto.subtle.importKey(
+    "jwk",
+    jwk,
+    { name: "ECDSA", namedCurve: "P-256" },
+    false,
+    ["sign"],
+  );
+
+  for (const key of [privateKey, fromPkcs8, fromJwk]) {
+    const signature = await crypto.subtle.sign(algorithm, key, data);
+    assert(await crypto.subtle.verify(algorithm, publicKey, signature, data));
+  }
+});
+
+unitTest(async function testAesGcmRoundTrip() {
+  const key = await crypto.subtle.generateKey(
+    { name: "AES-GCM", length: 256 },
+    true,
+    ["encrypt", "decrypt"],
+  ) as CryptoKey;
+  const iv = crypto.getRandomValues(new Uint8Array(12));
+  const plaintext = new TextEncoder().encode("hello world");
+  const ciphertext = await crypto.subtle.encrypt(
+    { name: "AES-GCM", iv },
+    key,
+    plaintext,
+  );
+  assertEquals(ciphertext.byteLength, plaintext.byteLength + 16);
+  const decrypted = await crypto.subtle.decrypt(
+    { name: "AES-GCM", iv },
+    key,
+    ciphertext,
+  );
+  assertEquals(new Uint8Array(decrypted), plaintext);
+
+  const tampered = new Uint8Array(ciphertext);
+  tampered[0] ^= 1;
+  await assertThrowsAsync(
+    () => crypto.subtle.decrypt({ name: "AES-GCM", iv }, key, tampered),
+    DOMException,
+  );
+});
+
+unitTest(async function testAesCbcAndCtrRoundTrip() {
+  const plaintext = new TextEncoder().encode("sixteen byte blk and more");
+  for (const name of ["AES-CBC", "AES-CTR"]) {
+    const key = await crypto.subtle.generateKey(
+      { name, length: 128 },
+      false,
+      ["encrypt", "decrypt"],
+    ) as CryptoKey;
+    const algorithm = name == "AES-CBC"
+      ? { name, iv: new Uint8Array(16) }
+      : { name, counter: new Uint8Array(16), length: 64 };
+    const ciphertext = await crypto.subtle.encrypt(algorithm, key, plaintext);
+    const decrypted = await crypto.subtle.decrypt(algorithm, key, ciphertext);
+    assertEquals(new Uint8Array(decrypted), plaintext);
+  }
+});
+
+unitTest(async function testJwkRoundTrip() {
+  const key = await crypto.subtle.generateKey(
+    { name: "AES-CBC", length: 128 },
+    true,
+    ["encrypt"],
+  ) as CryptoKey;
+  const jwk = await crypto.subtle.exportKey("jwk", key);
+  assertEquals(jwk.kty, "oct");
+  assertEquals(jwk.alg, "A128CBC");
+  assertEquals(jwk.key_ops, ["encrypt"]);
+  const imported = await crypto.subtle.importKey(
+    "jwk",
+    jwk,
+    "AES-CBC",
+    true,
+    ["encrypt"],
+  );
+  assertEquals(
+    new Uint8Array(await crypto.subtle.exportKey("raw", imported)),
+    new Uint8Array(await crypto.subtle.exportKey("raw", key)),
+  );
+});
+
+unitTest(async function testNonExtractableKey() {
+  const key = await crypto.subtle.generateKey(
+    { name: "HMAC", hash: "SHA-512" },
+    false,
+    ["sign"],
+  ) as CryptoKey;
+  await assertThrowsAsync(
+    () => crypto.subtle.exportKey("raw", key),
+    DOMException,
+    "not extractable",
+  );
+  await assertThrowsAsync(
+    () => crypto.subtle.verify("HMAC", key, new Uint8Array(), new Uint8Array()),
+    DOMException,
+    "verify",
+  );
+});
+
+function toHex(buffer: ArrayBuffer): string {
+  return Array.from(new Uint8Array(buffer))
+    .map((b) => b.toString(16).padStart(2, "0"))
+    .join("");
+}
+
+unitTest(async function testPbkdf2DeriveBits() {
+  // RFC 6070, test case 2.
+  const key = await crypto.subtle.importKey(
+    "raw",
+    new TextEncoder().encode("password"),
+    "PBKDF2",
+    false,
+    ["deriveBits"],
+  );
+  const bits = await crypto.subtle.deriveBits(
+    {
+      name: "PBKDF2",
+      hash: "SHA-1",
+      salt: new TextEncoder().encode("salt"),
+      iterations: 2,
+    },
+    key,
+    160,
+  );
+  assertEquals(toHex(bits), "ea6c014dc72d6f8ccd1ed92ace1d41f0d8de8957");
+});
+
+unitTest(async function testHkdfDeriveBits() {
+  // RFC 5869, test case 1.
+  const key = await crypto.subtle.importKey(
+    "raw",
+    new Uint8Array(22).fill(0x0b),
+    "HKDF",
+    false,
+    ["deriveBits"],
+  );
+  const bits = await crypto.subtle.deriveBits(
+    {
+      name: "HKDF",
+      hash: "SHA-256",
+      salt: new Uint8Array(13).map((_, i) => i),
+      info: new Uint8Array(10).map((_, i) => 0xf0 + i),
+    },
+    key,
+    42 * 8,
+  );
+  assertEquals(
+    toHex(bits),
+    "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865",
+  );
+});
+
+unitTest(async function testEcdhDeriveBits() {
+  const alice = await crypto.subtle.generateKey(
+    { name: "ECDH", namedCurve: "P-256" },
+    true,
+    ["deriveBits"],
+  ) as CryptoKeyPair;
+  const bob = await crypto.subtle.generateKey(
+    { name: "ECDH", namedCurve: "P-256" },
+    true,
+    ["deriveBits"],
+  ) as CryptoKeyPair;
+
+  const aliceSecret = await crypto.subtle.deriveBits(
+    { name: "ECDH", public: bob.publicKey },
+    alice.privateKey,
+    256,
+  );
+  const bobSecret = await crypto.subtle.deriveBits(
+    { name: "ECDH", public: alice.publicKey },
+    bob.privateKey,
+    256,
+  );
+  assertEquals(aliceSecret.byteLength, 32);
+  assertEquals(new Uint8Array(aliceSecret), new Uint8Array(bobSecret));
+});
+
+unitTest(async function testEcdhP384NotSupported() {
+  const alice = await crypto.subtle.generateKey(
+    { name: "ECDH", namedCurve: "P-384" },
+    false,
+    ["deriveBits", "deriveKey"],
+  ) as CryptoKeyPair;
+  const bob = await crypto.subtle.generateKey(
+    { name: "ECDH", namedCurve: "P-384" },
+    false,
+    ["deriveBits"],
+  ) as CryptoKeyPair;
+  const algorithm = { name: "ECDH", public: bob.publicKey };
+
+  await assertThrowsAsync(
+    () => crypto.subtle.deriveBits(algorithm, alice.privateKey, 384),
+    DOMException,
+    "ECDH is not supported with P-384",
+  );
+  await assertThrowsAsync(
+    () =>
+      crypto.subtle.deriveKey(
+        algorithm,
+        alice.privateKey,
+        { name: "AES-GCM", length: 256 },
+        false,
+        ["encrypt"],
+      ),
+    DOMException,
+    "ECDH is not supported with P-384",
+  );
+});
+
+unitTest(async function testPbkdf2DeriveKey() {
+  const key = await crypto.subtle.importKey(
+    "raw",
+    new TextEncoder().encode("correct horse battery staple"),
+    "PBKDF2",
+    false,
+    ["deriveKey"],
+  );
+  const derived = await crypto.subtle.deriveKey(
+    {
+      name: "PBKDF2",
+      hash: "SHA-256",
+      salt: new Uint8Array(16),
+      iterations: 1000,
+    },
+    key,
+    { name: "AES-GCM", length: 256 },
+    false,
+    ["encrypt", "decrypt"],
+  );
+  assertEquals(derived.algorithm, { name: "AES-GCM", length: 256 });
+  assertEquals(derived.usages, ["encrypt", "decrypt"]);
+
+  await assertThrowsAsync(
+    () =>
+      crypto.subtle.importKey("raw", new Uint8Array(8), "PBKDF2", true, [
+        "deriveKey",
+      ]),
+    DOMException,
+    "can not be extractable",
+  );
+});
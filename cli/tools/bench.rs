@@ -0,0 +1,523 @@
+// Copyright 2018-2021 the Deno authors. All rights reserved. MIT license.
+
+use crate::colors;
+use crate::create_main_worker;
+use crate::file_fetcher::File;
+use crate::media_type::MediaType;
+use crate::module_graph;
+use crate::program_state::ProgramState;
+use crate::tokio_util;
+use deno_core::error::generic_error;
+use deno_core::error::AnyError;
+use deno_core::futures::future;
+use deno_core::serde_json;
+use deno_core::serde_json::json;
+use deno_core::ModuleSpecifier;
+use deno_runtime::permissions::Permissions;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::mpsc::channel;
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Timings of a benchmark in nanoseconds per iteration.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct BenchStats {
+  pub samples: usize,
+  pub min: f64,
+  pub max: f64,
+  pub mean: f64,
+  pub p75: f64,
+  pub p99: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BenchResult {
+  Ok(BenchStats),
+  Ignored,
+  Failed(String),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", content = "data", rename_all = "camelCase")]
+pub enum BenchMessage {
+  Plan {
+    pending: usize,
+    filtered: usize,
+    only: bool,
+  },
+  Wait {
+    name: String,
+  },
+  Result {
+    name: String,
+    result: BenchResult,
+  },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BenchEvent {
+  pub origin: String,
+  pub message: BenchMessage,
+}
+
+/// The report written with `--json` and `--save-baseline`, and read back with
+/// `--baseline`.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BenchReport {
+  benches: Vec<BenchCaseReport>,
+  #[serde(default)]
+  summary: BenchSummary,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BenchCaseReport {
+  origin: String,
+  name: String,
+  result: String,
+  stats: Option<BenchStats>,
+  error: Option<String>,
+  /// The relative change of the mean compared to the baseline, if any.
+  #[serde(default)]
+  change: Option<f64>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BenchSummary {
+  measured: usize,
+  failed: usize,
+  ignored: usize,
+  filtered_out: usize,
+  duration: u64,
+}
+
+/// Formats a duration given in nanoseconds with a unit fitting its magnitude.
+fn format_nanos(nanos: f64) -> String {
+  if nanos < 1e3 {
+    format!("{:.2}ns", nanos)
+  } else if nanos < 1e6 {
+    format!("{:.2}µs", nanos / 1e3)
+  } else if nanos < 1e9 {
+    format!("{:.2}ms", nanos / 1e6)
+  } else {
+    format!("{:.2}s", nanos / 1e9)
+  }
+}
+
+fn format_change(change: f64) -> String {
+  let percent = format!("{:.1}%", change.abs() * 100.0);
+  if change > 0.0 {
+    colors::red(format!("{} slower than baseline", percent)).to_string()
+  } else {
+    colors::green(format!("{} faster than baseline", percent)).to_string()
+  }
+}
+
+/// Reads the mean timings of a report saved with `--save-baseline`, by origin
+/// and name.
+fn load_baseline(
+  path: &Path,
+) -> Result<HashMap<(String, String), f64>, AnyError> {
+  let text = std::fs::read_to_string(path).map_err(|err| {
+    std::io::Error::new(
+      err.kind(),
+      format!("Unable to read baseline {}: {}", path.display(), err),
+    )
+  })?;
+  let report: BenchReport = serde_json::from_str(&text)?;
+  Ok(
+    report
+      .benches
+      .into_iter()
+      .filter_map(|case| {
+        let mean = case.stats?.mean;
+        Some(((case.origin, case.name), mean))
+      })
+      .collect(),
+  )
+}
+
+struct BenchReporter {
+  time: Instant,
+  json: bool,
+  baseline: HashMap<(String, String), f64>,
+  filtered_out: usize,
+  pending: usize,
+  cases: Vec<BenchCaseReport>,
+  failures: Vec<(String, String)>,
+}
+
+impl BenchReporter {
+  fn new(json: bool, baseline: HashMap<(String, String), f64>) -> Self {
+    Self {
+      time: Instant::now(),
+      json,
+      baseline,
+      filtered_out: 0,
+      pending: 0,
+      cases: Vec::new(),
+      failures: Vec::new(),
+    }
+  }
+
+  fn visit_event(&mut self, event: BenchEvent) -> std::io::Result<()> {
+    let mut output = std::io::stdout();
+    match event.message {
+      BenchMessage::Plan {
+        pending, filtered, ..
+      } => {
+        self.pending += pending;
+        self.filtered_out += filtered;
+        if !self.json {
+          writeln!(
+            output,
+            "running {} bench{} from {}",
+            pending,
+            if pending == 1 { "" } else { "es" },
+            event.origin
+          )?;
+        }
+      }
+      BenchMessage::Wait { name } => {
+        if !self.json {
+          write!(output, "bench {} ...", name)?;
+          output.flush()?;
+        }
+      }
+      BenchMessage::Result { name, result } => {
+        self.pending -= 1;
+        let mut case = BenchCaseReport {
+          origin: event.origin,
+          name,
+          result: String::new(),
+          stats: None,
+          error: None,
+          change: None,
+        };
+        let status = match result {
+          BenchResult::Ok(stats) => {
+            case.result = "ok".to_string();
+            case.change = self
+              .baseline
+              .get(&(case.origin.clone(), case.name.clone()))
+              .filter(|mean| **mean > 0.0)
+              .map(|mean| (stats.mean - mean) / mean);
+            let mut status = format!(
+              "{} samples, mean {}/iter (min {}, max {}, p75 {}, p99 {})",
+              stats.samples,
+              colors::cyan(format_nanos(stats.mean)),
+              format_nanos(stats.min),
+              format_nanos(stats.max),
+              format_nanos(stats.p75),
+              format_nanos(stats.p99),
+            );
+            if let Some(change) = case.change {
+              status.push_str(&format!(", {}", format_change(change)));
+            }
+            case.stats = Some(stats);
+            status
+          }
+          BenchResult::Ignored => {
+            case.result = "ignored".to_string();
+            colors::yellow("ignored").to_string()
+          }
+          BenchResult::Failed(error) => {
+            case.result = "failed".to_string();
+            case.error = Some(colors::strip_ansi_codes(&error).to_string());
+            self.failures.push((case.name.clone(), error));
+            colors::red("FAILED").to_string()
+          }
+        };
+        if !self.json {
+          writeln!(output, " {}", status)?;
+        }
+        self.cases.push(case);
+      }
+    }
+
+    Ok(())
+  }
+
+  fn report(&self) -> BenchReport {
+    let count =
+      |result: &str| self.cases.iter().filter(|c| c.result == result).count();
+    BenchReport {
+      benches: self.cases.clone(),
+      summary: BenchSummary {
+        measured: count("ok"),
+        failed: count("failed"),
+        ignored: count("ignored"),
+        filtered_out: self.filtered_out,
+        duration: self.time.elapsed().as_millis() as u64,
+      },
+    }
+  }
+
+  fn done(&self, maybe_save_baseline: Option<&Path>) -> Result<(), AnyError> {
+    let report = self.report();
+    if let Some(path) = maybe_save_baseline {
+      std::fs::write(path, serde_json::to_string_pretty(&report)?)?;
+    }
+
+    let mut output = std::io::stdout();
+    if self.json {
+      serde_json::to_writer_pretty(&mut output, &report)?;
+      writeln!(output)?;
+      return Ok(output.flush()?);
+    }
+
+    if !self.failures.is_empty() {
+      writeln!(output, "\nfailures:\n")?;
+      for (name, error) in &self.failures {
+        writeln!(output, "{}", name)?;
+        writeln!(output, "{}", error)?;
+        writeln!(output)?;
+      }
+
+      writeln!(output, "failures:\n")?;
+      for (name, _) in &self.failures {
+        writeln!(output, "\t{}", name)?;
+      }
+    }
+
+    let status = if self.pending > 0 || !self.failures.is_empty() {
+      colors::red("FAILED").to_string()
+    } else {
+      colors::green("ok").to_string()
+    };
+    let summary = &report.summary;
+    writeln!(
+      output,
+      "\nbench result: {}. {} measured; {} failed; {} ignored; {} filtered out {}\n",
+      status,
+      summary.measured,
+      summary.failed,
+      summary.ignored,
+      summary.filtered_out,
+      colors::gray(format!("({}ms)", summary.duration)),
+    )?;
+
+    Ok(output.flush()?)
+  }
+}
+
+pub(crate) fn is_supported(p: &Path) -> bool {
+  use std::path::Component;
+  if let Some(Component::Normal(basename_os_str)) = p.components().next_back() {
+    let basename = basename_os_str.to_string_lossy();
+    ["ts", "tsx", "js", "mjs", "jsx"].iter().any(|ext| {
+      basename.ends_with(&format!("_bench.{}", ext))
+        || basename.ends_with(&format!(".bench.{}", ext))
+        || basename == format!("bench.{}", ext)
+    })
+  } else {
+    false
+  }
+}
+
+pub async fn run_bench_file(
+  program_state: Arc<ProgramState>,
+  main_module: ModuleSpecifier,
+  bench_module: ModuleSpecifier,
+  permissions: Permissions,
+  channel: Sender<BenchEvent>,
+) -> Result<(), AnyError> {
+  let mut worker =
+    create_main_worker(&program_state, main_module.clone(), permissions, true);
+
+  worker
+    .js_runtime
+    .op_state()
+    .borrow_mut()
+    .put::<Sender<BenchEvent>>(channel);
+
+  worker.execute_module(&main_module).await?;
+  worker.execute("window.dispatchEvent(new Event('load'))")?;
+  worker.execute_module(&bench_module).await?;
+  worker.run_event_loop(true).await?;
+  worker.execute("window.dispatchEvent(new Event('unload'))")?;
+
+  Ok(())
+}
+
+/// Runs benchmarks, one module after another so that they do not disturb
+/// each other's timings.
+///
+/// Returns a boolean indicating whether any benchmark failed.
+#[allow(clippy::too_many_arguments)]
+pub async fn run_benchmarks(
+  program_state: Arc<ProgramState>,
+  permissions: Permissions,
+  lib: module_graph::TypeLib,
+  bench_modules: Vec<ModuleSpecifier>,
+  filter: Option<String>,
+  json: bool,
+  baseline: Option<PathBuf>,
+  save_baseline: Option<PathBuf>,
+) -> Result<bool, AnyError> {
+  if bench_modules.is_empty() {
+    return Err(generic_error("No matching bench modules found"));
+  }
+
+  let baseline = match baseline {
+    Some(path) => load_baseline(&path)?,
+    None => HashMap::new(),
+  };
+
+  program_state
+    .prepare_module_graph(
+      bench_modules.clone(),
+      lib,
+      Permissions::allow_all(),
+      permissions.clone(),
+      program_state.maybe_import_map.clone(),
+    )
+    .await?;
+
+  let bench_options = json!({
+    "filter": filter,
+  });
+  let bench_module = deno_core::resolve_path("$deno$bench.js")?;
+  let bench_source = format!(
+    "await Deno[Deno.internal].runBenchmarks({});",
+    bench_options
+  );
+  let bench_file = File {
+    local: bench_module.to_file_path().unwrap(),
+    maybe_types: None,
+    media_type: MediaType::JavaScript,
+    source: bench_source,
+    specifier: bench_module.clone(),
+  };
+  program_state.file_fetcher.insert_cached(bench_file);
+
+  let (sender, receiver) = channel::<BenchEvent>();
+
+  let runner = tokio::task::spawn_blocking(move || {
+    std::thread::spawn(move || {
+      for main_module in bench_modules {
+        let future = run_bench_file(
+          program_state.clone(),
+          main_module,
+          bench_module.clone(),
+          permissions.clone(),
+          sender.clone(),
+        );
+        tokio_util::run_basic(future)?;
+      }
+      Ok::<(), AnyError>(())
+    })
+    .join()
+    .unwrap()
+  });
+
+  let handler =
+    tokio::task::spawn_blocking(move || -> Result<bool, AnyError> {
+      let mut reporter = BenchReporter::new(json, baseline);
+      let mut used_only = false;
+
+      for event in receiver.iter() {
+        if let BenchMessage::Plan { only: true, .. } = event.message {
+          used_only = true;
+        }
+        reporter.visit_event(event)?;
+      }
+
+      reporter.done(save_baseline.as_deref())?;
+
+      let mut has_error = reporter.pending > 0 || !reporter.failures.is_empty();
+      if used_only {
+        if !json {
+          println!(
+            "{} because the \"only\" option was used\n",
+            colors::red("FAILED")
+          );
+        }
+        has_error = true;
+      }
+
+      Ok(has_error)
+    });
+
+  let (result, runner_result) = future::join(handler, runner).await;
+  runner_result??;
+  result?
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_is_supported() {
+    assert!(is_supported(Path::new("benches/foo_bench.ts")));
+    assert!(is_supported(Path::new("benches/foo_bench.jsx")));
+    assert!(is_supported(Path::new("bar/foo.bench.ts")));
+    assert!(is_supported(Path::new("bar/foo.bench.mjs")));
+    assert!(is_supported(Path::new("foo/bar/bench.js")));
+    assert!(is_supported(Path::new("foo/bar/bench.tsx")));
+    assert!(!is_supported(Path::new("foo/bar/foo_test.ts")));
+    assert!(!is_supported(Path::new("foo/bar/workbench.ts")));
+    assert!(!is_supported(Path::new("README.md")));
+  }
+
+  #[test]
+  fn test_format_nanos() {
+    assert_eq!(format_nanos(12.345), "12.35ns");
+    assert_eq!(format_nanos(1234.0), "1.23µs");
+    assert_eq!(format_nanos(2_500_000.0), "2.50ms");
+    assert_eq!(format_nanos(3_000_000_000.0), "3.00s");
+  }
+
+  #[test]
+  fn test_load_baseline() {
+    let temp_dir = tempfile::TempDir::new().expect("tempdir fail");
+    let path = temp_dir.path().join("baseline.json");
+    std::fs::write(
+      &path,
+      r#"{
+        "benches": [
+          {
+            "origin": "file:///a_bench.ts",
+            "name": "sort",
+            "result": "ok",
+            "stats": {
+              "samples": 10,
+              "min": 1.0,
+              "max": 3.0,
+              "mean": 2.0,
+              "p75": 2.5,
+              "p99": 3.0
+            },
+            "error": null
+          },
+          {
+            "origin": "file:///a_bench.ts",
+            "name": "broken",
+            "result": "failed",
+            "stats": null,
+            "error": "Error: broken"
+          }
+        ]
+      }"#,
+    )
+    .unwrap();
+
+    let baseline = load_baseline(&path).unwrap();
+    assert_eq!(baseline.len(), 1);
+    assert_eq!(
+      baseline.get(&("file:///a_bench.ts".to_string(), "sort".to_string())),
+      Some(&2.0)
+    );
+    assert!(load_baseline(&temp_dir.path().join("missing.json")).is_err());
+  }
+}
@@ -1,14 +1,16 @@
 // Copyright 2018-2021 the Deno authors. All rights reserved. MIT license.
 
 use crate::ast;
-use crate::ast::TokenOrComment;
 use crate::colors;
+use crate::flags::CoverageThreshold;
+use crate::flags::CoverageThresholdKind;
 use crate::flags::Flags;
 use crate::fs_util::collect_files;
 use crate::media_type::MediaType;
 use crate::module_graph::TypeLib;
 use crate::program_state::ProgramState;
 use crate::source_maps::SourceMapGetter;
+use deno_core::error::generic_error;
 use deno_core::error::AnyError;
 use deno_core::serde_json;
 use deno_core::serde_json::json;
@@ -19,9 +21,16 @@ use regex::Regex;
 use serde::Deserialize;
 use serde::Serialize;
 use sourcemap::SourceMap;
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 use swc_common::Span;
+use swc_common::Spanned;
+use swc_common::DUMMY_SP;
+use swc_ecmascript::ast as swc_ast;
+use swc_ecmascript::visit::Node;
+use swc_ecmascript::visit::Visit;
+use swc_ecmascript::visit::VisitWith;
 use uuid::Uuid;
 
 pub struct CoverageCollector {
@@ -114,6 +123,7 @@ pub struct GetScriptSourceResult {
 pub enum CoverageReporterKind {
   Pretty,
   Lcov,
+  Html(PathBuf),
 }
 
 fn create_reporter(
@@ -122,6 +132,7 @@ fn create_reporter(
   match kind {
     CoverageReporterKind::Lcov => Box::new(LcovCoverageReporter::new()),
     CoverageReporterKind::Pretty => Box::new(PrettyCoverageReporter::new()),
+    CoverageReporterKind::Html(dir) => Box::new(HtmlCoverageReporter::new(dir)),
   }
 }
 
@@ -130,362 +141,479 @@ pub trait CoverageReporter {
     &mut self,
     script_coverage: &ScriptCoverage,
     script_source: &str,
-    maybe_source_map: Option<Vec<u8>>,
+    maybe_source_map: &Option<SourceMap>,
     maybe_original_source: Option<String>,
+    report: CoverageReport,
   );
 
-  fn done(&mut self);
+  fn done(&mut self) -> Result<(), AnyError>;
 }
 
-pub struct LcovCoverageReporter {}
+struct FunctionData {
+  name: String,
+  /// Line where the function starts, in the original source.
+  line_index: usize,
+  execution_count: usize,
+}
 
-impl LcovCoverageReporter {
-  pub fn new() -> LcovCoverageReporter {
-    LcovCoverageReporter {}
+struct BranchData {
+  /// Line where the branch starts, in the original source.
+  line_index: usize,
+  block_number: usize,
+  branch_number: usize,
+  /// How often the branch was taken, or `None` if the block containing it
+  /// was never executed.
+  taken: Option<usize>,
+}
+
+impl BranchData {
+  fn is_hit(&self) -> bool {
+    self.taken.map_or(false, |taken| taken > 0)
   }
 }
 
-impl CoverageReporter for LcovCoverageReporter {
-  fn visit_coverage(
-    &mut self,
-    script_coverage: &ScriptCoverage,
-    script_source: &str,
-    maybe_source_map: Option<Vec<u8>>,
-    _maybe_original_source: Option<String>,
-  ) {
-    // TODO(caspervonb) cleanup and reduce duplication between reporters, pre-compute line coverage
-    // elsewhere.
-    let maybe_source_map = maybe_source_map
-      .map(|source_map| SourceMap::from_slice(&source_map).unwrap());
+/// The coverage of a single module, mapped back to its original source.
+pub struct CoverageReport {
+  named_functions: Vec<FunctionData>,
+  branches: Vec<BranchData>,
+  /// The execution count of every line that was found, by line index.
+  found_lines: Vec<(usize, usize)>,
+}
 
-    let url = Url::parse(&script_coverage.url).unwrap();
-    let file_path = url.to_file_path().unwrap();
-    println!("SF:{}", file_path.to_str().unwrap());
+impl CoverageReport {
+  fn functions_hit(&self) -> usize {
+    self
+      .named_functions
+      .iter()
+      .filter(|function| function.execution_count > 0)
+      .count()
+  }
 
-    let mut functions_found = 0;
-    for function in &script_coverage.functions {
-      if function.function_name.is_empty() {
-        continue;
-      }
+  fn branches_hit(&self) -> usize {
+    self
+      .branches
+      .iter()
+      .filter(|branch| branch.is_hit())
+      .count()
+  }
 
-      let source_line = script_source[0..function.ranges[0].start_offset]
-        .split('\n')
-        .count();
+  fn lines_hit(&self) -> usize {
+    self
+      .found_lines
+      .iter()
+      .filter(|(_, count)| *count != 0)
+      .count()
+  }
 
-      let line_index = if let Some(source_map) = maybe_source_map.as_ref() {
-        source_map
-          .tokens()
-          .find(|token| token.get_dst_line() as usize == source_line)
-          .map(|token| token.get_src_line() as usize)
-          .unwrap_or(0)
-      } else {
-        source_line
-      };
+  /// The hit and found counts of each of the `THRESHOLD_KINDS`.
+  fn threshold_counts(&self) -> [(usize, usize); 3] {
+    [
+      (self.lines_hit(), self.found_lines.len()),
+      (self.branches_hit(), self.branches.len()),
+      (self.functions_hit(), self.named_functions.len()),
+    ]
+  }
+}
+
+/// A construct of the script that branches, with the byte offset at which it
+/// starts and the byte offset at which each of its arms starts. An arm without
+/// an offset is the implicit `else` of an `if` statement.
+struct BranchPoint {
+  offset: usize,
+  arms: Vec<Option<usize>>,
+}
 
-      let function_name = &function.function_name;
+struct BranchCollector<'a> {
+  source_map: &'a swc_common::SourceMap,
+  branch_points: Vec<BranchPoint>,
+}
 
-      println!("FN:{},{}", line_index + 1, function_name);
+impl<'a> BranchCollector<'a> {
+  fn offset(&self, span: Span) -> usize {
+    self.source_map.lookup_byte_offset(span.lo).pos.0 as usize
+  }
 
-      functions_found += 1;
-    }
+  fn push(&mut self, span: Span, arms: Vec<Option<Span>>) {
+    let offset = self.offset(span);
+    let arms = arms
+      .into_iter()
+      .map(|arm| arm.map(|span| self.offset(span)))
+      .collect();
 
-    let mut functions_hit = 0;
-    for function in &script_coverage.functions {
-      if function.function_name.is_empty() {
-        continue;
-      }
+    self.branch_points.push(BranchPoint { offset, arms });
+  }
+}
 
-      let execution_count = function.ranges[0].count;
-      let function_name = &function.function_name;
+impl<'a> Visit for BranchCollector<'a> {
+  fn visit_if_stmt(&mut self, node: &swc_ast::IfStmt, _parent: &dyn Node) {
+    self.push(
+      node.span,
+      vec![
+        Some(node.cons.span()),
+        node.alt.as_ref().map(|alt| alt.span()),
+      ],
+    );
+    node.visit_children_with(self);
+  }
 
-      println!("FNDA:{},{}", execution_count, function_name);
+  fn visit_cond_expr(&mut self, node: &swc_ast::CondExpr, _parent: &dyn Node) {
+    self.push(
+      node.span,
+      vec![Some(node.cons.span()), Some(node.alt.span())],
+    );
+    node.visit_children_with(self);
+  }
 
-      if execution_count != 0 {
-        functions_hit += 1;
-      }
+  fn visit_bin_expr(&mut self, node: &swc_ast::BinExpr, _parent: &dyn Node) {
+    if matches!(
+      node.op,
+      swc_ast::BinaryOp::LogicalAnd
+        | swc_ast::BinaryOp::LogicalOr
+        | swc_ast::BinaryOp::NullishCoalescing
+    ) {
+      self.push(
+        node.span,
+        vec![Some(node.left.span()), Some(node.right.span())],
+      );
     }
+    node.visit_children_with(self);
+  }
 
-    println!("FNF:{}", functions_found);
-    println!("FNH:{}", functions_hit);
-
-    let mut branches_found = 0;
-    let mut branches_hit = 0;
-    for (block_number, function) in script_coverage.functions.iter().enumerate()
-    {
-      let block_hits = function.ranges[0].count;
-      for (branch_number, range) in function.ranges[1..].iter().enumerate() {
-        let source_line =
-          script_source[0..range.start_offset].split('\n').count();
-
-        let line_index = if let Some(source_map) = maybe_source_map.as_ref() {
-          source_map
-            .tokens()
-            .find(|token| token.get_dst_line() as usize == source_line)
-            .map(|token| token.get_src_line() as usize)
-            .unwrap_or(0)
-        } else {
-          source_line
-        };
-
-        // From https://manpages.debian.org/unstable/lcov/geninfo.1.en.html:
-        //
-        // Block number and branch number are gcc internal IDs for the branch. Taken is either '-'
-        // if the basic block containing the branch was never executed or a number indicating how
-        // often that branch was taken.
-        //
-        // However with the data we get from v8 coverage profiles it seems we can't actually hit
-        // this as appears it won't consider any nested branches it hasn't seen but its here for
-        // the sake of accuracy.
-        let taken = if block_hits > 0 {
-          range.count.to_string()
-        } else {
-          "-".to_string()
-        };
+  fn visit_switch_stmt(
+    &mut self,
+    node: &swc_ast::SwitchStmt,
+    _parent: &dyn Node,
+  ) {
+    self.push(
+      node.span,
+      node.cases.iter().map(|case| Some(case.span)).collect(),
+    );
+    node.visit_children_with(self);
+  }
+}
 
-        println!(
-          "BRDA:{},{},{},{}",
-          line_index + 1,
-          block_number,
-          branch_number,
-          taken
-        );
+/// Collects the branch points of the executed script in source order. Scripts
+/// that fail to parse are reported without branches.
+fn collect_branch_points(url: &str, script_source: &str) -> Vec<BranchPoint> {
+  let parsed_module =
+    match ast::parse(url, script_source, &MediaType::JavaScript) {
+      Ok(parsed_module) => parsed_module,
+      Err(_) => return Vec::new(),
+    };
 
-        branches_found += 1;
-        if range.count > 0 {
-          branches_hit += 1;
-        }
-      }
+  let mut collector = BranchCollector {
+    source_map: &parsed_module.source_map,
+    branch_points: Vec::new(),
+  };
+  parsed_module
+    .module
+    .visit_with(&swc_ast::Invalid { span: DUMMY_SP }, &mut collector);
+
+  collector.branch_points
+}
+
+/// Maps each UTF-8 byte offset of `source`, which swc spans are in, to the
+/// UTF-16 offset that V8 reports coverage ranges in.
+fn utf16_offsets(source: &str) -> Vec<usize> {
+  let mut offsets = Vec::with_capacity(source.len() + 1);
+  let mut utf16_offset = 0;
+  for c in source.chars() {
+    offsets.extend(std::iter::repeat(utf16_offset).take(c.len_utf8()));
+    utf16_offset += c.len_utf16();
+  }
+  offsets.push(utf16_offset);
+  offsets
+}
+
+/// V8 reports nested block ranges, so the execution count at an offset is the
+/// count of the innermost range that contains it.
+fn count_at_offset(script_coverage: &ScriptCoverage, offset: usize) -> usize {
+  script_coverage
+    .functions
+    .iter()
+    .flat_map(|function| function.ranges.iter())
+    .filter(|range| range.start_offset <= offset && offset < range.end_offset)
+    .min_by_key(|range| range.end_offset - range.start_offset)
+    .map_or(0, |range| range.count)
+}
+
+fn generate_coverage_report(
+  script_coverage: &ScriptCoverage,
+  script_source: &str,
+  maybe_source_map: &Option<SourceMap>,
+) -> CoverageReport {
+  // The offsets of V8 count UTF-16 code units, so that is what the start and
+  // end offsets of the lines are in.
+  let line_offsets = {
+    let mut offsets: Vec<(usize, usize)> = Vec::new();
+    let mut index = 0;
+
+    for line in script_source.split('\n') {
+      let len = line.encode_utf16().count();
+      offsets.push((index, index + len + 1));
+      index += len + 1;
     }
 
-    println!("BRF:{}", branches_found);
-    println!("BRH:{}", branches_hit);
+    offsets
+  };
 
-    let lines = script_source.split('\n').collect::<Vec<_>>();
-    let line_offsets = {
-      let mut offsets: Vec<(usize, usize)> = Vec::new();
-      let mut index = 0;
+  let original_line_index = |offset: usize| {
+    let source_line = line_offsets
+      .iter()
+      .take_while(|(line_start_offset, _)| *line_start_offset <= offset)
+      .count();
+    if let Some(source_map) = maybe_source_map.as_ref() {
+      source_map
+        .tokens()
+        .find(|token| token.get_dst_line() as usize == source_line)
+        .map(|token| token.get_src_line() as usize)
+        .unwrap_or(0)
+    } else {
+      source_line
+    }
+  };
 
-      for line in &lines {
-        offsets.push((index, index + line.len() + 1));
-        index += line.len() + 1;
-      }
+  let named_functions = script_coverage
+    .functions
+    .iter()
+    .filter(|function| !function.function_name.is_empty())
+    .map(|function| FunctionData {
+      name: function.function_name.clone(),
+      line_index: original_line_index(function.ranges[0].start_offset),
+      execution_count: function.ranges[0].count,
+    })
+    .collect();
+
+  // Unlike the line index of functions, the line index of a branch arm is
+  // looked up from its exact position as several arms can share a line.
+  let branch_line_index = |offset: usize| {
+    let preceding = &script_source[0..offset];
+    let dst_line = preceding.split('\n').count() - 1;
+    let dst_col = offset - preceding.rfind('\n').map_or(0, |index| index + 1);
+    if let Some(source_map) = maybe_source_map.as_ref() {
+      source_map
+        .lookup_token(dst_line as u32, dst_col as u32)
+        .map(|token| token.get_src_line() as usize)
+        .unwrap_or(0)
+    } else {
+      dst_line
+    }
+  };
 
-      offsets
-    };
+  let utf16_offsets = utf16_offsets(script_source);
+  let count_at =
+    |offset: usize| count_at_offset(script_coverage, utf16_offsets[offset]);
 
-    let line_counts = line_offsets
+  let mut branches = Vec::new();
+  for (block_number, branch_point) in
+    collect_branch_points(&script_coverage.url, script_source)
+      .iter()
+      .enumerate()
+  {
+    let block_hits = count_at(branch_point.offset);
+    let explicit_hits: usize = branch_point
+      .arms
       .iter()
-      .map(|(line_start_offset, line_end_offset)| {
-        let mut count = 0;
-
-        // Count the hits of ranges that include the entire line which will always be at-least one
-        // as long as the code has been evaluated.
-        for function in &script_coverage.functions {
-          for range in &function.ranges {
-            if range.start_offset <= *line_start_offset
-              && range.end_offset >= *line_end_offset
-            {
-              count += range.count;
-            }
+      .flatten()
+      .map(|offset| count_at(*offset))
+      .sum();
+
+    for (branch_number, arm) in branch_point.arms.iter().enumerate() {
+      // An `if` statement without an `else` clause still has two arms, the
+      // implicit one is taken whenever the consequent is not.
+      let (offset, count) = match arm {
+        Some(offset) => (*offset, count_at(*offset)),
+        None => (
+          branch_point.offset,
+          block_hits.saturating_sub(explicit_hits),
+        ),
+      };
+
+      // From https://manpages.debian.org/unstable/lcov/geninfo.1.en.html:
+      //
+      // Block number and branch number are gcc internal IDs for the branch. Taken is either '-'
+      // if the basic block containing the branch was never executed or a number indicating how
+      // often that branch was taken.
+      let taken = if block_hits > 0 { Some(count) } else { None };
+
+      branches.push(BranchData {
+        line_index: branch_line_index(offset),
+        block_number,
+        branch_number,
+        taken,
+      });
+    }
+  }
+
+  let line_counts = line_offsets
+    .iter()
+    .map(|(line_start_offset, line_end_offset)| {
+      let mut count = 0;
+
+      // Count the hits of ranges that include the entire line which will always be at-least one
+      // as long as the code has been evaluated.
+      for function in &script_coverage.functions {
+        for range in &function.ranges {
+          if range.start_offset <= *line_start_offset
+            && range.end_offset >= *line_end_offset
+          {
+            count += range.count;
           }
         }
+      }
 
-        // We reset the count if any block with a zero count overlaps with the line range.
-        for function in &script_coverage.functions {
-          for range in &function.ranges {
-            if range.count > 0 {
-              continue;
-            }
-
-            let overlaps = std::cmp::max(line_end_offset, &range.end_offset)
-              - std::cmp::min(line_start_offset, &range.start_offset)
-              < (line_end_offset - line_start_offset)
-                + (range.end_offset - range.start_offset);
-
-            if overlaps {
-              count = 0;
-            }
+      // We reset the count if any block with a zero count overlaps with the line range.
+      for function in &script_coverage.functions {
+        for range in &function.ranges {
+          if range.count > 0 {
+            continue;
           }
-        }
 
-        count
-      })
-      .collect::<Vec<usize>>();
-
-    let found_lines = if let Some(source_map) = maybe_source_map.as_ref() {
-      let mut found_lines = line_counts
-        .iter()
-        .enumerate()
-        .map(|(index, count)| {
-          source_map
-            .tokens()
-            .filter(move |token| token.get_dst_line() as usize == index)
-            .map(move |token| (token.get_src_line() as usize, *count))
-        })
-        .flatten()
-        .collect::<Vec<(usize, usize)>>();
-
-      found_lines.sort_unstable_by_key(|(index, _)| *index);
-      found_lines.dedup_by_key(|(index, _)| *index);
-      found_lines
-    } else {
-      line_counts
-        .iter()
-        .enumerate()
-        .map(|(index, count)| (index, *count))
-        .collect::<Vec<(usize, usize)>>()
-    };
+          let overlaps = std::cmp::max(line_end_offset, &range.end_offset)
+            - std::cmp::min(line_start_offset, &range.start_offset)
+            < (line_end_offset - line_start_offset)
+              + (range.end_offset - range.start_offset);
 
-    for (index, count) in &found_lines {
-      println!("DA:{},{}", index + 1, count);
-    }
+          if overlaps {
+            count = 0;
+          }
+        }
+      }
 
-    let lines_hit = found_lines.iter().filter(|(_, count)| *count != 0).count();
+      count
+    })
+    .collect::<Vec<usize>>();
 
-    println!("LH:{}", lines_hit);
+  let found_lines = if let Some(source_map) = maybe_source_map.as_ref() {
+    let mut found_lines = line_counts
+      .iter()
+      .enumerate()
+      .map(|(index, count)| {
+        source_map
+          .tokens()
+          .filter(move |token| token.get_dst_line() as usize == index)
+          .map(move |token| (token.get_src_line() as usize, *count))
+      })
+      .flatten()
+      .collect::<Vec<(usize, usize)>>();
 
-    let lines_found = found_lines.len();
-    println!("LF:{}", lines_found);
+    found_lines.sort_unstable_by_key(|(index, _)| *index);
+    found_lines.dedup_by_key(|(index, _)| *index);
+    found_lines
+  } else {
+    line_counts
+      .iter()
+      .enumerate()
+      .map(|(index, count)| (index, *count))
+      .collect::<Vec<(usize, usize)>>()
+  };
 
-    println!("end_of_record");
+  CoverageReport {
+    named_functions,
+    branches,
+    found_lines,
   }
-
-  fn done(&mut self) {}
 }
 
-pub struct PrettyCoverageReporter {}
+pub struct LcovCoverageReporter {}
 
-impl PrettyCoverageReporter {
-  pub fn new() -> PrettyCoverageReporter {
-    PrettyCoverageReporter {}
+impl LcovCoverageReporter {
+  pub fn new() -> LcovCoverageReporter {
+    LcovCoverageReporter {}
   }
 }
 
-impl CoverageReporter for PrettyCoverageReporter {
+impl CoverageReporter for LcovCoverageReporter {
   fn visit_coverage(
     &mut self,
     script_coverage: &ScriptCoverage,
-    script_source: &str,
-    maybe_source_map: Option<Vec<u8>>,
-    maybe_original_source: Option<String>,
+    _script_source: &str,
+    _maybe_source_map: &Option<SourceMap>,
+    _maybe_original_source: Option<String>,
+    report: CoverageReport,
   ) {
-    let maybe_source_map = maybe_source_map
-      .map(|source_map| SourceMap::from_slice(&source_map).unwrap());
+    let url = Url::parse(&script_coverage.url).unwrap();
+    let file_path = url.to_file_path().unwrap();
+    println!("SF:{}", file_path.to_str().unwrap());
 
-    let mut ignored_spans: Vec<Span> = Vec::new();
-    for item in ast::lex("", script_source, &MediaType::JavaScript) {
-      if let TokenOrComment::Token(_) = item.inner {
-        continue;
-      }
+    for function in &report.named_functions {
+      println!("FN:{},{}", function.line_index + 1, function.name);
+    }
 
-      ignored_spans.push(item.span);
+    for function in &report.named_functions {
+      println!("FNDA:{},{}", function.execution_count, function.name);
     }
 
-    let lines = script_source.split('\n').collect::<Vec<_>>();
+    println!("FNF:{}", report.named_functions.len());
+    println!("FNH:{}", report.functions_hit());
 
-    let line_offsets = {
-      let mut offsets: Vec<(usize, usize)> = Vec::new();
-      let mut index = 0;
+    for branch in &report.branches {
+      let taken = if let Some(taken) = branch.taken {
+        taken.to_string()
+      } else {
+        "-".to_string()
+      };
 
-      for line in &lines {
-        offsets.push((index, index + line.len() + 1));
-        index += line.len() + 1;
-      }
+      println!(
+        "BRDA:{},{},{},{}",
+        branch.line_index + 1,
+        branch.block_number,
+        branch.branch_number,
+        taken
+      );
+    }
 
-      offsets
-    };
+    println!("BRF:{}", report.branches.len());
+    println!("BRH:{}", report.branches_hit());
 
-    // TODO(caspervonb): collect uncovered ranges on the lines so that we can highlight specific
-    // parts of a line in color (word diff style) instead of the entire line.
-    let line_counts = line_offsets
-      .iter()
-      .enumerate()
-      .map(|(index, (line_start_offset, line_end_offset))| {
-        let ignore = ignored_spans.iter().any(|span| {
-          (span.lo.0 as usize) <= *line_start_offset
-            && (span.hi.0 as usize) >= *line_end_offset
-        });
-
-        if ignore {
-          return (index, 1);
-        }
+    for (index, count) in &report.found_lines {
+      println!("DA:{},{}", index + 1, count);
+    }
 
-        let mut count = 0;
-
-        // Count the hits of ranges that include the entire line which will always be at-least one
-        // as long as the code has been evaluated.
-        for function in &script_coverage.functions {
-          for range in &function.ranges {
-            if range.start_offset <= *line_start_offset
-              && range.end_offset >= *line_end_offset
-            {
-              count += range.count;
-            }
-          }
-        }
+    println!("LH:{}", report.lines_hit());
+    println!("LF:{}", report.found_lines.len());
 
-        // We reset the count if any block with a zero count overlaps with the line range.
-        for function in &script_coverage.functions {
-          for range in &function.ranges {
-            if range.count > 0 {
-              continue;
-            }
-
-            let overlaps = std::cmp::max(line_end_offset, &range.end_offset)
-              - std::cmp::min(line_start_offset, &range.start_offset)
-              < (line_end_offset - line_start_offset)
-                + (range.end_offset - range.start_offset);
-
-            if overlaps {
-              count = 0;
-            }
-          }
-        }
+    println!("end_of_record");
+  }
 
-        (index, count)
-      })
-      .collect::<Vec<(usize, usize)>>();
+  fn done(&mut self) -> Result<(), AnyError> {
+    Ok(())
+  }
+}
 
-    let lines = if let Some(original_source) = maybe_original_source.as_ref() {
-      original_source.split('\n').collect::<Vec<_>>()
-    } else {
-      lines
-    };
+pub struct PrettyCoverageReporter {}
 
-    let line_counts = if let Some(source_map) = maybe_source_map.as_ref() {
-      let mut line_counts = line_counts
-        .iter()
-        .map(|(index, count)| {
-          source_map
-            .tokens()
-            .filter(move |token| token.get_dst_line() as usize == *index)
-            .map(move |token| (token.get_src_line() as usize, *count))
-        })
-        .flatten()
-        .collect::<Vec<(usize, usize)>>();
-
-      line_counts.sort_unstable_by_key(|(index, _)| *index);
-      line_counts.dedup_by_key(|(index, _)| *index);
-
-      line_counts
-    } else {
-      line_counts
-    };
+impl PrettyCoverageReporter {
+  pub fn new() -> PrettyCoverageReporter {
+    PrettyCoverageReporter {}
+  }
+}
 
-    print!("cover {} ... ", script_coverage.url);
+impl CoverageReporter for PrettyCoverageReporter {
+  fn visit_coverage(
+    &mut self,
+    script_coverage: &ScriptCoverage,
+    script_source: &str,
+    _maybe_source_map: &Option<SourceMap>,
+    maybe_original_source: Option<String>,
+    report: CoverageReport,
+  ) {
+    let lines = maybe_original_source
+      .as_deref()
+      .unwrap_or(script_source)
+      .split('\n')
+      .collect::<Vec<_>>();
 
-    let hit_lines = line_counts
-      .iter()
-      .filter(|(_, count)| *count != 0)
-      .map(|(index, _)| *index);
+    print!("cover {} ... ", script_coverage.url);
 
-    let missed_lines = line_counts
+    let missed_lines = report
+      .found_lines
       .iter()
       .filter(|(_, count)| *count == 0)
       .map(|(index, _)| *index);
 
-    let lines_found = line_counts.len();
-    let lines_hit = hit_lines.count();
+    let lines_found = report.found_lines.len();
+    let lines_hit = report.lines_hit();
     let line_ratio = lines_hit as f32 / lines_found as f32;
 
     let line_coverage =
@@ -499,8 +627,24 @@ impl CoverageReporter for PrettyCoverageReporter {
       println!("{}", colors::red(&line_coverage));
     }
 
+    // Lines that were executed but still have an arm of a branch that was
+    // never taken are listed along with the missed lines.
+    let mut untaken_branches: HashMap<usize, usize> = HashMap::new();
+    for branch in &report.branches {
+      if branch.taken == Some(0) {
+        *untaken_branches.entry(branch.line_index).or_insert(0) += 1;
+      }
+    }
+
+    let missed_lines = missed_lines.collect::<Vec<usize>>();
+    let mut listed_lines = missed_lines.clone();
+    listed_lines.extend(untaken_branches.keys().filter(|line_index| {
+      **line_index < lines.len() && !missed_lines.contains(line_index)
+    }));
+    listed_lines.sort_unstable();
+
     let mut last_line = None;
-    for line_index in missed_lines {
+    for line_index in listed_lines {
       const WIDTH: usize = 4;
       const SEPERATOR: &str = "|";
 
@@ -512,19 +656,489 @@ impl CoverageReporter for PrettyCoverageReporter {
         }
       }
 
-      println!(
-        "{:width$} {} {}",
-        line_index + 1,
-        colors::gray(SEPERATOR),
-        colors::red(&lines[line_index]),
-        width = WIDTH
-      );
+      if missed_lines.contains(&line_index) {
+        println!(
+          "{:width$} {} {}",
+          line_index + 1,
+          colors::gray(SEPERATOR),
+          colors::red(&lines[line_index]),
+          width = WIDTH
+        );
+      } else {
+        let untaken = untaken_branches[&line_index];
+        println!(
+          "{:width$} {} {} {}",
+          line_index + 1,
+          colors::gray(SEPERATOR),
+          colors::yellow(&lines[line_index]),
+          colors::gray(format!(
+            "// {} branch{} not taken",
+            untaken,
+            if untaken == 1 { "" } else { "es" }
+          )),
+          width = WIDTH
+        );
+      }
 
       last_line = Some(line_index);
     }
   }
 
-  fn done(&mut self) {}
+  fn done(&mut self) -> Result<(), AnyError> {
+    Ok(())
+  }
+}
+
+/// A module visited by the HTML reporter. The pages are only written once
+/// every module is known, so that they can be laid out relative to the common
+/// root directory of the modules.
+struct HtmlCoverageFile {
+  url: Url,
+  lines: Vec<String>,
+  report: CoverageReport,
+}
+
+pub struct HtmlCoverageReporter {
+  dir: PathBuf,
+  files: Vec<HtmlCoverageFile>,
+}
+
+const HTML_STYLE: &str = "
+body { font-family: sans-serif; margin: 2em; color: #333; }
+a { color: #0366d6; text-decoration: none; }
+table { border-collapse: collapse; }
+th, td { padding: 0.25em 0.75em; text-align: left; }
+.summary th, .summary td { border-bottom: 1px solid #ddd; }
+.summary tfoot td { font-weight: bold; }
+td.high { background: #e6ffed; }
+td.medium { background: #fff5b1; }
+td.low { background: #ffeef0; }
+.source { font-family: monospace; width: 100%; margin-top: 1em; }
+.source td { padding: 0 0.5em; white-space: pre; }
+.source .line-number, .source .count, .source .branches { text-align: right; color: #999; }
+.source tr.hit .count { background: #e6ffed; color: #333; }
+.source tr.miss .code { background: #ffeef0; }
+.source .branches.miss { background: #fff5b1; color: #333; }
+";
+
+impl HtmlCoverageReporter {
+  pub fn new(dir: PathBuf) -> HtmlCoverageReporter {
+    HtmlCoverageReporter {
+      dir,
+      files: Vec::new(),
+    }
+  }
+
+  /// Returns the path of the page of every file relative to the report
+  /// directory, without the common root of the modules.
+  fn page_paths(&self) -> Vec<Vec<String>> {
+    let segments = self
+      .files
+      .iter()
+      .map(|file| {
+        let mut segments = Vec::new();
+        if file.url.scheme() != "file" {
+          segments.push(file.url.scheme().to_string());
+          segments.push(file.url.host_str().unwrap_or("").to_string());
+        }
+        if let Some(path_segments) = file.url.path_segments() {
+          segments.extend(
+            path_segments.filter(|segment| !segment.is_empty()).map(
+              |segment| {
+                percent_encoding::percent_decode_str(segment)
+                  .decode_utf8_lossy()
+                  .to_string()
+              },
+            ),
+          );
+        }
+        segments
+      })
+      .collect::<Vec<_>>();
+
+    // Only directories are part of the common root.
+    let mut common_len = segments
+      .iter()
+      .map(|segments| segments.len().saturating_sub(1))
+      .min()
+      .unwrap_or(0);
+    if let Some(first) = segments.first() {
+      for other in &segments[1..] {
+        common_len = first
+          .iter()
+          .zip(other)
+          .take(common_len)
+          .take_while(|(a, b)| a == b)
+          .count();
+      }
+    }
+
+    segments
+      .into_iter()
+      .map(|segments| segments[common_len..].to_vec())
+      .collect()
+  }
+}
+
+fn escape_html(text: &str) -> String {
+  let mut escaped = String::with_capacity(text.len());
+  for c in text.chars() {
+    match c {
+      '&' => escaped.push_str("&amp;"),
+      '<' => escaped.push_str("&lt;"),
+      '>' => escaped.push_str("&gt;"),
+      '"' => escaped.push_str("&quot;"),
+      '\'' => escaped.push_str("&#39;"),
+      _ => escaped.push(c),
+    }
+  }
+  escaped
+}
+
+/// Formats a coverage ratio as a table cell, colored like the pretty
+/// reporter colors the line coverage.
+fn html_ratio_cell(hit: usize, found: usize) -> String {
+  if found == 0 {
+    return "<td>-</td>".to_string();
+  }
+
+  let ratio = hit as f32 / found as f32;
+  let class = if ratio >= 0.9 {
+    "high"
+  } else if ratio >= 0.75 {
+    "medium"
+  } else {
+    "low"
+  };
+  format!(
+    "<td class=\"{}\">{:.2}% ({}/{})</td>",
+    class,
+    ratio * 100.0,
+    hit,
+    found
+  )
+}
+
+fn html_summary_cells(reports: &[&CoverageReport]) -> String {
+  let sum = |f: &dyn Fn(&CoverageReport) -> usize| {
+    reports.iter().map(|report| f(report)).sum::<usize>()
+  };
+  format!(
+    "{}{}{}",
+    html_ratio_cell(
+      sum(&|report| report.lines_hit()),
+      sum(&|report| report.found_lines.len())
+    ),
+    html_ratio_cell(
+      sum(&|report| report.branches_hit()),
+      sum(&|report| report.branches.len())
+    ),
+    html_ratio_cell(
+      sum(&|report| report.functions_hit()),
+      sum(&|report| report.named_functions.len())
+    ),
+  )
+}
+
+fn html_page(title: &str, body: &str) -> String {
+  format!(
+    "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>{}</title>
+<style>{}</style>
+</head>
+<body>
+{}
+</body>
+</html>
+",
+    escape_html(title),
+    HTML_STYLE,
+    body
+  )
+}
+
+const HTML_SUMMARY_HEADER: &str =
+  "<thead><tr><th>File</th><th>Lines</th><th>Branches</th><th>Functions</th></tr></thead>";
+
+fn html_source_page(
+  file: &HtmlCoverageFile,
+  name: &str,
+  index_href: &str,
+) -> String {
+  let line_counts: HashMap<usize, usize> =
+    file.report.found_lines.iter().cloned().collect();
+  let mut branches: HashMap<usize, (usize, usize)> = HashMap::new();
+  for branch in &file.report.branches {
+    let entry = branches.entry(branch.line_index).or_insert((0, 0));
+    if branch.is_hit() {
+      entry.0 += 1;
+    }
+    entry.1 += 1;
+  }
+
+  let mut rows = String::new();
+  for (index, line) in file.lines.iter().enumerate() {
+    let (class, count) = match line_counts.get(&index) {
+      Some(0) => ("miss", String::new()),
+      Some(count) => ("hit", format!("{}x", count)),
+      None => ("", String::new()),
+    };
+    let branch_cell = match branches.get(&index) {
+      Some((hit, found)) => format!(
+        "<td class=\"branches{}\" title=\"{} of {} branches taken\">{}/{}</td>",
+        if hit < found { " miss" } else { "" },
+        hit,
+        found,
+        hit,
+        found
+      ),
+      None => "<td class=\"branches\"></td>".to_string(),
+    };
+    rows.push_str(&format!(
+      "<tr class=\"{}\"><td class=\"line-number\" id=\"L{}\"><a href=\"#L{}\">{}</a></td><td class=\"count\">{}</td>{}<td class=\"code\">{}</td></tr>\n",
+      class,
+      index + 1,
+      index + 1,
+      index + 1,
+      count,
+      branch_cell,
+      escape_html(line)
+    ));
+  }
+
+  let body = format!(
+    "<h1><a href=\"{}\">All files</a> / {}</h1>
+<table class=\"summary\">
+{}
+<tbody><tr><td>{}</td>{}</tr></tbody>
+</table>
+<table class=\"source\">
+<tbody>
+{}</tbody>
+</table>",
+    escape_html(index_href),
+    escape_html(name),
+    HTML_SUMMARY_HEADER,
+    escape_html(file.url.as_str()),
+    html_summary_cells(&[&file.report]),
+    rows
+  );
+  html_page(name, &body)
+}
+
+impl CoverageReporter for HtmlCoverageReporter {
+  fn visit_coverage(
+    &mut self,
+    script_coverage: &ScriptCoverage,
+    script_source: &str,
+    _maybe_source_map: &Option<SourceMap>,
+    maybe_original_source: Option<String>,
+    report: CoverageReport,
+  ) {
+    // The line indexes of the report are mapped back to the original source,
+    // so that is what is annotated.
+    let lines = maybe_original_source
+      .as_deref()
+      .unwrap_or(script_source)
+      .split('\n')
+      .map(String::from)
+      .collect();
+
+    self.files.push(HtmlCoverageFile {
+      url: Url::parse(&script_coverage.url).unwrap(),
+      lines,
+      report,
+    });
+  }
+
+  fn done(&mut self) -> Result<(), AnyError> {
+    fs::create_dir_all(&self.dir)?;
+
+    let mut rows = String::new();
+    for (file, segments) in self.files.iter().zip(self.page_paths()) {
+      let name = segments.join("/");
+      let href = format!("{}.html", name);
+      let index_href = format!(
+        "{}index.html",
+        "../".repeat(segments.len().saturating_sub(1))
+      );
+
+      let page_path = self.dir.join(&href);
+      if let Some(parent) = page_path.parent() {
+        fs::create_dir_all(parent)?;
+      }
+      fs::write(&page_path, html_source_page(file, &name, &index_href))?;
+
+      rows.push_str(&format!(
+        "<tr><td><a href=\"{}\">{}</a></td>{}</tr>\n",
+        escape_html(&href),
+        escape_html(&name),
+        html_summary_cells(&[&file.report])
+      ));
+    }
+
+    let reports = self
+      .files
+      .iter()
+      .map(|file| &file.report)
+      .collect::<Vec<_>>();
+    let body = format!(
+      "<h1>All files</h1>
+<table class=\"summary\">
+{}
+<tbody>
+{}</tbody>
+<tfoot><tr><td>All files</td>{}</tr></tfoot>
+</table>",
+      HTML_SUMMARY_HEADER,
+      rows,
+      html_summary_cells(&reports)
+    );
+    let index_path = self.dir.join("index.html");
+    fs::write(&index_path, html_page("Coverage report", &body))?;
+
+    println!("HTML coverage report written to {}", index_path.display());
+
+    Ok(())
+  }
+}
+
+/// The kinds of coverage that thresholds apply to, in the order of the counts
+/// returned by `CoverageReport::threshold_counts`.
+const THRESHOLD_KINDS: [(CoverageThresholdKind, &str); 3] = [
+  (CoverageThresholdKind::Lines, "lines"),
+  (CoverageThresholdKind::Branches, "branches"),
+  (CoverageThresholdKind::Functions, "functions"),
+];
+
+/// Describes the thresholds that the hit and found `counts` of `name` fall
+/// short of. For each kind only the last of the `thresholds` applies.
+fn check_thresholds<'a>(
+  name: &str,
+  counts: &[(usize, usize); 3],
+  thresholds: impl Iterator<Item = &'a CoverageThreshold> + Clone,
+) -> Option<String> {
+  let mut shortfalls = Vec::new();
+  for ((kind, kind_name), (hit, found)) in THRESHOLD_KINDS.iter().zip(counts) {
+    let maybe_threshold = thresholds
+      .clone()
+      .filter(|threshold| threshold.kind == *kind)
+      .last();
+    let threshold = match maybe_threshold {
+      Some(threshold) if *found > 0 => threshold,
+      _ => continue,
+    };
+
+    let percent = *hit as f64 * 100.0 / *found as f64;
+    if percent < threshold.percent {
+      shortfalls.push(format!(
+        "{} {:.3}% ({}/{}) < {}%",
+        kind_name, percent, hit, found, threshold.percent
+      ));
+    }
+  }
+
+  if shortfalls.is_empty() {
+    None
+  } else {
+    Some(format!("  {}: {}", name, shortfalls.join(", ")))
+  }
+}
+
+/// The contents of a coverage profile file, either a single script as dumped by
+/// the `CoverageCollector` or the scripts of a profile written by `--merge-out`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum CoverageProfile {
+  Script(ScriptCoverage),
+  Merged(Vec<ScriptCoverage>),
+}
+
+/// Returns the count of the innermost range that contains the given range.
+fn enclosing_range_count(
+  ranges: &[CoverageRange],
+  start_offset: usize,
+  end_offset: usize,
+) -> usize {
+  ranges
+    .iter()
+    .filter(|range| {
+      range.start_offset <= start_offset && end_offset <= range.end_offset
+    })
+    .min_by_key(|range| range.end_offset - range.start_offset)
+    .map_or(0, |range| range.count)
+}
+
+/// Merges two profiles of the same function by summing the counts of their
+/// ranges. V8 only reports the blocks of functions that have been executed, so
+/// a range that is missing from one of the profiles takes the count of its
+/// innermost enclosing range in that profile.
+fn merge_function_coverages(
+  a: &FunctionCoverage,
+  b: &FunctionCoverage,
+) -> FunctionCoverage {
+  let mut bounds = a
+    .ranges
+    .iter()
+    .chain(b.ranges.iter())
+    .map(|range| (range.start_offset, range.end_offset))
+    .collect::<Vec<_>>();
+
+  // Enclosing ranges come before the ranges they contain, which keeps the
+  // range of the function itself first.
+  bounds.sort_unstable_by(|(a_start, a_end), (b_start, b_end)| {
+    a_start.cmp(b_start).then(b_end.cmp(a_end))
+  });
+  bounds.dedup();
+
+  let ranges = bounds
+    .into_iter()
+    .map(|(start_offset, end_offset)| CoverageRange {
+      start_offset,
+      end_offset,
+      count: enclosing_range_count(&a.ranges, start_offset, end_offset)
+        + enclosing_range_count(&b.ranges, start_offset, end_offset),
+    })
+    .collect();
+
+  FunctionCoverage {
+    function_name: a.function_name.clone(),
+    ranges,
+    is_block_coverage: a.is_block_coverage || b.is_block_coverage,
+  }
+}
+
+/// Merges a profile into an existing profile of the same script. Functions are
+/// matched by their name and the bounds of their outermost range as anonymous
+/// functions all share the same empty name.
+fn merge_script_coverages(
+  existing_coverage: &mut ScriptCoverage,
+  new_coverage: ScriptCoverage,
+) {
+  for new_function in new_coverage.functions {
+    let existing_function =
+      existing_coverage.functions.iter_mut().find(|function| {
+        function.function_name == new_function.function_name
+          && function
+            .ranges
+            .first()
+            .map(|range| (range.start_offset, range.end_offset))
+            == new_function
+              .ranges
+              .first()
+              .map(|range| (range.start_offset, range.end_offset))
+      });
+
+    if let Some(existing_function) = existing_function {
+      *existing_function =
+        merge_function_coverages(existing_function, &new_function);
+    } else {
+      existing_coverage.functions.push(new_function);
+    }
+  }
 }
 
 fn collect_coverages(
@@ -538,38 +1152,20 @@ fn collect_coverages(
 
   for file_path in file_paths {
     let json = fs::read_to_string(file_path.as_path())?;
-    let new_coverage: ScriptCoverage = serde_json::from_str(&json)?;
-
-    let existing_coverage =
-      coverages.iter_mut().find(|x| x.url == new_coverage.url);
-
-    if let Some(existing_coverage) = existing_coverage {
-      for new_function in new_coverage.functions {
-        let existing_function = existing_coverage
-          .functions
-          .iter_mut()
-          .find(|x| x.function_name == new_function.function_name);
-
-        if let Some(existing_function) = existing_function {
-          for new_range in new_function.ranges {
-            let existing_range =
-              existing_function.ranges.iter_mut().find(|x| {
-                x.start_offset == new_range.start_offset
-                  && x.end_offset == new_range.end_offset
-              });
-
-            if let Some(existing_range) = existing_range {
-              existing_range.count += new_range.count;
-            } else {
-              existing_function.ranges.push(new_range);
-            }
-          }
-        } else {
-          existing_coverage.functions.push(new_function);
-        }
+    let new_coverages = match serde_json::from_str(&json)? {
+      CoverageProfile::Script(script_coverage) => vec![script_coverage],
+      CoverageProfile::Merged(script_coverages) => script_coverages,
+    };
+
+    for new_coverage in new_coverages {
+      let existing_coverage =
+        coverages.iter_mut().find(|x| x.url == new_coverage.url);
+
+      if let Some(existing_coverage) = existing_coverage {
+        merge_script_coverages(existing_coverage, new_coverage);
+      } else {
+        coverages.push(new_coverage);
       }
-    } else {
-      coverages.push(new_coverage);
     }
   }
 
@@ -604,6 +1200,7 @@ fn filter_coverages(
     .collect::<Vec<ScriptCoverage>>()
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn cover_files(
   flags: Flags,
   files: Vec<PathBuf>,
@@ -611,13 +1208,41 @@ pub async fn cover_files(
   include: Vec<String>,
   exclude: Vec<String>,
   lcov: bool,
+  html: Option<PathBuf>,
+  thresholds: Vec<CoverageThreshold>,
+  merge_out: Option<PathBuf>,
 ) -> Result<(), AnyError> {
   let program_state = ProgramState::build(flags).await?;
+  // Thresholds with a pattern apply to each file that matches it, the others
+  // to the coverage of all files together.
+  let check_coverage = !thresholds.is_empty();
+  let (file_thresholds, total_thresholds): (Vec<_>, Vec<_>) = thresholds
+    .into_iter()
+    .partition(|threshold| threshold.pattern.is_some());
+  let file_thresholds = file_thresholds
+    .into_iter()
+    .map(|threshold| {
+      let regex = Regex::new(threshold.pattern.as_deref().unwrap())?;
+      Ok((regex, threshold))
+    })
+    .collect::<Result<Vec<_>, AnyError>>()?;
+  let mut total_counts = [(0, 0); 3];
+  let mut shortfalls = Vec::new();
 
   let script_coverages = collect_coverages(files, ignore)?;
+
+  // The merged profile keeps every module, so that it can be filtered
+  // differently when it is reported on later.
+  if let Some(merge_out) = merge_out {
+    let json = serde_json::to_string(&script_coverages)?;
+    fs::write(merge_out, json)?;
+  }
+
   let script_coverages = filter_coverages(script_coverages, include, exclude);
 
-  let reporter_kind = if lcov {
+  let reporter_kind = if let Some(dir) = html {
+    CoverageReporterKind::Html(dir)
+  } else if lcov {
     CoverageReporterKind::Lcov
   } else {
     CoverageReporterKind::Pretty
@@ -642,21 +1267,57 @@ pub async fn cover_files(
     let module_source = program_state.load(module_specifier.clone(), None)?;
     let script_source = &module_source.code;
 
-    let maybe_source_map = program_state.get_source_map(&script_coverage.url);
+    let maybe_source_map = program_state
+      .get_source_map(&script_coverage.url)
+      .map(|source_map| SourceMap::from_slice(&source_map).unwrap());
     let maybe_cached_source = program_state
       .file_fetcher
       .get_source(&module_specifier)
       .map(|f| f.source);
 
+    let report = generate_coverage_report(
+      &script_coverage,
+      script_source,
+      &maybe_source_map,
+    );
+    if check_coverage {
+      let counts = report.threshold_counts();
+      for (total, (hit, found)) in total_counts.iter_mut().zip(&counts) {
+        total.0 += hit;
+        total.1 += found;
+      }
+      shortfalls.extend(check_thresholds(
+        &script_coverage.url,
+        &counts,
+        file_thresholds
+          .iter()
+          .filter(|(regex, _)| regex.is_match(&script_coverage.url))
+          .map(|(_, threshold)| threshold),
+      ));
+    }
+
     reporter.visit_coverage(
       &script_coverage,
       &script_source,
-      maybe_source_map,
+      &maybe_source_map,
       maybe_cached_source,
+      report,
     );
   }
 
-  reporter.done();
+  reporter.done()?;
+
+  shortfalls.extend(check_thresholds(
+    "all files",
+    &total_counts,
+    total_thresholds.iter(),
+  ));
+  if !shortfalls.is_empty() {
+    return Err(generic_error(format!(
+      "Coverage is below the threshold:\n{}",
+      shortfalls.join("\n")
+    )));
+  }
 
   Ok(())
 }
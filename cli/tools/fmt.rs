@@ -8,6 +8,7 @@
 //! the same functions as ops available in JS runtime.
 
 use crate::colors;
+use crate::config_file::FmtOptionsConfig;
 use crate::diff::diff;
 use crate::file_watcher;
 use crate::file_watcher::ResolutionResult;
@@ -36,6 +37,7 @@ pub async fn format(
   ignore: Vec<PathBuf>,
   check: bool,
   watch: bool,
+  fmt_options: FmtOptionsConfig,
 ) -> Result<(), AnyError> {
   let resolver = |changed: Option<Vec<PathBuf>>| {
     let files_changed = changed.is_some();
@@ -65,7 +67,7 @@ pub async fn format(
     }
   };
   let operation = |paths: Vec<PathBuf>| {
-    let config = get_typescript_config();
+    let config = get_typescript_config(&fmt_options);
     async move {
       if check {
         check_source_files(config, paths).await?;
@@ -288,12 +290,16 @@ async fn format_source_files(
 /// Format stdin and write result to stdout.
 /// Treats input as TypeScript or as set by `--ext` flag.
 /// Compatible with `--check` flag.
-pub fn format_stdin(check: bool, ext: String) -> Result<(), AnyError> {
+pub fn format_stdin(
+  check: bool,
+  ext: String,
+  fmt_options: FmtOptionsConfig,
+) -> Result<(), AnyError> {
   let mut source = String::new();
   if stdin().read_to_string(&mut source).is_err() {
     return Err(generic_error("Failed to read from stdin"));
   }
-  let config = get_typescript_config();
+  let config = get_typescript_config(&fmt_options);
   let file_path = PathBuf::from(format!("_stdin.{}", ext));
 
   match format_file(&file_path, &source, config) {
@@ -322,10 +328,30 @@ fn files_str(len: usize) -> &'static str {
 }
 
 pub fn get_typescript_config(
+  options: &FmtOptionsConfig,
 ) -> dprint_plugin_typescript::configuration::Configuration {
-  dprint_plugin_typescript::configuration::ConfigurationBuilder::new()
-    .deno()
-    .build()
+  let mut builder =
+    dprint_plugin_typescript::configuration::ConfigurationBuilder::new();
+  builder.deno();
+
+  if let Some(use_tabs) = options.use_tabs {
+    builder.use_tabs(use_tabs);
+  }
+  if let Some(line_width) = options.line_width {
+    builder.line_width(line_width);
+  }
+  if let Some(indent_width) = options.indent_width {
+    builder.indent_width(indent_width);
+  }
+  if let Some(single_quote) = options.single_quote {
+    builder.quote_style(if single_quote {
+      dprint_plugin_typescript::configuration::QuoteStyle::PreferSingle
+    } else {
+      dprint_plugin_typescript::configuration::QuoteStyle::PreferDouble
+    });
+  }
+
+  builder.build()
 }
 
 fn get_markdown_config() -> dprint_plugin_markdown::configuration::Configuration
@@ -8,6 +8,7 @@
 //! the same functions as ops available in JS runtime.
 use crate::ast;
 use crate::colors;
+use crate::config_file::LintRulesConfig;
 use crate::fmt_errors;
 use crate::fs_util::{collect_files, is_supported_ext};
 use crate::media_type::MediaType;
@@ -45,10 +46,13 @@ pub async fn lint_files(
   args: Vec<PathBuf>,
   ignore: Vec<PathBuf>,
   json: bool,
+  rules_config: LintRulesConfig,
 ) -> Result<(), AnyError> {
   if args.len() == 1 && args[0].to_string_lossy() == "-" {
-    return lint_stdin(json);
+    return lint_stdin(json, &rules_config);
   }
+  // Fail early on unknown rule codes rather than once per file.
+  get_configured_rules(&rules_config)?;
   let target_files =
     collect_files(&args, &ignore, is_supported_ext).and_then(|files| {
       if files.is_empty() {
@@ -73,7 +77,7 @@ pub async fn lint_files(
     let reporter_lock = reporter_lock.clone();
     let has_error = has_error.clone();
     move |file_path| {
-      let r = lint_file(file_path.clone());
+      let r = lint_file(file_path.clone(), &rules_config);
       let mut reporter = reporter_lock.lock().unwrap();
 
       match r {
@@ -143,15 +147,53 @@ pub fn create_linter(syntax: Syntax, rules: Vec<Box<dyn LintRule>>) -> Linter {
     .build()
 }
 
+/// Returns the rules selected by `rules_config`, defaulting to the
+/// recommended set.
+pub fn get_configured_rules(
+  rules_config: &LintRulesConfig,
+) -> Result<Vec<Box<dyn LintRule>>, AnyError> {
+  if rules_config.tags.is_none()
+    && rules_config.include.is_none()
+    && rules_config.exclude.is_none()
+  {
+    return Ok(rules::get_recommended_rules());
+  }
+
+  let tags = rules_config
+    .tags
+    .clone()
+    .unwrap_or_else(|| vec!["recommended".to_string()]);
+  let include = rules_config.include.clone().unwrap_or_default();
+  let exclude = rules_config.exclude.clone().unwrap_or_default();
+
+  let all_rules = rules::get_all_rules();
+  for code in include.iter().chain(exclude.iter()) {
+    if !all_rules.iter().any(|rule| rule.code() == code) {
+      return Err(generic_error(format!("Unknown lint rule: {}", code)));
+    }
+  }
+
+  let configured_rules = all_rules
+    .into_iter()
+    .filter(|rule| {
+      let code = rule.code().to_string();
+      let tagged = rule.tags().iter().any(|tag| tags.iter().any(|t| t == tag));
+      (tagged || include.contains(&code)) && !exclude.contains(&code)
+    })
+    .collect();
+  Ok(configured_rules)
+}
+
 fn lint_file(
   file_path: PathBuf,
+  rules_config: &LintRulesConfig,
 ) -> Result<(Vec<LintDiagnostic>, String), AnyError> {
   let file_name = file_path.to_string_lossy().to_string();
   let source_code = fs::read_to_string(&file_path)?;
   let media_type = MediaType::from(&file_path);
   let syntax = ast::get_syntax(&media_type);
 
-  let lint_rules = rules::get_recommended_rules();
+  let lint_rules = get_configured_rules(rules_config)?;
   let linter = create_linter(syntax, lint_rules);
 
   let (_, file_diagnostics) = linter.lint(file_name, source_code.clone())?;
@@ -162,7 +204,10 @@ fn lint_file(
 /// Lint stdin and write result to stdout.
 /// Treats input as TypeScript.
 /// Compatible with `--json` flag.
-fn lint_stdin(json: bool) -> Result<(), AnyError> {
+fn lint_stdin(
+  json: bool,
+  rules_config: &LintRulesConfig,
+) -> Result<(), AnyError> {
   let mut source = String::new();
   if stdin().read_to_string(&mut source).is_err() {
     return Err(generic_error("Failed to read from stdin"));
@@ -174,7 +219,7 @@ fn lint_stdin(json: bool) -> Result<(), AnyError> {
     LintReporterKind::Pretty
   };
   let mut reporter = create_reporter(reporter_kind);
-  let lint_rules = rules::get_recommended_rules();
+  let lint_rules = get_configured_rules(rules_config)?;
   let syntax = ast::get_syntax(&MediaType::TypeScript);
   let linter = create_linter(syntax, lint_rules);
   let mut has_error = false;
@@ -224,6 +224,7 @@ pub fn compile_to_runtime_flags(
     no_check: false,
     prompt: flags.prompt,
     no_remote: false,
+    permission_log: None,
     reload: false,
     repl: false,
     seed: flags.seed,
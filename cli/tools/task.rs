@@ -0,0 +1,704 @@
+// Copyright 2018-2021 the Deno authors. All rights reserved. MIT license.
+
+//! This module implements `deno task`, which runs the named command lines of
+//! the `tasks` section of a config file.
+//!
+//! Tasks are run by a small shell that behaves the same on every platform.
+//! It understands sequential lists (`;`), `&&` and `||`, pipelines, single
+//! and double quotes, `NAME=value` assignments and `$NAME` expansion, and
+//! implements `echo`, `export` and `exit` as builtins. Everything else is
+//! spawned as a process.
+
+use crate::colors;
+use crate::config_file::ConfigFile;
+use deno_core::error::generic_error;
+use deno_core::error::AnyError;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::Child;
+use std::process::ChildStdout;
+use std::process::Command;
+use std::process::Stdio;
+
+/// Runs `task_name` from the config file with `args` appended to its command
+/// line, returning the exit code. Lists the available tasks when no name is
+/// given.
+pub fn execute_task(
+  config_file: &ConfigFile,
+  maybe_task_name: Option<String>,
+  args: &[String],
+) -> Result<i32, AnyError> {
+  let tasks = config_file.to_tasks_config()?.unwrap_or_default();
+  let task_name = match maybe_task_name {
+    Some(task_name) => task_name,
+    None => {
+      print_available_tasks(&tasks);
+      return Ok(0);
+    }
+  };
+  let command_line = match tasks.get(&task_name) {
+    Some(command_line) => command_line,
+    None => {
+      eprintln!("Task not found: {}", task_name);
+      print_available_tasks(&tasks);
+      return Ok(1);
+    }
+  };
+
+  let command_line = args.iter().fold(command_line.clone(), |acc, arg| {
+    format!("{} {}", acc, quote(arg))
+  });
+  eprintln!(
+    "{} {} {}",
+    colors::green("Task"),
+    colors::cyan(&task_name),
+    command_line
+  );
+
+  let list = parse(&command_line)?;
+  let mut shell = Shell::new(config_file.dir().to_path_buf());
+  shell.run_list(&list)
+}
+
+fn print_available_tasks(tasks: &std::collections::BTreeMap<String, String>) {
+  eprintln!("{}", colors::green("Available tasks:"));
+  if tasks.is_empty() {
+    eprintln!("  {}", colors::red("No tasks found in configuration file"));
+  }
+  for (name, command_line) in tasks {
+    eprintln!("- {}", colors::cyan(name));
+    eprintln!("    {}", command_line);
+  }
+}
+
+/// Quotes an argument so the shell passes it through unchanged.
+fn quote(arg: &str) -> String {
+  format!("'{}'", arg.replace('\'', "'\"'\"'"))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum WordPart {
+  Text(String),
+  Var(String),
+}
+
+type Word = Vec<WordPart>;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+  Word(Word),
+  And,
+  Or,
+  Pipe,
+  Semicolon,
+}
+
+fn push_char(word: &mut Word, c: char) {
+  match word.last_mut() {
+    Some(WordPart::Text(text)) => text.push(c),
+    _ => word.push(WordPart::Text(c.to_string())),
+  }
+}
+
+fn is_name_char(c: char) -> bool {
+  c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Reads the name of a variable following a `$`, either `NAME` or `{NAME}`.
+/// A `$` that is not followed by a name is kept as text.
+fn read_var(
+  chars: &mut std::iter::Peekable<std::str::Chars>,
+  word: &mut Word,
+) -> Result<(), AnyError> {
+  let mut name = String::new();
+  if chars.peek() == Some(&'{') {
+    chars.next();
+    loop {
+      match chars.next() {
+        Some('}') => break,
+        Some(c) => name.push(c),
+        None => return Err(generic_error("Unterminated '${' in task")),
+      }
+    }
+  } else {
+    while let Some(&c) = chars.peek() {
+      if !is_name_char(c) {
+        break;
+      }
+      name.push(c);
+      chars.next();
+    }
+  }
+  if name.is_empty() {
+    push_char(word, '$');
+  } else {
+    word.push(WordPart::Var(name));
+  }
+  Ok(())
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, AnyError> {
+  let mut tokens = Vec::new();
+  let mut chars = input.chars().peekable();
+  let mut word = Word::new();
+  let mut in_word = false;
+
+  macro_rules! finish_word {
+    () => {
+      if in_word {
+        tokens.push(Token::Word(std::mem::take(&mut word)));
+        in_word = false;
+      }
+    };
+  }
+
+  while let Some(c) = chars.next() {
+    match c {
+      ' ' | '\t' | '\r' | '\n' => finish_word!(),
+      '&' => {
+        finish_word!();
+        if chars.next() != Some('&') {
+          return Err(generic_error(
+            "Running commands in the background with '&' is not supported",
+          ));
+        }
+        tokens.push(Token::And);
+      }
+      '|' => {
+        finish_word!();
+        if chars.peek() == Some(&'|') {
+          chars.next();
+          tokens.push(Token::Or);
+        } else {
+          tokens.push(Token::Pipe);
+        }
+      }
+      ';' => {
+        finish_word!();
+        tokens.push(Token::Semicolon);
+      }
+      '\'' => {
+        in_word = true;
+        if !matches!(word.last(), Some(WordPart::Text(_))) {
+          word.push(WordPart::Text(String::new()));
+        }
+        loop {
+          match chars.next() {
+            Some('\'') => break,
+            Some(c) => push_char(&mut word, c),
+            None => return Err(generic_error("Unterminated ' in task")),
+          }
+        }
+      }
+      '"' => {
+        in_word = true;
+        if !matches!(word.last(), Some(WordPart::Text(_))) {
+          word.push(WordPart::Text(String::new()));
+        }
+        loop {
+          match chars.next() {
+            Some('"') => break,
+            Some('\\') => match chars.next() {
+              Some(c) if matches!(c, '"' | '\\' | '$') => {
+                push_char(&mut word, c)
+              }
+              Some(c) => {
+                push_char(&mut word, '\\');
+                push_char(&mut word, c);
+              }
+              None => return Err(generic_error("Unterminated \" in task")),
+            },
+            Some('$') => read_var(&mut chars, &mut word)?,
+            Some(c) => push_char(&mut word, c),
+            None => return Err(generic_error("Unterminated \" in task")),
+          }
+        }
+      }
+      '\\' => {
+        in_word = true;
+        if let Some(c) = chars.next() {
+          push_char(&mut word, c);
+        }
+      }
+      '$' => {
+        in_word = true;
+        read_var(&mut chars, &mut word)?;
+      }
+      c => {
+        in_word = true;
+        push_char(&mut word, c);
+      }
+    }
+  }
+  if in_word {
+    tokens.push(Token::Word(word));
+  }
+
+  Ok(tokens)
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct SimpleCommand {
+  env: Vec<(String, Word)>,
+  args: Vec<Word>,
+}
+
+type Pipeline = Vec<SimpleCommand>;
+
+#[derive(Debug, PartialEq)]
+enum Connector {
+  And,
+  Or,
+}
+
+#[derive(Debug, PartialEq)]
+struct AndOrList {
+  first: Pipeline,
+  rest: Vec<(Connector, Pipeline)>,
+}
+
+type SequentialList = Vec<AndOrList>;
+
+/// Splits `NAME=value` into its name and value, if `word` is an assignment.
+fn as_assignment(word: &[WordPart]) -> Option<(String, Word)> {
+  let text = match word.first() {
+    Some(WordPart::Text(text)) => text,
+    _ => return None,
+  };
+  let (name, value) = text.split_at(text.find('=')?);
+  if name.is_empty()
+    || name.starts_with(|c: char| c.is_ascii_digit())
+    || !name.chars().all(is_name_char)
+  {
+    return None;
+  }
+  let mut value_word = vec![WordPart::Text(value[1..].to_string())];
+  value_word.extend_from_slice(&word[1..]);
+  Some((name.to_string(), value_word))
+}
+
+fn parse(input: &str) -> Result<SequentialList, AnyError> {
+  let mut list = SequentialList::new();
+  let mut pipelines: Vec<(Option<Connector>, Pipeline)> = Vec::new();
+  let mut pipeline = Pipeline::new();
+  let mut command = SimpleCommand::default();
+  let mut connector = None;
+
+  let unexpected =
+    |token: &str| generic_error(format!("Unexpected '{}' in task", token));
+
+  let mut tokens = tokenize(input)?.into_iter().peekable();
+  while let Some(token) = tokens.next() {
+    let op = match token {
+      Token::Word(word) => {
+        match as_assignment(&word) {
+          Some(assignment) if command.args.is_empty() => {
+            command.env.push(assignment)
+          }
+          _ => command.args.push(word),
+        }
+        continue;
+      }
+      Token::Pipe => "|",
+      Token::And => "&&",
+      Token::Or => "||",
+      Token::Semicolon => ";",
+    };
+
+    if command == SimpleCommand::default() {
+      return Err(unexpected(op));
+    }
+    pipeline.push(std::mem::take(&mut command));
+    if op == "|" {
+      continue;
+    }
+    pipelines.push((connector.take(), std::mem::take(&mut pipeline)));
+    match op {
+      "&&" => connector = Some(Connector::And),
+      "||" => connector = Some(Connector::Or),
+      _ => {
+        list.push(to_and_or_list(std::mem::take(&mut pipelines)));
+      }
+    }
+    if op != ";" && tokens.peek().is_none() {
+      return Err(generic_error(format!("Task ends with '{}'", op)));
+    }
+  }
+
+  if command != SimpleCommand::default() {
+    pipeline.push(command);
+  } else if !pipeline.is_empty() {
+    return Err(generic_error("Task ends with '|'"));
+  }
+  if !pipeline.is_empty() {
+    pipelines.push((connector.take(), pipeline));
+  }
+  if !pipelines.is_empty() {
+    list.push(to_and_or_list(pipelines));
+  }
+
+  Ok(list)
+}
+
+fn to_and_or_list(pipelines: Vec<(Option<Connector>, Pipeline)>) -> AndOrList {
+  let mut pipelines = pipelines.into_iter();
+  let (_, first) = pipelines.next().expect("at least one pipeline");
+  AndOrList {
+    first,
+    rest: pipelines
+      .map(|(connector, pipeline)| {
+        (
+          connector.expect("pipelines after the first are connected"),
+          pipeline,
+        )
+      })
+      .collect(),
+  }
+}
+
+/// Where a pipeline stage reads its input from.
+enum StageInput {
+  Inherit,
+  Bytes(Vec<u8>),
+  Child(ChildStdout),
+}
+
+enum Stage {
+  Builtin { code: i32, output: Vec<u8> },
+  Child(Child),
+}
+
+struct Shell {
+  cwd: PathBuf,
+  env: HashMap<String, String>,
+  exit_code: Option<i32>,
+}
+
+impl Shell {
+  fn new(cwd: PathBuf) -> Self {
+    Self {
+      cwd,
+      env: std::env::vars().collect(),
+      exit_code: None,
+    }
+  }
+
+  fn expand(&self, word: &[WordPart]) -> String {
+    word
+      .iter()
+      .map(|part| match part {
+        WordPart::Text(text) => text.clone(),
+        WordPart::Var(name) => self.env.get(name).cloned().unwrap_or_default(),
+      })
+      .collect()
+  }
+
+  fn run_list(&mut self, list: &[AndOrList]) -> Result<i32, AnyError> {
+    let mut code = 0;
+    for and_or in list {
+      code = self.run_pipeline(&and_or.first)?;
+      for (connector, pipeline) in &and_or.rest {
+        if self.exit_code.is_some() {
+          break;
+        }
+        code = match connector {
+          Connector::And if code == 0 => self.run_pipeline(pipeline)?,
+          Connector::Or if code != 0 => self.run_pipeline(pipeline)?,
+          _ => code,
+        };
+      }
+      if let Some(exit_code) = self.exit_code {
+        return Ok(exit_code);
+      }
+    }
+    Ok(code)
+  }
+
+  fn run_pipeline(
+    &mut self,
+    pipeline: &[SimpleCommand],
+  ) -> Result<i32, AnyError> {
+    let last = pipeline.len() - 1;
+    let mut input = StageInput::Inherit;
+    let mut children: Vec<(usize, Child)> = Vec::new();
+    let mut code = 0;
+
+    for (i, command) in pipeline.iter().enumerate() {
+      let piped = i != last;
+      let stage = match self.start_stage(command, input, piped) {
+        Ok(stage) => stage,
+        Err(err) => {
+          // Don't leave the earlier stages of the pipeline running.
+          for (_, mut child) in children {
+            let _ = child.kill();
+            let _ = child.wait();
+          }
+          return Err(err);
+        }
+      };
+      match stage {
+        Stage::Builtin { code: c, output } => {
+          code = c;
+          input = StageInput::Bytes(output);
+        }
+        Stage::Child(mut child) => {
+          input = match child.stdout.take() {
+            Some(stdout) => StageInput::Child(stdout),
+            None => StageInput::Inherit,
+          };
+          children.push((i, child));
+        }
+      }
+    }
+
+    for (i, mut child) in children {
+      let status = child.wait()?;
+      if i == last {
+        code = status.code().unwrap_or(1);
+      }
+    }
+    Ok(code)
+  }
+
+  fn start_stage(
+    &mut self,
+    command: &SimpleCommand,
+    input: StageInput,
+    piped: bool,
+  ) -> Result<Stage, AnyError> {
+    let env: Vec<(String, String)> = command
+      .env
+      .iter()
+      .map(|(name, value)| (name.clone(), self.expand(value)))
+      .collect();
+    let args: Vec<String> =
+      command.args.iter().map(|arg| self.expand(arg)).collect();
+
+    // A command consisting only of assignments sets shell variables.
+    if args.is_empty() {
+      self.env.extend(env);
+      return Ok(Stage::Builtin {
+        code: 0,
+        output: Vec::new(),
+      });
+    }
+
+    match args[0].as_str() {
+      "echo" => {
+        let line = format!("{}\n", args[1..].join(" "));
+        if piped {
+          return Ok(Stage::Builtin {
+            code: 0,
+            output: line.into_bytes(),
+          });
+        }
+        let mut stdout = std::io::stdout();
+        stdout.write_all(line.as_bytes())?;
+        stdout.flush()?;
+        return Ok(Stage::Builtin {
+          code: 0,
+          output: Vec::new(),
+        });
+      }
+      "export" => {
+        for arg in &args[1..] {
+          match arg.find('=') {
+            Some(index) => {
+              self
+                .env
+                .insert(arg[..index].to_string(), arg[index + 1..].to_string());
+            }
+            None => {
+              return Err(generic_error(format!(
+                "export: expected NAME=value, found '{}'",
+                arg
+              )))
+            }
+          }
+        }
+        return Ok(Stage::Builtin {
+          code: 0,
+          output: Vec::new(),
+        });
+      }
+      "exit" => {
+        let code = match args.get(1) {
+          Some(code) => code.parse().map_err(|_| {
+            generic_error(format!("exit: invalid exit code '{}'", code))
+          })?,
+          None => 0,
+        };
+        self.exit_code = Some(code);
+        return Ok(Stage::Builtin {
+          code,
+          output: Vec::new(),
+        });
+      }
+      _ => {}
+    }
+
+    let mut process = Command::new(&args[0]);
+    process
+      .args(&args[1..])
+      .current_dir(&self.cwd)
+      .env_clear()
+      .envs(&self.env)
+      .envs(env);
+    if piped {
+      process.stdout(Stdio::piped());
+    }
+    let mut pending_input = None;
+    match input {
+      StageInput::Inherit => {}
+      StageInput::Child(stdout) => {
+        process.stdin(stdout);
+      }
+      StageInput::Bytes(bytes) => {
+        process.stdin(Stdio::piped());
+        pending_input = Some(bytes);
+      }
+    }
+
+    let mut child = match process.spawn() {
+      Ok(child) => child,
+      Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+        eprintln!("{}: command not found", args[0]);
+        return Ok(Stage::Builtin {
+          code: 127,
+          output: Vec::new(),
+        });
+      }
+      Err(err) => return Err(err.into()),
+    };
+    if let (Some(bytes), Some(mut stdin)) = (pending_input, child.stdin.take())
+    {
+      // Written on another thread so a child that does not drain its stdin
+      // can't block the shell.
+      std::thread::spawn(move || stdin.write_all(&bytes));
+    }
+    Ok(Stage::Child(child))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn text(s: &str) -> WordPart {
+    WordPart::Text(s.to_string())
+  }
+
+  fn var(s: &str) -> WordPart {
+    WordPart::Var(s.to_string())
+  }
+
+  #[test]
+  fn tokenize_quotes_and_vars() {
+    let tokens =
+      tokenize(r#"echo 'a $b' "c $D ${E}f" g\ h $ "" && x||y|z;"#).unwrap();
+    assert_eq!(
+      tokens,
+      vec![
+        Token::Word(vec![text("echo")]),
+        Token::Word(vec![text("a $b")]),
+        Token::Word(vec![text("c "), var("D"), text(" "), var("E"), text("f")]),
+        Token::Word(vec![text("g h")]),
+        Token::Word(vec![text("$")]),
+        Token::Word(vec![text("")]),
+        Token::And,
+        Token::Word(vec![text("x")]),
+        Token::Or,
+        Token::Word(vec![text("y")]),
+        Token::Pipe,
+        Token::Word(vec![text("z")]),
+        Token::Semicolon,
+      ]
+    );
+  }
+
+  #[test]
+  fn tokenize_errors() {
+    assert!(tokenize("echo 'a").is_err());
+    assert!(tokenize("echo \"a").is_err());
+    assert!(tokenize("sleep 1 &").is_err());
+  }
+
+  #[test]
+  fn parse_lists_and_pipelines() {
+    let list = parse("FOO=bar cmd a | grep b && c; d || e").unwrap();
+    assert_eq!(list.len(), 2);
+    assert_eq!(
+      list[0].first,
+      vec![
+        SimpleCommand {
+          env: vec![("FOO".to_string(), vec![text("bar")])],
+          args: vec![vec![text("cmd")], vec![text("a")]],
+        },
+        SimpleCommand {
+          env: vec![],
+          args: vec![vec![text("grep")], vec![text("b")]],
+        },
+      ]
+    );
+    assert_eq!(list[0].rest.len(), 1);
+    assert_eq!(list[0].rest[0].0, Connector::And);
+    assert_eq!(list[1].rest.len(), 1);
+    assert_eq!(list[1].rest[0].0, Connector::Or);
+  }
+
+  #[test]
+  fn parse_assignment_only_after_command_name() {
+    let list = parse("cmd A=b").unwrap();
+    assert_eq!(
+      list[0].first[0],
+      SimpleCommand {
+        env: vec![],
+        args: vec![vec![text("cmd")], vec![text("A=b")]],
+      }
+    );
+  }
+
+  #[test]
+  fn parse_errors() {
+    assert!(parse("&& a").is_err());
+    assert!(parse("a &&").is_err());
+    assert!(parse("a |").is_err());
+    assert!(parse("a | | b").is_err());
+    assert!(parse("a; ; b").is_err());
+  }
+
+  #[test]
+  fn quote_args() {
+    assert_eq!(quote("a b"), "'a b'");
+    assert_eq!(quote("it's"), "'it'\"'\"'s'");
+    let tokens = tokenize(&quote("it's $HOME")).unwrap();
+    assert_eq!(tokens, vec![Token::Word(vec![text("it's $HOME")])]);
+  }
+
+  #[test]
+  fn run_builtins_and_connectors() {
+    let cwd = std::env::current_dir().unwrap();
+    let mut shell = Shell::new(cwd.clone());
+    let list = parse("A=1 && export B=$A && exit 3 && exit 4").unwrap();
+    assert_eq!(shell.run_list(&list).unwrap(), 3);
+    assert_eq!(shell.env.get("A"), Some(&"1".to_string()));
+    assert_eq!(shell.env.get("B"), Some(&"1".to_string()));
+
+    let mut shell = Shell::new(cwd);
+    let list = parse("exit 2 || exit 5").unwrap();
+    assert_eq!(shell.run_list(&list).unwrap(), 2);
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn run_pipeline_kills_started_stages_on_error() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let marker = temp_dir.path().join("marker");
+    let mut shell = Shell::new(temp_dir.path().to_path_buf());
+    let list = parse("sh -c 'sleep 1 && touch marker' | export A").unwrap();
+    assert!(shell.run_list(&list).is_err());
+    std::thread::sleep(std::time::Duration::from_secs(2));
+    assert!(!marker.exists());
+  }
+}
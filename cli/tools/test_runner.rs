@@ -4,6 +4,7 @@ use crate::ast;
 use crate::colors;
 use crate::create_main_worker;
 use crate::file_fetcher::File;
+use crate::flags::TestReporterKind;
 use crate::fs_util::collect_files;
 use crate::fs_util::normalize_path;
 use crate::media_type::MediaType;
@@ -16,12 +17,21 @@ use deno_core::futures::future;
 use deno_core::futures::stream;
 use deno_core::futures::FutureExt;
 use deno_core::futures::StreamExt;
+use deno_core::serde_json;
 use deno_core::serde_json::json;
+use deno_core::serde_json::Value;
 use deno_core::url::Url;
 use deno_core::ModuleSpecifier;
 use deno_runtime::permissions::Permissions;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
 use regex::Regex;
 use serde::Deserialize;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::io::BufWriter;
+use std::io::Write;
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::mpsc::channel;
@@ -54,6 +64,16 @@ pub enum TestMessage {
     duration: usize,
     result: TestResult,
   },
+  StepWait {
+    name: String,
+    level: usize,
+  },
+  StepResult {
+    name: String,
+    level: usize,
+    duration: usize,
+    result: TestResult,
+  },
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -63,8 +83,8 @@ pub struct TestEvent {
 }
 
 trait TestReporter {
-  fn visit_event(&mut self, event: TestEvent);
-  fn done(&mut self);
+  fn visit_event(&mut self, event: TestEvent) -> std::io::Result<()>;
+  fn done(&mut self) -> std::io::Result<()>;
 }
 
 struct PrettyTestReporter {
@@ -77,10 +97,21 @@ struct PrettyTestReporter {
   pending: usize,
   failures: Vec<(String, String)>,
   concurrent: bool,
+  /// Events of the tests that are still running, by origin. Only used when
+  /// running concurrently, so that the steps of a test are printed together.
+  buffered_events: HashMap<String, Vec<TestEvent>>,
+  /// The names of the running test and its running steps.
+  scope: Vec<String>,
+  /// Whether the last line printed is a "test ..." line awaiting its result.
+  line_open: bool,
+  output: Box<dyn Write + Send>,
 }
 
 impl PrettyTestReporter {
-  fn new(concurrent: bool) -> PrettyTestReporter {
+  fn new(
+    concurrent: bool,
+    output: Box<dyn Write + Send>,
+  ) -> PrettyTestReporter {
     PrettyTestReporter {
       time: Instant::now(),
       failed: 0,
@@ -91,12 +122,63 @@ impl PrettyTestReporter {
       pending: 0,
       failures: Vec::new(),
       concurrent,
+      buffered_events: HashMap::new(),
+      scope: Vec::new(),
+      line_open: false,
+      output,
     }
   }
-}
 
-impl TestReporter for PrettyTestReporter {
-  fn visit_event(&mut self, event: TestEvent) {
+  fn write_wait(&mut self, name: &str, level: usize) -> std::io::Result<()> {
+    if self.line_open {
+      writeln!(self.output)?;
+    }
+    write!(self.output, "{}test {} ...", "  ".repeat(level), name)?;
+    self.line_open = true;
+    self.scope.truncate(level);
+    self.scope.push(name.to_string());
+    Ok(())
+  }
+
+  fn write_result(
+    &mut self,
+    level: usize,
+    duration: usize,
+    result: &TestResult,
+  ) -> std::io::Result<()> {
+    let status = match result {
+      TestResult::Ok => colors::green("ok").to_string(),
+      TestResult::Ignored => colors::yellow("ignored").to_string(),
+      TestResult::Failed(_) => colors::red("FAILED").to_string(),
+    };
+    // A step that was not awaited may have left its line open.
+    if self.line_open && self.scope.len() == level + 1 {
+      write!(self.output, " ")?;
+    } else {
+      if self.line_open {
+        writeln!(self.output)?;
+      }
+      write!(self.output, "{}", "  ".repeat(level))?;
+    }
+    writeln!(
+      self.output,
+      "{} {}",
+      status,
+      colors::gray(format!("({}ms)", duration))
+    )?;
+    self.line_open = false;
+
+    self.scope.truncate(level + 1);
+    if let TestResult::Failed(error) = result {
+      self
+        .failures
+        .push((self.scope.join(" ... "), error.to_string()));
+    }
+    self.scope.truncate(level);
+    Ok(())
+  }
+
+  fn write_event(&mut self, event: TestEvent) -> std::io::Result<()> {
     match &event.message {
       TestMessage::Plan {
         pending,
@@ -104,9 +186,17 @@ impl TestReporter for PrettyTestReporter {
         only: _,
       } => {
         if *pending == 1 {
-          println!("running {} test from {}", pending, event.origin);
+          writeln!(
+            self.output,
+            "running {} test from {}",
+            pending, event.origin
+          )?;
         } else {
-          println!("running {} tests from {}", pending, event.origin);
+          writeln!(
+            self.output,
+            "running {} tests from {}",
+            pending, event.origin
+          )?;
         }
 
         self.pending += pending;
@@ -114,68 +204,84 @@ impl TestReporter for PrettyTestReporter {
       }
 
       TestMessage::Wait { name } => {
-        if !self.concurrent {
-          print!("test {} ...", name);
-        }
+        self.write_wait(name, 0)?;
+      }
+
+      TestMessage::StepWait { name, level } => {
+        self.write_wait(name, *level)?;
+      }
+
+      TestMessage::StepResult {
+        name: _,
+        level,
+        duration,
+        result,
+      } => {
+        self.write_result(*level, *duration, result)?;
       }
 
       TestMessage::Result {
-        name,
+        name: _,
         duration,
         result,
       } => {
         self.pending -= 1;
 
-        if self.concurrent {
-          print!("test {} ...", name);
+        match result {
+          TestResult::Ok => self.passed += 1,
+          TestResult::Ignored => self.ignored += 1,
+          TestResult::Failed(_) => self.failed += 1,
         }
 
-        match result {
-          TestResult::Ok => {
-            println!(
-              " {} {}",
-              colors::green("ok"),
-              colors::gray(format!("({}ms)", duration))
-            );
+        self.write_result(0, *duration, result)?;
+      }
+    }
 
-            self.passed += 1;
-          }
-          TestResult::Ignored => {
-            println!(
-              " {} {}",
-              colors::yellow("ignored"),
-              colors::gray(format!("({}ms)", duration))
-            );
+    Ok(())
+  }
+}
 
-            self.ignored += 1;
-          }
-          TestResult::Failed(error) => {
-            println!(
-              " {} {}",
-              colors::red("FAILED"),
-              colors::gray(format!("({}ms)", duration))
-            );
-
-            self.failed += 1;
-            self.failures.push((name.to_string(), error.to_string()));
-          }
+impl TestReporter for PrettyTestReporter {
+  fn visit_event(&mut self, event: TestEvent) -> std::io::Result<()> {
+    if !self.concurrent {
+      return self.write_event(event);
+    }
+
+    match &event.message {
+      TestMessage::Plan { .. } => self.write_event(event),
+      TestMessage::Result { .. } => {
+        let buffered_events = self
+          .buffered_events
+          .remove(&event.origin)
+          .unwrap_or_default();
+        for buffered_event in buffered_events {
+          self.write_event(buffered_event)?;
         }
+        self.write_event(event)
+      }
+      _ => {
+        self
+          .buffered_events
+          .entry(event.origin.clone())
+          .or_default()
+          .push(event);
+        Ok(())
       }
     }
   }
 
-  fn done(&mut self) {
+  fn done(&mut self) -> std::io::Result<()> {
     if !self.failures.is_empty() {
-      println!("\nfailures:\n");
+      writeln!(self.output, "\nfailures:\n")?;
       for (name, error) in &self.failures {
-        println!("{}", name);
-        println!("{}", error);
-        println!();
+        writeln!(self.output, "{}", name)?;
+        writeln!(self.output, "{}", error)?;
+        writeln!(self.output)?;
       }
 
-      println!("failures:\n");
+      writeln!(self.output, "failures:\n")?;
       for (name, _) in &self.failures {
-        println!("\t{}", name);
+        writeln!(self.output, "\t{}", name)?;
       }
     }
 
@@ -185,7 +291,8 @@ impl TestReporter for PrettyTestReporter {
       colors::green("ok").to_string()
     };
 
-    println!(
+    writeln!(
+        self.output,
         "\ntest result: {}. {} passed; {} failed; {} ignored; {} measured; {} filtered out {}\n",
         status,
         self.passed,
@@ -194,12 +301,448 @@ impl TestReporter for PrettyTestReporter {
         self.measured,
         self.filtered_out,
         colors::gray(format!("({}ms)", self.time.elapsed().as_millis())),
+      )?;
+
+    self.output.flush()
+  }
+}
+
+/// A finished test or test step, as recorded by the reporters that write
+/// their report once all tests have run.
+struct TestCaseReport {
+  origin: String,
+  name: String,
+  duration: usize,
+  result: TestResult,
+  /// The steps that finished, in the order they finished.
+  steps: Vec<TestCaseReport>,
+}
+
+impl TestCaseReport {
+  /// Returns the test followed by all of its steps, each named after the
+  /// steps and test it is nested in.
+  fn flatten(self) -> Vec<TestCaseReport> {
+    let mut cases = Vec::new();
+    let mut pending = vec![(String::new(), self)];
+    while let Some((prefix, mut case)) = pending.pop() {
+      let name = format!("{}{}", prefix, case.name);
+      let steps = std::mem::take(&mut case.steps);
+      pending.extend(
+        steps
+          .into_iter()
+          .rev()
+          .map(|step| (format!("{} ... ", name), step)),
       );
+      cases.push(TestCaseReport { name, ..case });
+    }
+    cases
+  }
+}
+
+/// Assembles the steps reported for the running test of each module into
+/// the `TestCaseReport` of that test.
+#[derive(Default)]
+struct TestCaseCollector {
+  /// For the running test of each module and its running steps, by level,
+  /// the steps that finished so far.
+  running: HashMap<String, Vec<Vec<TestCaseReport>>>,
+}
+
+impl TestCaseCollector {
+  /// Records the event, returning the report of the test it finishes.
+  fn visit_event(&mut self, event: TestEvent) -> Option<TestCaseReport> {
+    match event.message {
+      TestMessage::Plan { .. } => None,
+      TestMessage::Wait { .. } => {
+        self.running.insert(event.origin, vec![Vec::new()]);
+        None
+      }
+      TestMessage::StepWait { level, .. } => {
+        let running = self.running.entry(event.origin).or_default();
+        running.truncate(level);
+        running.push(Vec::new());
+        None
+      }
+      TestMessage::StepResult {
+        name,
+        level,
+        duration,
+        result,
+      } => {
+        let running = self.running.entry(event.origin.clone()).or_default();
+        running.truncate(level + 1);
+        let steps = if running.len() == level + 1 {
+          running.pop().unwrap_or_default()
+        } else {
+          Vec::new()
+        };
+        if let Some(siblings) = running.last_mut() {
+          siblings.push(TestCaseReport {
+            origin: event.origin,
+            name,
+            duration,
+            result,
+            steps,
+          });
+        }
+        None
+      }
+      TestMessage::Result {
+        name,
+        duration,
+        result,
+      } => {
+        let steps = self
+          .running
+          .remove(&event.origin)
+          .and_then(|mut running| running.drain(..).next())
+          .unwrap_or_default();
+        Some(TestCaseReport {
+          origin: event.origin,
+          name,
+          duration,
+          result,
+          steps,
+        })
+      }
+    }
+  }
+}
+
+/// Removes ANSI codes and the control characters that are not allowed in
+/// XML or YAML documents from a failure message.
+fn clean_message(message: &str) -> String {
+  colors::strip_ansi_codes(message)
+    .chars()
+    .filter(|c| !c.is_control() || matches!(c, '\n' | '\t'))
+    .collect()
+}
+
+fn escape_xml(text: &str) -> String {
+  let mut escaped = String::with_capacity(text.len());
+  for c in text.chars() {
+    match c {
+      '&' => escaped.push_str("&amp;"),
+      '<' => escaped.push_str("&lt;"),
+      '>' => escaped.push_str("&gt;"),
+      '"' => escaped.push_str("&quot;"),
+      '\'' => escaped.push_str("&apos;"),
+      c => escaped.push(c),
+    }
+  }
+  escaped
+}
+
+fn format_seconds(millis: u128) -> String {
+  format!("{:.3}", millis as f64 / 1000.0)
+}
+
+/// Writes a JUnit XML document with one test suite per test module. Test
+/// steps are test cases of their own, named after the test they belong to.
+struct JunitTestReporter {
+  time: Instant,
+  collector: TestCaseCollector,
+  suites: Vec<(String, Vec<TestCaseReport>)>,
+  output: Box<dyn Write + Send>,
+}
+
+impl JunitTestReporter {
+  fn new(output: Box<dyn Write + Send>) -> Self {
+    Self {
+      time: Instant::now(),
+      collector: TestCaseCollector::default(),
+      suites: Vec::new(),
+      output,
+    }
+  }
+
+  fn write_test_case(&mut self, case: &TestCaseReport) -> std::io::Result<()> {
+    write!(
+      self.output,
+      "    <testcase name=\"{}\" classname=\"{}\" time=\"{}\"",
+      escape_xml(&case.name),
+      escape_xml(&case.origin),
+      format_seconds(case.duration as u128)
+    )?;
+    match &case.result {
+      TestResult::Ok => writeln!(self.output, "/>"),
+      TestResult::Ignored => {
+        writeln!(self.output, ">")?;
+        writeln!(self.output, "      <skipped/>")?;
+        writeln!(self.output, "    </testcase>")
+      }
+      TestResult::Failed(error) => {
+        let error = clean_message(error);
+        let message = error.lines().next().unwrap_or_default();
+        writeln!(self.output, ">")?;
+        writeln!(
+          self.output,
+          "      <failure message=\"{}\">{}</failure>",
+          escape_xml(message),
+          escape_xml(&error)
+        )?;
+        writeln!(self.output, "    </testcase>")
+      }
+    }
+  }
+}
+
+fn count_results(cases: &[TestCaseReport]) -> (usize, usize, usize) {
+  let failures = cases
+    .iter()
+    .filter(|case| matches!(case.result, TestResult::Failed(_)))
+    .count();
+  let skipped = cases
+    .iter()
+    .filter(|case| case.result == TestResult::Ignored)
+    .count();
+  (cases.len(), failures, skipped)
+}
+
+impl TestReporter for JunitTestReporter {
+  fn visit_event(&mut self, event: TestEvent) -> std::io::Result<()> {
+    if let TestMessage::Plan { .. } = event.message {
+      if !self
+        .suites
+        .iter()
+        .any(|(origin, _)| *origin == event.origin)
+      {
+        self.suites.push((event.origin, Vec::new()));
+      }
+    } else if let Some(case) = self.collector.visit_event(event) {
+      match self
+        .suites
+        .iter_mut()
+        .find(|(origin, _)| *origin == case.origin)
+      {
+        Some((_, cases)) => cases.push(case),
+        None => self.suites.push((case.origin.clone(), vec![case])),
+      }
+    }
+
+    Ok(())
+  }
+
+  fn done(&mut self) -> std::io::Result<()> {
+    // Steps run within their test, so only tests add up to the time.
+    let suites: Vec<(String, usize, Vec<TestCaseReport>)> =
+      std::mem::take(&mut self.suites)
+        .into_iter()
+        .map(|(origin, cases)| {
+          let time = cases.iter().map(|case| case.duration).sum();
+          let cases = cases
+            .into_iter()
+            .flat_map(TestCaseReport::flatten)
+            .collect();
+          (origin, time, cases)
+        })
+        .collect();
+    let (tests, failures, skipped) = suites.iter().fold(
+      (0, 0, 0),
+      |(tests, failures, skipped), (_, _, cases)| {
+        let counts = count_results(cases);
+        (tests + counts.0, failures + counts.1, skipped + counts.2)
+      },
+    );
+
+    writeln!(self.output, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    writeln!(
+      self.output,
+      "<testsuites name=\"deno test\" tests=\"{}\" failures=\"{}\" skipped=\"{}\" time=\"{}\">",
+      tests,
+      failures,
+      skipped,
+      format_seconds(self.time.elapsed().as_millis())
+    )?;
+
+    for (origin, time, cases) in &suites {
+      let (tests, failures, skipped) = count_results(cases);
+      writeln!(
+        self.output,
+        "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" skipped=\"{}\" time=\"{}\">",
+        escape_xml(origin),
+        tests,
+        failures,
+        skipped,
+        format_seconds(*time as u128)
+      )?;
+      for case in cases {
+        self.write_test_case(case)?;
+      }
+      writeln!(self.output, "  </testsuite>")?;
+    }
+
+    writeln!(self.output, "</testsuites>")?;
+    self.output.flush()
+  }
+}
+
+/// Writes a TAP version 13 stream, with the duration and failure message of
+/// each test in a YAML diagnostic block.
+struct TapTestReporter {
+  started: bool,
+  count: usize,
+  output: Box<dyn Write + Send>,
+}
+
+impl TapTestReporter {
+  fn new(output: Box<dyn Write + Send>) -> Self {
+    Self {
+      started: false,
+      count: 0,
+      output,
+    }
+  }
+
+  fn start(&mut self) -> std::io::Result<()> {
+    if !self.started {
+      self.started = true;
+      writeln!(self.output, "TAP version 13")?;
+    }
+    Ok(())
+  }
+}
+
+impl TestReporter for TapTestReporter {
+  fn visit_event(&mut self, event: TestEvent) -> std::io::Result<()> {
+    self.start()?;
+    match event.message {
+      TestMessage::Plan { .. } => {
+        writeln!(self.output, "# {}", event.origin)?;
+      }
+      // Steps are reported as part of their test, which fails if any of
+      // them failed.
+      TestMessage::Wait { .. }
+      | TestMessage::StepWait { .. }
+      | TestMessage::StepResult { .. } => {}
+      TestMessage::Result {
+        name,
+        duration,
+        result,
+      } => {
+        self.count += 1;
+        let name = name.replace('#', "\\#");
+        match &result {
+          TestResult::Ok => {
+            writeln!(self.output, "ok {} - {}", self.count, name)?
+          }
+          TestResult::Ignored => {
+            writeln!(self.output, "ok {} - {} # SKIP", self.count, name)?
+          }
+          TestResult::Failed(_) => {
+            writeln!(self.output, "not ok {} - {}", self.count, name)?
+          }
+        }
+        writeln!(self.output, "  ---")?;
+        writeln!(self.output, "  duration_ms: {}", duration)?;
+        if let TestResult::Failed(error) = &result {
+          writeln!(self.output, "  message: |-")?;
+          for line in clean_message(error).lines() {
+            writeln!(self.output, "    {}", line)?;
+          }
+        }
+        writeln!(self.output, "  ...")?;
+      }
+    }
+
+    Ok(())
+  }
+
+  fn done(&mut self) -> std::io::Result<()> {
+    self.start()?;
+    writeln!(self.output, "1..{}", self.count)?;
+    self.output.flush()
   }
 }
 
-fn create_reporter(concurrent: bool) -> Box<dyn TestReporter + Send> {
-  Box::new(PrettyTestReporter::new(concurrent))
+/// Writes a single JSON document with every test result and a summary.
+/// Test steps are nested in the test they belong to.
+struct JsonTestReporter {
+  time: Instant,
+  filtered_out: usize,
+  collector: TestCaseCollector,
+  cases: Vec<TestCaseReport>,
+  output: Box<dyn Write + Send>,
+}
+
+impl JsonTestReporter {
+  fn new(output: Box<dyn Write + Send>) -> Self {
+    Self {
+      time: Instant::now(),
+      filtered_out: 0,
+      collector: TestCaseCollector::default(),
+      cases: Vec::new(),
+      output,
+    }
+  }
+}
+
+fn test_case_json(case: &TestCaseReport) -> Value {
+  let (result, error) = match &case.result {
+    TestResult::Ok => ("ok", None),
+    TestResult::Ignored => ("ignored", None),
+    TestResult::Failed(error) => ("failed", Some(clean_message(error))),
+  };
+  let steps: Vec<Value> = case.steps.iter().map(test_case_json).collect();
+  json!({
+    "origin": case.origin,
+    "name": case.name,
+    "duration": case.duration,
+    "result": result,
+    "error": error,
+    "steps": steps,
+  })
+}
+
+impl TestReporter for JsonTestReporter {
+  fn visit_event(&mut self, event: TestEvent) -> std::io::Result<()> {
+    if let TestMessage::Plan { filtered, .. } = event.message {
+      self.filtered_out += filtered;
+    } else if let Some(case) = self.collector.visit_event(event) {
+      self.cases.push(case);
+    }
+
+    Ok(())
+  }
+
+  fn done(&mut self) -> std::io::Result<()> {
+    let (_, failed, ignored) = count_results(&self.cases);
+    let tests: Vec<Value> = self.cases.iter().map(test_case_json).collect();
+    let report = json!({
+      "tests": tests,
+      "summary": {
+        "passed": self.cases.len() - failed - ignored,
+        "failed": failed,
+        "ignored": ignored,
+        "filteredOut": self.filtered_out,
+        "duration": self.time.elapsed().as_millis() as u64,
+      },
+    });
+
+    serde_json::to_writer_pretty(&mut self.output, &report)?;
+    writeln!(self.output)?;
+    self.output.flush()
+  }
+}
+
+fn create_reporter(
+  kind: &TestReporterKind,
+  concurrent: bool,
+  maybe_output_path: Option<&Path>,
+) -> Result<Box<dyn TestReporter + Send>, AnyError> {
+  let output: Box<dyn Write + Send> = match maybe_output_path {
+    Some(path) => Box::new(BufWriter::new(std::fs::File::create(path)?)),
+    None => Box::new(std::io::stdout()),
+  };
+
+  Ok(match kind {
+    TestReporterKind::Pretty => {
+      Box::new(PrettyTestReporter::new(concurrent, output))
+    }
+    TestReporterKind::Junit => Box::new(JunitTestReporter::new(output)),
+    TestReporterKind::Tap => Box::new(TapTestReporter::new(output)),
+    TestReporterKind::Json => Box::new(JsonTestReporter::new(output)),
+  })
 }
 
 pub(crate) fn is_supported(p: &Path) -> bool {
@@ -233,6 +776,7 @@ pub fn is_remote_url(module_url: &str) -> bool {
 
 pub fn collect_test_module_specifiers<P>(
   include: Vec<String>,
+  ignore: &[PathBuf],
   root_path: &Path,
   predicate: P,
 ) -> Result<Vec<Url>, AnyError>
@@ -246,7 +790,7 @@ where
   for path in include_paths {
     let p = normalize_path(&root_path.join(path));
     if p.is_dir() {
-      let test_files = collect_files(&[p], &[], &predicate).unwrap();
+      let test_files = collect_files(&[p], ignore, &predicate).unwrap();
       let test_files_as_urls = test_files
         .iter()
         .map(|f| Url::from_file_path(f).unwrap())
@@ -266,6 +810,67 @@ where
   Ok(prepared)
 }
 
+/// Selects the test modules of the 1-based `shard` and shuffles them with the
+/// `shuffle` seed. The modules are sorted first so that the partitioning and
+/// the order are the same on every machine.
+pub fn order_test_modules(
+  mut test_modules: Vec<ModuleSpecifier>,
+  shard: Option<(usize, usize)>,
+  shuffle: Option<u32>,
+) -> Vec<ModuleSpecifier> {
+  if shard.is_none() && shuffle.is_none() {
+    return test_modules;
+  }
+
+  test_modules.sort();
+  if let Some((index, count)) = shard {
+    test_modules = test_modules
+      .into_iter()
+      .enumerate()
+      .filter(|(i, _)| i % count == index - 1)
+      .map(|(_, specifier)| specifier)
+      .collect();
+  }
+  if let Some(seed) = shuffle {
+    test_modules.shuffle(&mut StdRng::seed_from_u64(u64::from(seed)));
+  }
+
+  test_modules
+}
+
+/// Returns the local files that the test modules depend on, which are to be
+/// watched, along with the test modules that depend on any of the changed
+/// paths and need to be run again.
+pub fn get_affected_test_modules(
+  graph: &module_graph::Graph,
+  test_modules: &[ModuleSpecifier],
+  changed: &[PathBuf],
+) -> Result<(Vec<PathBuf>, Vec<ModuleSpecifier>), AnyError> {
+  let changed: HashSet<ModuleSpecifier> = changed
+    .iter()
+    .filter_map(|path| ModuleSpecifier::from_file_path(path).ok())
+    .collect();
+  let mut paths_to_watch = Vec::new();
+  let mut affected_test_modules = Vec::new();
+
+  for specifier in test_modules {
+    let mut modules = graph.get_dependencies(specifier)?;
+    modules.insert(specifier.clone());
+
+    paths_to_watch.extend(
+      modules
+        .iter()
+        .filter_map(|specifier| specifier.to_file_path().ok()),
+    );
+
+    if modules.iter().any(|module| changed.contains(module)) {
+      affected_test_modules.push(specifier.clone());
+    }
+  }
+
+  Ok((paths_to_watch, affected_test_modules))
+}
+
 pub async fn run_test_file(
   program_state: Arc<ProgramState>,
   main_module: ModuleSpecifier,
@@ -337,7 +942,13 @@ pub async fn run_tests(
   allow_none: bool,
   filter: Option<String>,
   concurrent_jobs: usize,
+  reporter_kind: TestReporterKind,
+  reporter_output: Option<PathBuf>,
+  shard: Option<(usize, usize)>,
+  shuffle: Option<u32>,
 ) -> Result<bool, AnyError> {
+  // Doc modules are only type checked, so their order doesn't matter.
+  let doc_modules = order_test_modules(doc_modules, shard, None);
   if !doc_modules.is_empty() {
     let mut test_programs = Vec::new();
 
@@ -424,6 +1035,8 @@ pub async fn run_tests(
     return Ok(false);
   }
 
+  let test_modules = order_test_modules(test_modules, shard, shuffle);
+
   program_state
     .prepare_module_graph(
       test_modules.clone(),
@@ -443,6 +1056,7 @@ pub async fn run_tests(
   let test_options = json!({
       "disableLog": quiet,
       "filter": filter,
+      "shuffle": shuffle,
   });
 
   let test_module = deno_core::resolve_path("$deno$test.js")?;
@@ -488,9 +1102,13 @@ pub async fn run_tests(
     .buffer_unordered(concurrent_jobs)
     .collect::<Vec<Result<Result<(), AnyError>, tokio::task::JoinError>>>();
 
-  let mut reporter = create_reporter(concurrent_jobs > 1);
+  let mut reporter = create_reporter(
+    &reporter_kind,
+    concurrent_jobs > 1,
+    reporter_output.as_deref(),
+  )?;
   let handler = {
-    tokio::task::spawn_blocking(move || {
+    tokio::task::spawn_blocking(move || -> Result<bool, AnyError> {
       let mut used_only = false;
       let mut has_error = false;
       let mut planned = 0;
@@ -523,7 +1141,7 @@ pub async fn run_tests(
           _ => {}
         }
 
-        reporter.visit_event(event);
+        reporter.visit_event(event)?;
 
         if has_error && fail_fast {
           break;
@@ -534,7 +1152,7 @@ pub async fn run_tests(
         has_error = true;
       }
 
-      reporter.done();
+      reporter.done()?;
 
       if planned > reported {
         has_error = true;
@@ -549,7 +1167,14 @@ pub async fn run_tests(
         has_error = true;
       }
 
-      has_error
+      if let (true, Some(seed)) = (has_error, shuffle) {
+        eprintln!(
+          "Tests were shuffled with seed {}, pass --shuffle={} to run them in the same order.\n",
+          seed, seed
+        );
+      }
+
+      Ok(has_error)
     })
   };
 
@@ -565,7 +1190,7 @@ pub async fn run_tests(
   if let Some(e) = join_errors.next() {
     Err(e)
   } else {
-    Ok(result.unwrap_or(false))
+    result.unwrap_or(Ok(false))
   }
 }
 
@@ -584,6 +1209,7 @@ mod tests {
         "subdir2/mod2.ts".to_string(),
         "http://example.com/printf_test.ts".to_string(),
       ],
+      &[],
       &test_data_path,
       is_supported,
     )
@@ -605,6 +1231,46 @@ mod tests {
     assert_eq!(matched_urls, expected);
   }
 
+  #[test]
+  fn test_order_test_modules() {
+    let modules: Vec<ModuleSpecifier> = (0..10)
+      .map(|i| Url::parse(&format!("file:///{}_test.ts", i)).unwrap())
+      .collect();
+    let mut reversed = modules.clone();
+    reversed.reverse();
+
+    assert_eq!(order_test_modules(reversed.clone(), None, None), reversed);
+
+    let shard_1 = order_test_modules(reversed.clone(), Some((1, 3)), None);
+    let shard_2 = order_test_modules(reversed.clone(), Some((2, 3)), None);
+    let shard_3 = order_test_modules(reversed.clone(), Some((3, 3)), None);
+    assert_eq!(
+      shard_1,
+      vec![
+        modules[0].clone(),
+        modules[3].clone(),
+        modules[6].clone(),
+        modules[9].clone()
+      ]
+    );
+    assert_eq!(shard_2.len(), 3);
+    assert_eq!(shard_3.len(), 3);
+    let mut all: Vec<_> =
+      shard_1.into_iter().chain(shard_2).chain(shard_3).collect();
+    all.sort();
+    assert_eq!(all, modules);
+
+    let shuffled = order_test_modules(reversed, None, Some(42));
+    assert_eq!(
+      order_test_modules(modules.clone(), None, Some(42)),
+      shuffled
+    );
+    assert_ne!(shuffled, modules);
+    let mut sorted = shuffled;
+    sorted.sort();
+    assert_eq!(sorted, modules);
+  }
+
   #[test]
   fn test_is_supported() {
     assert!(is_supported(Path::new("tests/subdir/foo_test.ts")));
@@ -636,6 +1302,7 @@ mod tests {
     println!("root {:?}", root);
     let mut matched_urls = collect_test_module_specifiers(
       vec![".".to_string()],
+      &[],
       &root,
       is_supported,
     )
@@ -2,6 +2,7 @@
 
 use crate::error::type_error;
 use crate::error::AnyError;
+use crate::error::JsStackFrame;
 use crate::gotham_state::GothamState;
 use crate::resources::ResourceTable;
 use crate::runtime::GetErrorClassFn;
@@ -42,6 +43,42 @@ impl<'a, 'b, 'c> OpPayload<'a, 'b, 'c> {
       .map_err(|e| type_error(format!("Error parsing args: {}", e)))?;
     Ok((a, b))
   }
+
+  /// Captures the JavaScript stack the op is being called from, innermost
+  /// frame first. Only the function names and locations are filled in, and
+  /// the depth is that of the stack traces captured for uncaught exceptions.
+  pub fn stack_trace(&mut self) -> Vec<JsStackFrame> {
+    let scope = &mut *self.scope;
+    // The message of a value that isn't an error carries the current stack.
+    let undefined = v8::undefined(scope).into();
+    let message = v8::Exception::create_message(scope, undefined);
+    let stack_trace = match message.get_stack_trace(scope) {
+      Some(stack_trace) => stack_trace,
+      None => return vec![],
+    };
+
+    let mut frames = vec![];
+    for i in 0..stack_trace.get_frame_count() {
+      let frame = match stack_trace.get_frame(scope, i) {
+        Some(frame) => frame,
+        None => continue,
+      };
+      let file_name = frame
+        .get_script_name_or_source_url(scope)
+        .map(|name| name.to_rust_string_lossy(scope));
+      let mut js_frame = JsStackFrame::from_location(
+        file_name,
+        Some(frame.get_line_number() as i64),
+        Some(frame.get_column() as i64),
+      );
+      js_frame.function_name = frame
+        .get_function_name(scope)
+        .map(|name| name.to_rust_string_lossy(scope))
+        .filter(|name| !name.is_empty());
+      frames.push(js_frame);
+    }
+    frames
+  }
 }
 
 pub enum Op {
@@ -140,4 +140,40 @@ mod tests {
     assert!(e.contains("at async f1 (<init>:"));
     assert!(e.contains("at async f2 (<init>:"));
   }
+
+  #[test]
+  fn op_payload_stack_trace() {
+    let mut runtime = crate::JsRuntime::new(Default::default());
+    runtime.register_op("op_stack", |state, mut payload| -> Op {
+      let frames: Vec<(Option<String>, Option<String>)> = payload
+        .stack_trace()
+        .into_iter()
+        .map(|frame| (frame.function_name, frame.file_name))
+        .collect();
+      Op::Sync(serialize_op_result(Ok(frames), state))
+    });
+    runtime.sync_ops_cache();
+    runtime
+      .execute(
+        "<init>",
+        r#"
+    function inner() {
+      return Deno.core.opSync('op_stack');
+    }
+
+    function outer() {
+      return inner();
+    }
+
+    const frames = outer();
+    const i = frames.findIndex(([name]) => name === 'inner');
+    if (
+      i === -1 || frames[i][1] !== '<init>' || frames[i + 1][0] !== 'outer'
+    ) {
+      throw new Error(JSON.stringify(frames));
+    }
+    "#,
+      )
+      .unwrap();
+  }
 }
@@ -12,10 +12,44 @@
     return webidl.converters["DOMString"](V, opts);
   };
 
+  webidl.converters["HashAlgorithmIdentifier"] =
+    webidl.converters["AlgorithmIdentifier"];
+
+  webidl.converters["KeyType"] = webidl.createEnumConverter("KeyType", [
+    "public",
+    "private",
+    "secret",
+  ]);
+
+  webidl.converters["KeyFormat"] = webidl.createEnumConverter("KeyFormat", [
+    "raw",
+    "pkcs8",
+    "spki",
+    "jwk",
+  ]);
+
+  webidl.converters["KeyUsage"] = webidl.createEnumConverter("KeyUsage", [
+    "encrypt",
+    "decrypt",
+    "sign",
+    "verify",
+    "deriveKey",
+    "deriveBits",
+    "wrapKey",
+    "unwrapKey",
+  ]);
+
+  webidl.converters["sequence<KeyUsage>"] = webidl.createSequenceConverter(
+    webidl.converters["KeyUsage"],
+  );
+
+  webidl.converters["BigInteger"] = webidl.converters["Uint8Array"];
+
   const algorithmDictionary = [
     {
       key: "name",
       converter: webidl.converters["DOMString"],
+      required: true,
     },
   ];
 
@@ -23,4 +57,305 @@
     "Algorithm",
     algorithmDictionary,
   );
+
+  const rsaKeyGenParamsDictionary = [
+    ...algorithmDictionary,
+    {
+      key: "modulusLength",
+      converter: (V, opts) =>
+        webidl.converters["unsigned long"](V, { ...opts, enforceRange: true }),
+      required: true,
+    },
+    {
+      key: "publicExponent",
+      converter: webidl.converters["BigInteger"],
+      required: true,
+    },
+  ];
+
+  webidl.converters["RsaKeyGenParams"] = webidl.createDictionaryConverter(
+    "RsaKeyGenParams",
+    rsaKeyGenParamsDictionary,
+  );
+
+  const rsaHashedKeyGenParamsDictionary = [
+    ...rsaKeyGenParamsDictionary,
+    {
+      key: "hash",
+      converter: webidl.converters["HashAlgorithmIdentifier"],
+      required: true,
+    },
+  ];
+
+  webidl.converters["RsaHashedKeyGenParams"] = webidl
+    .createDictionaryConverter(
+      "RsaHashedKeyGenParams",
+      rsaHashedKeyGenParamsDictionary,
+    );
+
+  const rsaHashedImportParamsDictionary = [
+    ...algorithmDictionary,
+    {
+      key: "hash",
+      converter: webidl.converters["HashAlgorithmIdentifier"],
+      required: true,
+    },
+  ];
+
+  webidl.converters["RsaHashedImportParams"] = webidl
+    .createDictionaryConverter(
+      "RsaHashedImportParams",
+      rsaHashedImportParamsDictionary,
+    );
+
+  const rsaPssParamsDictionary = [
+    ...algorithmDictionary,
+    {
+      key: "saltLength",
+      converter: (V, opts) =>
+        webidl.converters["unsigned long"](V, { ...opts, enforceRange: true }),
+      required: true,
+    },
+  ];
+
+  webidl.converters["RsaPssParams"] = webidl.createDictionaryConverter(
+    "RsaPssParams",
+    rsaPssParamsDictionary,
+  );
+
+  const ecKeyGenParamsDictionary = [
+    ...algorithmDictionary,
+    {
+      key: "namedCurve",
+      converter: webidl.converters["DOMString"],
+      required: true,
+    },
+  ];
+
+  webidl.converters["EcKeyGenParams"] = webidl.createDictionaryConverter(
+    "EcKeyGenParams",
+    ecKeyGenParamsDictionary,
+  );
+
+  webidl.converters["EcKeyImportParams"] = webidl.createDictionaryConverter(
+    "EcKeyImportParams",
+    ecKeyGenParamsDictionary,
+  );
+
+  const ecdsaParamsDictionary = [
+    ...algorithmDictionary,
+    {
+      key: "hash",
+      converter: webidl.converters["HashAlgorithmIdentifier"],
+      required: true,
+    },
+  ];
+
+  webidl.converters["EcdsaParams"] = webidl.createDictionaryConverter(
+    "EcdsaParams",
+    ecdsaParamsDictionary,
+  );
+
+  const hmacKeyGenParamsDictionary = [
+    ...algorithmDictionary,
+    {
+      key: "hash",
+      converter: webidl.converters["HashAlgorithmIdentifier"],
+      required: true,
+    },
+    {
+      key: "length",
+      converter: (V, opts) =>
+        webidl.converters["unsigned long"](V, { ...opts, enforceRange: true }),
+    },
+  ];
+
+  webidl.converters["HmacKeyGenParams"] = webidl.createDictionaryConverter(
+    "HmacKeyGenParams",
+    hmacKeyGenParamsDictionary,
+  );
+
+  webidl.converters["HmacImportParams"] = webidl.createDictionaryConverter(
+    "HmacImportParams",
+    hmacKeyGenParamsDictionary,
+  );
+
+  const aesKeyGenParamsDictionary = [
+    ...algorithmDictionary,
+    {
+      key: "length",
+      converter: (V, opts) =>
+        webidl.converters["unsigned short"](V, { ...opts, enforceRange: true }),
+      required: true,
+    },
+  ];
+
+  webidl.converters["AesKeyGenParams"] = webidl.createDictionaryConverter(
+    "AesKeyGenParams",
+    aesKeyGenParamsDictionary,
+  );
+
+  const aesGcmParamsDictionary = [
+    ...algorithmDictionary,
+    {
+      key: "iv",
+      converter: webidl.converters["BufferSource"],
+      required: true,
+    },
+    {
+      key: "additionalData",
+      converter: webidl.converters["BufferSource"],
+    },
+    {
+      key: "tagLength",
+      converter: (V, opts) =>
+        webidl.converters["octet"](V, { ...opts, enforceRange: true }),
+    },
+  ];
+
+  webidl.converters["AesGcmParams"] = webidl.createDictionaryConverter(
+    "AesGcmParams",
+    aesGcmParamsDictionary,
+  );
+
+  const aesCbcParamsDictionary = [
+    ...algorithmDictionary,
+    {
+      key: "iv",
+      converter: webidl.converters["BufferSource"],
+      required: true,
+    },
+  ];
+
+  webidl.converters["AesCbcParams"] = webidl.createDictionaryConverter(
+    "AesCbcParams",
+    aesCbcParamsDictionary,
+  );
+
+  const aesCtrParamsDictionary = [
+    ...algorithmDictionary,
+    {
+      key: "counter",
+      converter: webidl.converters["BufferSource"],
+      required: true,
+    },
+    {
+      key: "length",
+      converter: (V, opts) =>
+        webidl.converters["octet"](V, { ...opts, enforceRange: true }),
+      required: true,
+    },
+  ];
+
+  webidl.converters["AesCtrParams"] = webidl.createDictionaryConverter(
+    "AesCtrParams",
+    aesCtrParamsDictionary,
+  );
+
+  const aesDerivedKeyParamsDictionary = [
+    ...algorithmDictionary,
+    {
+      key: "length",
+      converter: (V, opts) =>
+        webidl.converters["unsigned short"](V, { ...opts, enforceRange: true }),
+      required: true,
+    },
+  ];
+
+  webidl.converters["AesDerivedKeyParams"] = webidl.createDictionaryConverter(
+    "AesDerivedKeyParams",
+    aesDerivedKeyParamsDictionary,
+  );
+
+  const ecdhKeyDeriveParamsDictionary = [
+    ...algorithmDictionary,
+    {
+      key: "public",
+      // CryptoKey is defined in 01_crypto.js, after this file has run.
+      converter: (V, opts) => webidl.converters["CryptoKey"](V, opts),
+      required: true,
+    },
+  ];
+
+  webidl.converters["EcdhKeyDeriveParams"] = webidl.createDictionaryConverter(
+    "EcdhKeyDeriveParams",
+    ecdhKeyDeriveParamsDictionary,
+  );
+
+  const hkdfParamsDictionary = [
+    ...algorithmDictionary,
+    {
+      key: "hash",
+      converter: webidl.converters["HashAlgorithmIdentifier"],
+      required: true,
+    },
+    {
+      key: "salt",
+      converter: webidl.converters["BufferSource"],
+      required: true,
+    },
+    {
+      key: "info",
+      converter: webidl.converters["BufferSource"],
+      required: true,
+    },
+  ];
+
+  webidl.converters["HkdfParams"] = webidl.createDictionaryConverter(
+    "HkdfParams",
+    hkdfParamsDictionary,
+  );
+
+  const pbkdf2ParamsDictionary = [
+    ...algorithmDictionary,
+    {
+      key: "hash",
+      converter: webidl.converters["HashAlgorithmIdentifier"],
+      required: true,
+    },
+    {
+      key: "salt",
+      converter: webidl.converters["BufferSource"],
+      required: true,
+    },
+    {
+      key: "iterations",
+      converter: (V, opts) =>
+        webidl.converters["unsigned long"](V, { ...opts, enforceRange: true }),
+      required: true,
+    },
+  ];
+
+  webidl.converters["Pbkdf2Params"] = webidl.createDictionaryConverter(
+    "Pbkdf2Params",
+    pbkdf2ParamsDictionary,
+  );
+
+  const jsonWebKeyDictionary = [
+    { key: "kty", converter: webidl.converters["DOMString"] },
+    { key: "use", converter: webidl.converters["DOMString"] },
+    {
+      key: "key_ops",
+      converter: webidl.createSequenceConverter(webidl.converters["DOMString"]),
+    },
+    { key: "alg", converter: webidl.converters["DOMString"] },
+    { key: "ext", converter: webidl.converters["boolean"] },
+    { key: "crv", converter: webidl.converters["DOMString"] },
+    { key: "x", converter: webidl.converters["DOMString"] },
+    { key: "y", converter: webidl.converters["DOMString"] },
+    { key: "d", converter: webidl.converters["DOMString"] },
+    { key: "n", converter: webidl.converters["DOMString"] },
+    { key: "e", converter: webidl.converters["DOMString"] },
+    { key: "p", converter: webidl.converters["DOMString"] },
+    { key: "q", converter: webidl.converters["DOMString"] },
+    { key: "dp", converter: webidl.converters["DOMString"] },
+    { key: "dq", converter: webidl.converters["DOMString"] },
+    { key: "qi", converter: webidl.converters["DOMString"] },
+    { key: "k", converter: webidl.converters["DOMString"] },
+  ];
+
+  webidl.converters["JsonWebKey"] = webidl.createDictionaryConverter(
+    "JsonWebKey",
+    jsonWebKeyDictionary,
+  );
 })(this);
@@ -5,15 +5,122 @@
   const core = window.Deno.core;
   const webidl = window.__bootstrap.webidl;
 
+  // Maps each operation to the algorithms supporting it, and each algorithm
+  // to the IDL dictionary its parameters are converted with (or `null` when
+  // only `name` is relevant).
+  // See https://www.w3.org/TR/WebCryptoAPI/#algorithm-normalization-internal
   const supportedAlgorithms = {
     "digest": {
-      "SHA-1": {},
-      "SHA-256": {},
-      "SHA-384": {},
-      "SHA-512": {},
+      "SHA-1": null,
+      "SHA-256": null,
+      "SHA-384": null,
+      "SHA-512": null,
     },
+    "generateKey": {
+      "RSASSA-PKCS1-v1_5": "RsaHashedKeyGenParams",
+      "RSA-PSS": "RsaHashedKeyGenParams",
+      "ECDSA": "EcKeyGenParams",
+      "ECDH": "EcKeyGenParams",
+      "HMAC": "HmacKeyGenParams",
+      "AES-GCM": "AesKeyGenParams",
+      "AES-CBC": "AesKeyGenParams",
+      "AES-CTR": "AesKeyGenParams",
+    },
+    "importKey": {
+      "RSASSA-PKCS1-v1_5": "RsaHashedImportParams",
+      "RSA-PSS": "RsaHashedImportParams",
+      "ECDSA": "EcKeyImportParams",
+      "ECDH": "EcKeyImportParams",
+      "HMAC": "HmacImportParams",
+      "AES-GCM": null,
+      "AES-CBC": null,
+      "AES-CTR": null,
+      "PBKDF2": null,
+      "HKDF": null,
+    },
+    "sign": {
+      "RSASSA-PKCS1-v1_5": null,
+      "RSA-PSS": "RsaPssParams",
+      "ECDSA": "EcdsaParams",
+      "HMAC": null,
+    },
+    "verify": {
+      "RSASSA-PKCS1-v1_5": null,
+      "RSA-PSS": "RsaPssParams",
+      "ECDSA": "EcdsaParams",
+      "HMAC": null,
+    },
+    "encrypt": {
+      "AES-GCM": "AesGcmParams",
+      "AES-CBC": "AesCbcParams",
+      "AES-CTR": "AesCtrParams",
+    },
+    "decrypt": {
+      "AES-GCM": "AesGcmParams",
+      "AES-CBC": "AesCbcParams",
+      "AES-CTR": "AesCtrParams",
+    },
+    "deriveBits": {
+      "ECDH": "EcdhKeyDeriveParams",
+      "HKDF": "HkdfParams",
+      "PBKDF2": "Pbkdf2Params",
+    },
+    "get key length": {
+      "AES-GCM": "AesDerivedKeyParams",
+      "AES-CBC": "AesDerivedKeyParams",
+      "AES-CTR": "AesDerivedKeyParams",
+      "HMAC": "HmacImportParams",
+    },
+  };
+
+  // Dictionary members holding a BufferSource, which must be copied during
+  // normalization.
+  const bufferSourceMembers = [
+    "additionalData",
+    "counter",
+    "info",
+    "iv",
+    "publicExponent",
+    "salt",
+  ];
+
+  const supportedNamedCurves = ["P-256", "P-384"];
+
+  // Which usages are valid for each algorithm and key type.
+  const recognisedUsages = {
+    "RSASSA-PKCS1-v1_5": { private: ["sign"], public: ["verify"] },
+    "RSA-PSS": { private: ["sign"], public: ["verify"] },
+    "ECDSA": { private: ["sign"], public: ["verify"] },
+    "ECDH": { private: ["deriveKey", "deriveBits"], public: [] },
+    "HMAC": { secret: ["sign", "verify"] },
+    "AES-GCM": { secret: ["encrypt", "decrypt"] },
+    "AES-CBC": { secret: ["encrypt", "decrypt"] },
+    "AES-CTR": { secret: ["encrypt", "decrypt"] },
+    "PBKDF2": { secret: ["deriveKey", "deriveBits"] },
+    "HKDF": { secret: ["deriveKey", "deriveBits"] },
+  };
+
+  const supportedKeyFormats = {
+    "RSASSA-PKCS1-v1_5": ["pkcs8", "spki", "jwk"],
+    "RSA-PSS": ["pkcs8", "spki", "jwk"],
+    "ECDSA": ["raw", "pkcs8", "spki", "jwk"],
+    "ECDH": ["raw", "pkcs8", "spki", "jwk"],
+    "HMAC": ["raw", "jwk"],
+    "AES-GCM": ["raw", "jwk"],
+    "AES-CBC": ["raw", "jwk"],
+    "AES-CTR": ["raw", "jwk"],
+    "PBKDF2": ["raw"],
+    "HKDF": ["raw"],
   };
 
+  function copyBuffer(input) {
+    if (ArrayBuffer.isView(input)) {
+      return new Uint8Array(input.buffer, input.byteOffset, input.byteLength)
+        .slice();
+    }
+    return new Uint8Array(input).slice();
+  }
+
   function normalizeAlgorithm(algorithm, op) {
     if (typeof algorithm == "string") {
       return normalizeAlgorithm({ name: algorithm }, op);
@@ -34,13 +141,26 @@
       );
     }
 
-    // TODO(caspervonb) Step 6 (create from webidl definition), when the need arises.
-    // See https://www.w3.org/TR/WebCryptoAPI/#dfn-normalize-an-algorithm
-    const normalizedAlgorithm = {};
+    const desiredType = registeredAlgorithms[algorithmName];
+    if (desiredType === null) {
+      return { name: algorithmName };
+    }
+
+    const normalizedAlgorithm = webidl.converters[desiredType](algorithm, {
+      prefix: "Failed to normalize algorithm",
+      context: "passed algorithm",
+    });
     normalizedAlgorithm.name = algorithmName;
 
-    // TODO(caspervonb) Step 9 and 10, when the need arises.
-    // See https://www.w3.org/TR/WebCryptoAPI/#dfn-normalize-an-algorithm
+    for (const member of Object.keys(normalizedAlgorithm)) {
+      const idlValue = normalizedAlgorithm[member];
+      if (member == "hash") {
+        normalizedAlgorithm.hash = normalizeAlgorithm(idlValue, "digest");
+      } else if (bufferSourceMembers.includes(member)) {
+        normalizedAlgorithm[member] = copyBuffer(idlValue);
+      }
+    }
+
     return normalizedAlgorithm;
   }
 
@@ -58,6 +178,179 @@
     }
   }
 
+  const _type = Symbol("[[type]]");
+  const _extractable = Symbol("[[extractable]]");
+  const _algorithm = Symbol("[[algorithm]]");
+  const _usages = Symbol("[[usages]]");
+  const _rid = Symbol("[[rid]]");
+
+  // Key resources have no close method of their own, they are released once
+  // the `CryptoKey` holding them is garbage collected.
+  const keyRegistry = new FinalizationRegistry((rid) => core.close(rid));
+
+  class CryptoKey {
+    constructor() {
+      webidl.illegalConstructor();
+    }
+
+    get type() {
+      webidl.assertBranded(this, CryptoKey);
+      return this[_type];
+    }
+
+    get extractable() {
+      webidl.assertBranded(this, CryptoKey);
+      return this[_extractable];
+    }
+
+    get usages() {
+      webidl.assertBranded(this, CryptoKey);
+      return this[_usages];
+    }
+
+    get algorithm() {
+      webidl.assertBranded(this, CryptoKey);
+      return this[_algorithm];
+    }
+
+    get [Symbol.toStringTag]() {
+      return "CryptoKey";
+    }
+
+    [Symbol.for("Deno.customInspect")](inspect) {
+      return `${this.constructor.name} ${
+        inspect({
+          type: this.type,
+          extractable: this.extractable,
+          algorithm: this.algorithm,
+          usages: this.usages,
+        })
+      }`;
+    }
+  }
+
+  webidl.configurePrototype(CryptoKey);
+
+  webidl.converters["CryptoKey"] = webidl.createInterfaceConverter(
+    "CryptoKey",
+    CryptoKey,
+  );
+
+  /**
+   * @param {{ rid: number, type: string, length?: number, modulusLength?: number, publicExponent?: Uint8Array }} info
+   * @param {object} normalizedAlgorithm
+   * @param {boolean} extractable
+   * @param {string[]} usages
+   * @returns {CryptoKey}
+   */
+  function constructKey(info, normalizedAlgorithm, extractable, usages) {
+    const name = normalizedAlgorithm.name;
+    let algorithm;
+    switch (name) {
+      case "RSASSA-PKCS1-v1_5":
+      case "RSA-PSS":
+        algorithm = {
+          name,
+          modulusLength: info.modulusLength,
+          publicExponent: info.publicExponent,
+          hash: { name: normalizedAlgorithm.hash.name },
+        };
+        break;
+      case "ECDSA":
+      case "ECDH":
+        algorithm = { name, namedCurve: normalizedAlgorithm.namedCurve };
+        break;
+      case "HMAC":
+        algorithm = {
+          name,
+          length: info.length,
+          hash: { name: normalizedAlgorithm.hash.name },
+        };
+        break;
+      case "PBKDF2":
+      case "HKDF":
+        algorithm = { name };
+        break;
+      default:
+        algorithm = { name, length: info.length };
+    }
+
+    const key = webidl.createBranded(CryptoKey);
+    key[_type] = info.type;
+    key[_extractable] = extractable;
+    key[_algorithm] = algorithm;
+    key[_usages] = usages;
+    key[_rid] = info.rid;
+    keyRegistry.register(key, info.rid);
+    return key;
+  }
+
+  function keyUsagesFor(algorithmName, type, usages) {
+    const allowed = recognisedUsages[algorithmName][type] ?? [];
+    return usages.filter((usage) => allowed.includes(usage));
+  }
+
+  function assertValidUsages(algorithmName, usages) {
+    const allowed = Object.values(recognisedUsages[algorithmName]).flat();
+    if (usages.some((usage) => !allowed.includes(usage))) {
+      throw new DOMException(
+        `Unsupported key usage for ${algorithmName} key`,
+        "SyntaxError",
+      );
+    }
+  }
+
+  function assertNamedCurve(normalizedAlgorithm) {
+    if (
+      (normalizedAlgorithm.name == "ECDSA" ||
+        normalizedAlgorithm.name == "ECDH") &&
+      !supportedNamedCurves.includes(normalizedAlgorithm.namedCurve)
+    ) {
+      throw new DOMException(
+        "Unsupported named curve",
+        "NotSupportedError",
+      );
+    }
+  }
+
+  function assertKeyUsable(key, normalizedAlgorithm, usage) {
+    if (normalizedAlgorithm.name !== key[_algorithm].name) {
+      throw new DOMException(
+        "The key algorithm does not match the requested algorithm",
+        "InvalidAccessError",
+      );
+    }
+    if (!key[_usages].includes(usage)) {
+      throw new DOMException(
+        `The key does not support the '${usage}' operation`,
+        "InvalidAccessError",
+      );
+    }
+  }
+
+  /**
+   * Returns the JWK "alg" value for a key, if the JWA defines one.
+   * @param {object} algorithm
+   * @returns {string | undefined}
+   */
+  function jwkAlg(algorithm) {
+    const hashBits = () => algorithm.hash.name.slice("SHA-".length);
+    switch (algorithm.name) {
+      case "HMAC":
+        return `HS${hashBits()}`;
+      case "RSASSA-PKCS1-v1_5":
+        return `RS${hashBits()}`;
+      case "RSA-PSS":
+        return `PS${hashBits()}`;
+      case "AES-GCM":
+        return `A${algorithm.length}GCM`;
+      case "AES-CBC":
+        return `A${algorithm.length}CBC`;
+      case "AES-CTR":
+        return `A${algorithm.length}CTR`;
+    }
+  }
+
   class SubtleCrypto {
     constructor() {
       webidl.illegalConstructor();
@@ -79,13 +372,7 @@
         context: "Argument 2",
       });
 
-      if (ArrayBuffer.isView(data)) {
-        data = new Uint8Array(data.buffer, data.byteOffset, data.byteLength);
-      } else {
-        data = new Uint8Array(data);
-      }
-
-      data = data.slice();
+      data = copyBuffer(data);
 
       algorithm = normalizeAlgorithm(algorithm, "digest");
 
@@ -97,6 +384,522 @@
 
       return result.buffer;
     }
+
+    async sign(algorithm, key, data) {
+      const prefix = "Failed to execute 'sign' on 'SubtleCrypto'";
+
+      webidl.assertBranded(this, SubtleCrypto);
+      webidl.requiredArguments(arguments.length, 3, { prefix });
+
+      algorithm = webidl.converters.AlgorithmIdentifier(algorithm, {
+        prefix,
+        context: "Argument 1",
+      });
+      key = webidl.converters.CryptoKey(key, {
+        prefix,
+        context: "Argument 2",
+      });
+      data = webidl.converters.BufferSource(data, {
+        prefix,
+        context: "Argument 3",
+      });
+
+      data = copyBuffer(data);
+
+      const normalizedAlgorithm = normalizeAlgorithm(algorithm, "sign");
+      assertKeyUsable(key, normalizedAlgorithm, "sign");
+
+      const signature = await core.opAsync("op_crypto_sign_key", {
+        rid: key[_rid],
+        algorithm: normalizedAlgorithm.name,
+        hash: normalizedAlgorithm.hash?.name,
+        saltLength: normalizedAlgorithm.saltLength,
+      }, data);
+
+      return signature.buffer;
+    }
+
+    async verify(algorithm, key, signature, data) {
+      const prefix = "Failed to execute 'verify' on 'SubtleCrypto'";
+
+      webidl.assertBranded(this, SubtleCrypto);
+      webidl.requiredArguments(arguments.length, 4, { prefix });
+
+      algorithm = webidl.converters.AlgorithmIdentifier(algorithm, {
+        prefix,
+        context: "Argument 1",
+      });
+      key = webidl.converters.CryptoKey(key, {
+        prefix,
+        context: "Argument 2",
+      });
+      signature = webidl.converters.BufferSource(signature, {
+        prefix,
+        context: "Argument 3",
+      });
+      data = webidl.converters.BufferSource(data, {
+        prefix,
+        context: "Argument 4",
+      });
+
+      signature = copyBuffer(signature);
+      data = copyBuffer(data);
+
+      const normalizedAlgorithm = normalizeAlgorithm(algorithm, "verify");
+      assertKeyUsable(key, normalizedAlgorithm, "verify");
+
+      return await core.opAsync("op_crypto_verify_key", {
+        rid: key[_rid],
+        algorithm: normalizedAlgorithm.name,
+        hash: normalizedAlgorithm.hash?.name,
+        saltLength: normalizedAlgorithm.saltLength,
+        signature,
+      }, data);
+    }
+
+    async encrypt(algorithm, key, data) {
+      const prefix = "Failed to execute 'encrypt' on 'SubtleCrypto'";
+
+      webidl.assertBranded(this, SubtleCrypto);
+      webidl.requiredArguments(arguments.length, 3, { prefix });
+
+      return await encryptOrDecrypt("encrypt", prefix, algorithm, key, data);
+    }
+
+    async decrypt(algorithm, key, data) {
+      const prefix = "Failed to execute 'decrypt' on 'SubtleCrypto'";
+
+      webidl.assertBranded(this, SubtleCrypto);
+      webidl.requiredArguments(arguments.length, 3, { prefix });
+
+      return await encryptOrDecrypt("decrypt", prefix, algorithm, key, data);
+    }
+
+    async generateKey(algorithm, extractable, keyUsages) {
+      const prefix = "Failed to execute 'generateKey' on 'SubtleCrypto'";
+
+      webidl.assertBranded(this, SubtleCrypto);
+      webidl.requiredArguments(arguments.length, 3, { prefix });
+
+      algorithm = webidl.converters.AlgorithmIdentifier(algorithm, {
+        prefix,
+        context: "Argument 1",
+      });
+      extractable = webidl.converters["boolean"](extractable, {
+        prefix,
+        context: "Argument 2",
+      });
+      keyUsages = webidl.converters["sequence<KeyUsage>"](keyUsages, {
+        prefix,
+        context: "Argument 3",
+      });
+
+      const normalizedAlgorithm = normalizeAlgorithm(algorithm, "generateKey");
+      assertNamedCurve(normalizedAlgorithm);
+      assertValidUsages(normalizedAlgorithm.name, keyUsages);
+
+      const keys = await core.opAsync("op_crypto_generate_key", {
+        algorithm: normalizedAlgorithm.name,
+        hash: normalizedAlgorithm.hash?.name,
+        length: normalizedAlgorithm.length,
+        namedCurve: normalizedAlgorithm.namedCurve,
+        modulusLength: normalizedAlgorithm.modulusLength,
+        publicExponent: normalizedAlgorithm.publicExponent,
+      });
+
+      if (keys.length == 1) {
+        if (keyUsages.length == 0) {
+          core.close(keys[0].rid);
+          throw new DOMException("Key usages can not be empty", "SyntaxError");
+        }
+        return constructKey(keys[0], normalizedAlgorithm, extractable, [
+          ...keyUsages,
+        ]);
+      }
+
+      const [privateInfo, publicInfo] = keys;
+      const privateUsages = keyUsagesFor(
+        normalizedAlgorithm.name,
+        "private",
+        keyUsages,
+      );
+      if (privateUsages.length == 0) {
+        core.close(privateInfo.rid);
+        core.close(publicInfo.rid);
+        throw new DOMException(
+          "Private key usages can not be empty",
+          "SyntaxError",
+        );
+      }
+
+      return {
+        privateKey: constructKey(
+          privateInfo,
+          normalizedAlgorithm,
+          extractable,
+          privateUsages,
+        ),
+        publicKey: constructKey(
+          publicInfo,
+          normalizedAlgorithm,
+          true,
+          keyUsagesFor(normalizedAlgorithm.name, "public", keyUsages),
+        ),
+      };
+    }
+
+    async importKey(format, keyData, algorithm, extractable, keyUsages) {
+      const prefix = "Failed to execute 'importKey' on 'SubtleCrypto'";
+
+      webidl.assertBranded(this, SubtleCrypto);
+      webidl.requiredArguments(arguments.length, 5, { prefix });
+
+      format = webidl.converters.KeyFormat(format, {
+        prefix,
+        context: "Argument 1",
+      });
+      if (format == "jwk") {
+        keyData = webidl.converters.JsonWebKey(keyData, {
+          prefix,
+          context: "Argument 2",
+        });
+      } else {
+        keyData = copyBuffer(webidl.converters.BufferSource(keyData, {
+          prefix,
+          context: "Argument 2",
+        }));
+      }
+      algorithm = webidl.converters.AlgorithmIdentifier(algorithm, {
+        prefix,
+        context: "Argument 3",
+      });
+      extractable = webidl.converters["boolean"](extractable, {
+        prefix,
+        context: "Argument 4",
+      });
+      keyUsages = webidl.converters["sequence<KeyUsage>"](keyUsages, {
+        prefix,
+        context: "Argument 5",
+      });
+
+      const normalizedAlgorithm = normalizeAlgorithm(algorithm, "importKey");
+      assertNamedCurve(normalizedAlgorithm);
+      assertValidUsages(normalizedAlgorithm.name, keyUsages);
+
+      if (!supportedKeyFormats[normalizedAlgorithm.name].includes(format)) {
+        throw new DOMException(
+          `${normalizedAlgorithm.name} keys can not be imported in '${format}' format`,
+          "NotSupportedError",
+        );
+      }
+      if (
+        (normalizedAlgorithm.name == "PBKDF2" ||
+          normalizedAlgorithm.name == "HKDF") && extractable
+      ) {
+        throw new DOMException(
+          `${normalizedAlgorithm.name} keys can not be extractable`,
+          "SyntaxError",
+        );
+      }
+
+      let jwk;
+      if (format == "jwk") {
+        if (keyData.ext === false && extractable) {
+          throw new DOMException(
+            "Non-extractable JWK can not be imported as extractable",
+            "DataError",
+          );
+        }
+        if (
+          keyData.key_ops !== undefined &&
+          keyUsages.some((usage) => !keyData.key_ops.includes(usage))
+        ) {
+          throw new DOMException(
+            "Key usages are not allowed by the JWK 'key_ops' member",
+            "DataError",
+          );
+        }
+        const expectedAlg = jwkAlg({
+          ...normalizedAlgorithm,
+          length: keyData.k !== undefined
+            ? Math.floor(keyData.k.length * 6 / 8) * 8
+            : undefined,
+        });
+        if (
+          keyData.alg !== undefined && expectedAlg !== undefined &&
+          keyData.alg !== expectedAlg
+        ) {
+          throw new DOMException(
+            `JWK 'alg' member must be '${expectedAlg}'`,
+            "DataError",
+          );
+        }
+        jwk = keyData;
+        keyData = undefined;
+      }
+
+      const info = core.opSync("op_crypto_import_key", {
+        algorithm: normalizedAlgorithm.name,
+        format,
+        hash: normalizedAlgorithm.hash?.name,
+        namedCurve: normalizedAlgorithm.namedCurve,
+        jwk,
+      }, keyData);
+
+      if (info.type != "public" && keyUsages.length == 0) {
+        core.close(info.rid);
+        throw new DOMException("Key usages can not be empty", "SyntaxError");
+      }
+      const usages = keyUsagesFor(
+        normalizedAlgorithm.name,
+        info.type,
+        keyUsages,
+      );
+      if (usages.length != keyUsages.length) {
+        core.close(info.rid);
+        throw new DOMException(
+          `Unsupported key usage for ${info.type} key`,
+          "SyntaxError",
+        );
+      }
+
+      return constructKey(info, normalizedAlgorithm, extractable, usages);
+    }
+
+    async exportKey(format, key) {
+      const prefix = "Failed to execute 'exportKey' on 'SubtleCrypto'";
+
+      webidl.assertBranded(this, SubtleCrypto);
+      webidl.requiredArguments(arguments.length, 2, { prefix });
+
+      format = webidl.converters.KeyFormat(format, {
+        prefix,
+        context: "Argument 1",
+      });
+      key = webidl.converters.CryptoKey(key, {
+        prefix,
+        context: "Argument 2",
+      });
+
+      if (!key[_extractable]) {
+        throw new DOMException("Key is not extractable", "InvalidAccessError");
+      }
+      if (!supportedKeyFormats[key[_algorithm].name].includes(format)) {
+        throw new DOMException(
+          `${key[_algorithm].name} keys can not be exported in '${format}' format`,
+          "NotSupportedError",
+        );
+      }
+
+      const { data, jwk } = core.opSync("op_crypto_export_key", {
+        rid: key[_rid],
+        format,
+      });
+
+      if (format != "jwk") {
+        return data.buffer;
+      }
+
+      const alg = jwkAlg(key[_algorithm]);
+      if (alg !== undefined) {
+        jwk.alg = alg;
+      }
+      jwk.key_ops = [...key[_usages]];
+      jwk.ext = key[_extractable];
+      return jwk;
+    }
+
+    async deriveBits(algorithm, baseKey, length) {
+      const prefix = "Failed to execute 'deriveBits' on 'SubtleCrypto'";
+
+      webidl.assertBranded(this, SubtleCrypto);
+      webidl.requiredArguments(arguments.length, 3, { prefix });
+
+      algorithm = webidl.converters.AlgorithmIdentifier(algorithm, {
+        prefix,
+        context: "Argument 1",
+      });
+      baseKey = webidl.converters.CryptoKey(baseKey, {
+        prefix,
+        context: "Argument 2",
+      });
+      if (length !== null) {
+        length = webidl.converters["unsigned long"](length, {
+          prefix,
+          context: "Argument 3",
+        });
+      }
+
+      const normalizedAlgorithm = normalizeAlgorithm(algorithm, "deriveBits");
+      return await deriveBits(
+        normalizedAlgorithm,
+        baseKey,
+        length,
+        "deriveBits",
+      );
+    }
+
+    async deriveKey(
+      algorithm,
+      baseKey,
+      derivedKeyType,
+      extractable,
+      keyUsages,
+    ) {
+      const prefix = "Failed to execute 'deriveKey' on 'SubtleCrypto'";
+
+      webidl.assertBranded(this, SubtleCrypto);
+      webidl.requiredArguments(arguments.length, 5, { prefix });
+
+      algorithm = webidl.converters.AlgorithmIdentifier(algorithm, {
+        prefix,
+        context: "Argument 1",
+      });
+      baseKey = webidl.converters.CryptoKey(baseKey, {
+        prefix,
+        context: "Argument 2",
+      });
+      derivedKeyType = webidl.converters.AlgorithmIdentifier(derivedKeyType, {
+        prefix,
+        context: "Argument 3",
+      });
+      extractable = webidl.converters["boolean"](extractable, {
+        prefix,
+        context: "Argument 4",
+      });
+      keyUsages = webidl.converters["sequence<KeyUsage>"](keyUsages, {
+        prefix,
+        context: "Argument 5",
+      });
+
+      const normalizedAlgorithm = normalizeAlgorithm(algorithm, "deriveBits");
+      // Validates derivedKeyType before any bits are derived.
+      normalizeAlgorithm(derivedKeyType, "importKey");
+      const length = getKeyLength(
+        normalizeAlgorithm(derivedKeyType, "get key length"),
+      );
+
+      const secret = await deriveBits(
+        normalizedAlgorithm,
+        baseKey,
+        length,
+        "deriveKey",
+      );
+
+      return await this.importKey(
+        "raw",
+        secret,
+        derivedKeyType,
+        extractable,
+        keyUsages,
+      );
+    }
+  }
+
+  /**
+   * Returns the length in bits of a key derived for `normalizedAlgorithm`, as
+   * normalized for the "get key length" operation.
+   * @param {object} normalizedAlgorithm
+   * @returns {number}
+   */
+  function getKeyLength(normalizedAlgorithm) {
+    if (normalizedAlgorithm.name == "HMAC") {
+      if (normalizedAlgorithm.length !== undefined) {
+        if (normalizedAlgorithm.length == 0) {
+          throw new TypeError("HMAC key length must not be zero");
+        }
+        return normalizedAlgorithm.length;
+      }
+      switch (normalizedAlgorithm.hash.name) {
+        case "SHA-1":
+        case "SHA-256":
+          return 512;
+        default:
+          return 1024;
+      }
+    }
+    if (![128, 192, 256].includes(normalizedAlgorithm.length)) {
+      throw new DOMException(
+        "AES key length must be 128, 192 or 256 bits",
+        "OperationError",
+      );
+    }
+    return normalizedAlgorithm.length;
+  }
+
+  async function deriveBits(normalizedAlgorithm, baseKey, length, usage) {
+    assertKeyUsable(baseKey, normalizedAlgorithm, usage);
+
+    let publicKey;
+    if (normalizedAlgorithm.name == "ECDH") {
+      if (baseKey[_algorithm].namedCurve != "P-256") {
+        throw new DOMException(
+          `ECDH is not supported with ${baseKey[_algorithm].namedCurve}`,
+          "NotSupportedError",
+        );
+      }
+      publicKey = normalizedAlgorithm.public;
+      if (publicKey[_type] != "public") {
+        throw new DOMException(
+          "The public key must be of type 'public'",
+          "InvalidAccessError",
+        );
+      }
+      if (
+        publicKey[_algorithm].name != "ECDH" ||
+        publicKey[_algorithm].namedCurve != baseKey[_algorithm].namedCurve
+      ) {
+        throw new DOMException(
+          "The public key must be an ECDH key on the same curve",
+          "InvalidAccessError",
+        );
+      }
+    }
+
+    const bits = await core.opAsync("op_crypto_derive_bits", {
+      rid: baseKey[_rid],
+      algorithm: normalizedAlgorithm.name,
+      hash: normalizedAlgorithm.hash?.name,
+      salt: normalizedAlgorithm.salt,
+      info: normalizedAlgorithm.info,
+      iterations: normalizedAlgorithm.iterations,
+      publicKey: publicKey?.[_rid],
+      length: length ?? undefined,
+    });
+
+    return bits.buffer;
+  }
+
+  async function encryptOrDecrypt(op, prefix, algorithm, key, data) {
+    algorithm = webidl.converters.AlgorithmIdentifier(algorithm, {
+      prefix,
+      context: "Argument 1",
+    });
+    key = webidl.converters.CryptoKey(key, {
+      prefix,
+      context: "Argument 2",
+    });
+    data = webidl.converters.BufferSource(data, {
+      prefix,
+      context: "Argument 3",
+    });
+
+    data = copyBuffer(data);
+
+    const normalizedAlgorithm = normalizeAlgorithm(algorithm, op);
+    assertKeyUsable(key, normalizedAlgorithm, op);
+
+    const result = await core.opAsync(`op_crypto_${op}_key`, {
+      rid: key[_rid],
+      algorithm: normalizedAlgorithm.name,
+      iv: normalizedAlgorithm.iv,
+      additionalData: normalizedAlgorithm.additionalData,
+      tagLength: normalizedAlgorithm.tagLength,
+      counter: normalizedAlgorithm.counter,
+      length: normalizedAlgorithm.length,
+    }, data);
+
+    return result.buffer;
   }
 
   const subtle = webidl.createBranded(SubtleCrypto);
@@ -164,5 +967,6 @@
     SubtleCrypto,
     crypto: webidl.createBranded(Crypto),
     Crypto,
+    CryptoKey,
   };
 })(this);
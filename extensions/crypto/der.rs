@@ -0,0 +1,152 @@
+// Copyright 2018-2021 the Deno authors. All rights reserved. MIT license.
+
+//! A minimal DER reader and writer, covering just enough ASN.1 to handle the
+//! PKCS#1, PKCS#8, SPKI and SEC1 structures used by the WebCrypto key formats.
+
+use crate::data_error;
+use deno_core::error::AnyError;
+
+pub const TAG_INTEGER: u8 = 0x02;
+pub const TAG_BIT_STRING: u8 = 0x03;
+pub const TAG_OCTET_STRING: u8 = 0x04;
+pub const TAG_NULL: u8 = 0x05;
+pub const TAG_OID: u8 = 0x06;
+pub const TAG_SEQUENCE: u8 = 0x30;
+pub const TAG_CONTEXT_0: u8 = 0xa0;
+pub const TAG_CONTEXT_1: u8 = 0xa1;
+
+// Encoded contents of the object identifiers we understand.
+/// 1.2.840.113549.1.1.1
+pub const OID_RSA_ENCRYPTION: &[u8] =
+  &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x01];
+/// 1.2.840.10045.2.1
+pub const OID_EC_PUBLIC_KEY: &[u8] =
+  &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01];
+/// 1.2.840.10045.3.1.7
+pub const OID_SECP256R1: &[u8] =
+  &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+/// 1.3.132.0.34
+pub const OID_SECP384R1: &[u8] = &[0x2b, 0x81, 0x04, 0x00, 0x22];
+
+pub fn encode(tag: u8, contents: &[u8]) -> Vec<u8> {
+  let len = contents.len();
+  let mut out = Vec::with_capacity(len + 6);
+  out.push(tag);
+  if len < 0x80 {
+    out.push(len as u8);
+  } else {
+    let len_bytes = (len as u32).to_be_bytes();
+    let skip = len_bytes.iter().take_while(|b| **b == 0).count();
+    out.push(0x80 | (4 - skip) as u8);
+    out.extend_from_slice(&len_bytes[skip..]);
+  }
+  out.extend_from_slice(contents);
+  out
+}
+
+pub fn sequence(items: &[&[u8]]) -> Vec<u8> {
+  encode(TAG_SEQUENCE, &items.concat())
+}
+
+/// Encodes an unsigned big-endian integer.
+pub fn integer(bytes: &[u8]) -> Vec<u8> {
+  let skip = bytes.iter().take_while(|b| **b == 0).count();
+  let bytes = &bytes[skip..];
+  if bytes.is_empty() {
+    encode(TAG_INTEGER, &[0])
+  } else if bytes[0] & 0x80 != 0 {
+    encode(TAG_INTEGER, &[&[0u8][..], bytes].concat())
+  } else {
+    encode(TAG_INTEGER, bytes)
+  }
+}
+
+pub fn bit_string(bytes: &[u8]) -> Vec<u8> {
+  encode(TAG_BIT_STRING, &[&[0u8][..], bytes].concat())
+}
+
+pub fn octet_string(bytes: &[u8]) -> Vec<u8> {
+  encode(TAG_OCTET_STRING, bytes)
+}
+
+pub fn oid(contents: &[u8]) -> Vec<u8> {
+  encode(TAG_OID, contents)
+}
+
+pub fn null() -> Vec<u8> {
+  encode(TAG_NULL, &[])
+}
+
+pub struct Reader<'a> {
+  input: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+  pub fn new(input: &'a [u8]) -> Self {
+    Self { input }
+  }
+
+  pub fn peek_tag(&self) -> Option<u8> {
+    self.input.first().copied()
+  }
+
+  /// Reads the next element, which must have the given tag, and returns its
+  /// contents.
+  pub fn read(&mut self, tag: u8) -> Result<&'a [u8], AnyError> {
+    let malformed = || data_error("Malformed DER encoded key");
+    if self.peek_tag() != Some(tag) {
+      return Err(malformed());
+    }
+    let first = *self.input.get(1).ok_or_else(malformed)? as usize;
+    let (len, header) = if first < 0x80 {
+      (first, 2)
+    } else {
+      let count = first & 0x7f;
+      if count == 0 || count > 4 {
+        return Err(malformed());
+      }
+      let len_bytes = self.input.get(2..2 + count).ok_or_else(malformed)?;
+      let len = len_bytes
+        .iter()
+        .fold(0usize, |acc, b| (acc << 8) | *b as usize);
+      (len, 2 + count)
+    };
+    let contents =
+      self.input.get(header..header + len).ok_or_else(malformed)?;
+    self.input = &self.input[header + len..];
+    Ok(contents)
+  }
+
+  /// Reads an unsigned integer, stripping the leading sign byte if present.
+  pub fn read_integer(&mut self) -> Result<&'a [u8], AnyError> {
+    let contents = self.read(TAG_INTEGER)?;
+    match contents {
+      [0, rest @ ..] if !rest.is_empty() => Ok(rest),
+      _ => Ok(contents),
+    }
+  }
+
+  pub fn read_bit_string(&mut self) -> Result<&'a [u8], AnyError> {
+    match self.read(TAG_BIT_STRING)? {
+      [0, rest @ ..] => Ok(rest),
+      _ => Err(data_error("Unsupported BIT STRING encoding")),
+    }
+  }
+
+  pub fn read_sequence(&mut self) -> Result<Reader<'a>, AnyError> {
+    self.read(TAG_SEQUENCE).map(Reader::new)
+  }
+}
+
+/// Returns the leading zero-padded big-endian encoding of `bytes` in exactly
+/// `len` bytes, as needed for fixed size EC scalars and coordinates.
+pub fn pad_to(bytes: &[u8], len: usize) -> Result<Vec<u8>, AnyError> {
+  let skip = bytes.iter().take_while(|b| **b == 0).count();
+  let bytes = &bytes[skip..];
+  if bytes.len() > len {
+    return Err(data_error("Integer is too large"));
+  }
+  let mut out = vec![0; len - bytes.len()];
+  out.extend_from_slice(bytes);
+  Ok(out)
+}
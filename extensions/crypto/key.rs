@@ -0,0 +1,623 @@
+// Copyright 2018-2021 the Deno authors. All rights reserved. MIT license.
+
+use crate::data_error;
+use crate::der;
+use crate::not_supported_error;
+use deno_core::error::AnyError;
+use deno_core::Resource;
+use ring::digest;
+use ring::hkdf;
+use ring::hmac;
+use ring::pbkdf2;
+use ring::signature;
+use serde::Deserialize;
+use serde::Serialize;
+use std::borrow::Cow;
+
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, Eq, PartialEq)]
+pub enum CryptoHash {
+  #[serde(rename = "SHA-1")]
+  Sha1,
+  #[serde(rename = "SHA-256")]
+  Sha256,
+  #[serde(rename = "SHA-384")]
+  Sha384,
+  #[serde(rename = "SHA-512")]
+  Sha512,
+}
+
+impl CryptoHash {
+  pub fn digest_algorithm(self) -> &'static digest::Algorithm {
+    match self {
+      CryptoHash::Sha1 => &digest::SHA1_FOR_LEGACY_USE_ONLY,
+      CryptoHash::Sha256 => &digest::SHA256,
+      CryptoHash::Sha384 => &digest::SHA384,
+      CryptoHash::Sha512 => &digest::SHA512,
+    }
+  }
+
+  pub fn hmac_algorithm(self) -> hmac::Algorithm {
+    match self {
+      CryptoHash::Sha1 => hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY,
+      CryptoHash::Sha256 => hmac::HMAC_SHA256,
+      CryptoHash::Sha384 => hmac::HMAC_SHA384,
+      CryptoHash::Sha512 => hmac::HMAC_SHA512,
+    }
+  }
+
+  pub fn hkdf_algorithm(self) -> hkdf::Algorithm {
+    match self {
+      CryptoHash::Sha1 => hkdf::HKDF_SHA1_FOR_LEGACY_USE_ONLY,
+      CryptoHash::Sha256 => hkdf::HKDF_SHA256,
+      CryptoHash::Sha384 => hkdf::HKDF_SHA384,
+      CryptoHash::Sha512 => hkdf::HKDF_SHA512,
+    }
+  }
+
+  pub fn pbkdf2_algorithm(self) -> pbkdf2::Algorithm {
+    match self {
+      CryptoHash::Sha1 => pbkdf2::PBKDF2_HMAC_SHA1,
+      CryptoHash::Sha256 => pbkdf2::PBKDF2_HMAC_SHA256,
+      CryptoHash::Sha384 => pbkdf2::PBKDF2_HMAC_SHA384,
+      CryptoHash::Sha512 => pbkdf2::PBKDF2_HMAC_SHA512,
+    }
+  }
+
+  /// Block size of the hash function in bits, which is also the default
+  /// length of generated HMAC keys.
+  pub fn block_size(self) -> usize {
+    match self {
+      CryptoHash::Sha1 | CryptoHash::Sha256 => 512,
+      CryptoHash::Sha384 | CryptoHash::Sha512 => 1024,
+    }
+  }
+
+  /// Size of the digest in bytes.
+  pub fn output_len(self) -> usize {
+    self.digest_algorithm().output_len
+  }
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, Eq, PartialEq)]
+pub enum CryptoNamedCurve {
+  #[serde(rename = "P-256")]
+  P256,
+  #[serde(rename = "P-384")]
+  P384,
+}
+
+impl CryptoNamedCurve {
+  pub fn from_oid(oid: &[u8]) -> Option<Self> {
+    match oid {
+      der::OID_SECP256R1 => Some(CryptoNamedCurve::P256),
+      der::OID_SECP384R1 => Some(CryptoNamedCurve::P384),
+      _ => None,
+    }
+  }
+
+  pub fn oid(self) -> &'static [u8] {
+    match self {
+      CryptoNamedCurve::P256 => der::OID_SECP256R1,
+      CryptoNamedCurve::P384 => der::OID_SECP384R1,
+    }
+  }
+
+  pub fn name(self) -> &'static str {
+    match self {
+      CryptoNamedCurve::P256 => "P-256",
+      CryptoNamedCurve::P384 => "P-384",
+    }
+  }
+
+  /// Size in bytes of private scalars and point coordinates on the curve.
+  pub fn field_len(self) -> usize {
+    match self {
+      CryptoNamedCurve::P256 => 32,
+      CryptoNamedCurve::P384 => 48,
+    }
+  }
+
+  pub fn signing_algorithm(
+    self,
+    hash: CryptoHash,
+  ) -> Result<&'static signature::EcdsaSigningAlgorithm, AnyError> {
+    match (self, hash) {
+      (CryptoNamedCurve::P256, CryptoHash::Sha256) => {
+        Ok(&signature::ECDSA_P256_SHA256_FIXED_SIGNING)
+      }
+      (CryptoNamedCurve::P384, CryptoHash::Sha384) => {
+        Ok(&signature::ECDSA_P384_SHA384_FIXED_SIGNING)
+      }
+      _ => Err(not_supported_error(format!(
+        "ECDSA with {} and {:?} is not supported",
+        self.name(),
+        hash
+      ))),
+    }
+  }
+
+  pub fn verification_algorithm(
+    self,
+    hash: CryptoHash,
+  ) -> Result<&'static signature::EcdsaVerificationAlgorithm, AnyError> {
+    match (self, hash) {
+      (CryptoNamedCurve::P256, CryptoHash::Sha256) => {
+        Ok(&signature::ECDSA_P256_SHA256_FIXED)
+      }
+      (CryptoNamedCurve::P384, CryptoHash::Sha384) => {
+        Ok(&signature::ECDSA_P384_SHA384_FIXED)
+      }
+      _ => Err(not_supported_error(format!(
+        "ECDSA with {} and {:?} is not supported",
+        self.name(),
+        hash
+      ))),
+    }
+  }
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Algorithm {
+  #[serde(rename = "RSASSA-PKCS1-v1_5")]
+  RsassaPkcs1v15,
+  #[serde(rename = "RSA-PSS")]
+  RsaPss,
+  #[serde(rename = "ECDSA")]
+  Ecdsa,
+  #[serde(rename = "ECDH")]
+  Ecdh,
+  #[serde(rename = "HMAC")]
+  Hmac,
+  #[serde(rename = "AES-GCM")]
+  AesGcm,
+  #[serde(rename = "AES-CBC")]
+  AesCbc,
+  #[serde(rename = "AES-CTR")]
+  AesCtr,
+  #[serde(rename = "PBKDF2")]
+  Pbkdf2,
+  #[serde(rename = "HKDF")]
+  Hkdf,
+}
+
+impl Algorithm {
+  /// Whether keys of this algorithm can be built from the given material.
+  pub fn accepts(self, material: &KeyMaterial) -> bool {
+    match self {
+      Algorithm::RsassaPkcs1v15 | Algorithm::RsaPss => matches!(
+        material,
+        KeyMaterial::RsaPrivate(_) | KeyMaterial::RsaPublic(_)
+      ),
+      Algorithm::Ecdsa | Algorithm::Ecdh => matches!(
+        material,
+        KeyMaterial::EcPrivate { .. } | KeyMaterial::EcPublic(_)
+      ),
+      Algorithm::Hmac | Algorithm::Pbkdf2 | Algorithm::Hkdf => {
+        matches!(material, KeyMaterial::Secret(_))
+      }
+      Algorithm::AesGcm | Algorithm::AesCbc | Algorithm::AesCtr => {
+        matches!(material, KeyMaterial::Secret(secret) if matches!(secret.len(), 16 | 24 | 32))
+      }
+    }
+  }
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyType {
+  Secret,
+  Private,
+  Public,
+}
+
+#[derive(Deserialize, Copy, Clone, Debug, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyFormat {
+  Raw,
+  Pkcs8,
+  Spki,
+  Jwk,
+}
+
+/// The members of a JSON Web Key that carry key material. Validation of the
+/// remaining members (`alg`, `use`, `key_ops`, `ext`) happens in JavaScript.
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct JsonWebKey {
+  pub kty: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub crv: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub k: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub n: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub e: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub d: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub p: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub q: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub dp: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub dq: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub qi: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub x: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub y: Option<String>,
+}
+
+fn b64_decode(
+  value: &Option<String>,
+  member: &str,
+) -> Result<Vec<u8>, AnyError> {
+  let value = value
+    .as_ref()
+    .ok_or_else(|| data_error(format!("JWK is missing member '{}'", member)))?;
+  base64::decode_config(value, base64::URL_SAFE_NO_PAD).map_err(|_| {
+    data_error(format!("JWK member '{}' is not valid base64url", member))
+  })
+}
+
+fn b64_encode(bytes: &[u8]) -> Option<String> {
+  Some(base64::encode_config(bytes, base64::URL_SAFE_NO_PAD))
+}
+
+/// The raw key material held by a `CryptoKeyResource`.
+#[derive(Clone)]
+pub enum KeyMaterial {
+  Secret(Vec<u8>),
+  /// A PKCS#1 `RSAPrivateKey` structure.
+  RsaPrivate(Vec<u8>),
+  /// A PKCS#1 `RSAPublicKey` structure.
+  RsaPublic(Vec<u8>),
+  /// The private scalar and the uncompressed public point.
+  EcPrivate {
+    d: Vec<u8>,
+    public: Vec<u8>,
+  },
+  /// An uncompressed public point.
+  EcPublic(Vec<u8>),
+}
+
+impl KeyMaterial {
+  pub fn key_type(&self) -> KeyType {
+    match self {
+      KeyMaterial::Secret(_) => KeyType::Secret,
+      KeyMaterial::RsaPrivate(_) | KeyMaterial::EcPrivate { .. } => {
+        KeyType::Private
+      }
+      KeyMaterial::RsaPublic(_) | KeyMaterial::EcPublic(_) => KeyType::Public,
+    }
+  }
+
+  /// Derives the public half of a private key.
+  pub fn public_key(&self) -> Result<KeyMaterial, AnyError> {
+    match self {
+      KeyMaterial::RsaPrivate(pkcs1) => {
+        let [n, e, ..] = parse_rsa_private(pkcs1)?;
+        Ok(KeyMaterial::RsaPublic(encode_rsa_public(n, e)))
+      }
+      KeyMaterial::EcPrivate { public, .. } => {
+        Ok(KeyMaterial::EcPublic(public.clone()))
+      }
+      _ => Err(data_error("Key is not a private key")),
+    }
+  }
+
+  /// Returns the modulus and public exponent of an RSA key.
+  pub fn rsa_public_components(&self) -> Result<(&[u8], &[u8]), AnyError> {
+    match self {
+      KeyMaterial::RsaPrivate(pkcs1) => {
+        let [n, e, ..] = parse_rsa_private(pkcs1)?;
+        Ok((n, e))
+      }
+      KeyMaterial::RsaPublic(pkcs1) => parse_rsa_public(pkcs1),
+      _ => Err(data_error("Key is not an RSA key")),
+    }
+  }
+
+  pub fn from_pkcs8(
+    input: &[u8],
+  ) -> Result<(KeyMaterial, Option<CryptoNamedCurve>), AnyError> {
+    let mut info = der::Reader::new(input).read_sequence()?;
+    info.read_integer()?;
+    let mut algorithm = info.read_sequence()?;
+    let oid = algorithm.read(der::TAG_OID)?;
+    let private_key = info.read(der::TAG_OCTET_STRING)?;
+
+    match oid {
+      der::OID_RSA_ENCRYPTION => {
+        parse_rsa_private(private_key)?;
+        Ok((KeyMaterial::RsaPrivate(private_key.to_vec()), None))
+      }
+      der::OID_EC_PUBLIC_KEY => {
+        let curve = read_curve(&mut algorithm)?;
+        let mut ec_key = der::Reader::new(private_key).read_sequence()?;
+        ec_key.read_integer()?;
+        let d = ec_key.read(der::TAG_OCTET_STRING)?;
+        let mut public = None;
+        while let Some(tag) = ec_key.peek_tag() {
+          let contents = ec_key.read(tag)?;
+          if tag == der::TAG_CONTEXT_1 {
+            public = Some(der::Reader::new(contents).read_bit_string()?);
+          }
+        }
+        let public = public.ok_or_else(|| {
+          data_error("EC private key does not contain its public key")
+        })?;
+        check_ec_point(curve, public)?;
+        Ok((
+          KeyMaterial::EcPrivate {
+            d: der::pad_to(d, curve.field_len())?,
+            public: public.to_vec(),
+          },
+          Some(curve),
+        ))
+      }
+      _ => Err(data_error("Unsupported PKCS#8 key algorithm")),
+    }
+  }
+
+  pub fn from_spki(
+    input: &[u8],
+  ) -> Result<(KeyMaterial, Option<CryptoNamedCurve>), AnyError> {
+    let mut info = der::Reader::new(input).read_sequence()?;
+    let mut algorithm = info.read_sequence()?;
+    let oid = algorithm.read(der::TAG_OID)?;
+    let public_key = info.read_bit_string()?;
+
+    match oid {
+      der::OID_RSA_ENCRYPTION => {
+        parse_rsa_public(public_key)?;
+        Ok((KeyMaterial::RsaPublic(public_key.to_vec()), None))
+      }
+      der::OID_EC_PUBLIC_KEY => {
+        let curve = read_curve(&mut algorithm)?;
+        check_ec_point(curve, public_key)?;
+        Ok((KeyMaterial::EcPublic(public_key.to_vec()), Some(curve)))
+      }
+      _ => Err(data_error("Unsupported SPKI key algorithm")),
+    }
+  }
+
+  // The RSA parameters are named after their JWK members.
+  #[allow(clippy::many_single_char_names)]
+  pub fn from_jwk(
+    jwk: &JsonWebKey,
+    named_curve: Option<CryptoNamedCurve>,
+  ) -> Result<KeyMaterial, AnyError> {
+    match jwk.kty.as_str() {
+      "oct" => Ok(KeyMaterial::Secret(b64_decode(&jwk.k, "k")?)),
+      "RSA" => {
+        let n = b64_decode(&jwk.n, "n")?;
+        let e = b64_decode(&jwk.e, "e")?;
+        if jwk.d.is_none() {
+          return Ok(KeyMaterial::RsaPublic(encode_rsa_public(&n, &e)));
+        }
+        let d = b64_decode(&jwk.d, "d")?;
+        let p = b64_decode(&jwk.p, "p")?;
+        let q = b64_decode(&jwk.q, "q")?;
+        let dp = b64_decode(&jwk.dp, "dp")?;
+        let dq = b64_decode(&jwk.dq, "dq")?;
+        let qi = b64_decode(&jwk.qi, "qi")?;
+        Ok(KeyMaterial::RsaPrivate(encode_rsa_private([
+          &n, &e, &d, &p, &q, &dp, &dq, &qi,
+        ])))
+      }
+      "EC" => {
+        let curve = named_curve
+          .ok_or_else(|| data_error("EC keys require a named curve"))?;
+        if jwk.crv.as_deref() != Some(curve.name()) {
+          return Err(data_error("JWK 'crv' does not match the named curve"));
+        }
+        let x = b64_decode(&jwk.x, "x")?;
+        let y = b64_decode(&jwk.y, "y")?;
+        let public = [&[4u8][..], &x, &y].concat();
+        check_ec_point(curve, &public)?;
+        if jwk.d.is_none() {
+          return Ok(KeyMaterial::EcPublic(public));
+        }
+        let d = b64_decode(&jwk.d, "d")?;
+        if d.len() != curve.field_len() {
+          return Err(data_error("JWK 'd' has an invalid length"));
+        }
+        Ok(KeyMaterial::EcPrivate { d, public })
+      }
+      kty => Err(data_error(format!("Unsupported JWK key type '{}'", kty))),
+    }
+  }
+
+  pub fn to_pkcs8(
+    &self,
+    named_curve: Option<CryptoNamedCurve>,
+  ) -> Result<Vec<u8>, AnyError> {
+    match self {
+      KeyMaterial::RsaPrivate(pkcs1) => Ok(der::sequence(&[
+        &der::integer(&[0]),
+        &der::sequence(&[&der::oid(der::OID_RSA_ENCRYPTION), &der::null()]),
+        &der::octet_string(pkcs1),
+      ])),
+      KeyMaterial::EcPrivate { d, public } => {
+        let curve = require_named_curve(named_curve)?;
+        let ec_key = der::sequence(&[
+          &der::integer(&[1]),
+          &der::octet_string(d),
+          &der::encode(der::TAG_CONTEXT_0, &der::oid(curve.oid())),
+          &der::encode(der::TAG_CONTEXT_1, &der::bit_string(public)),
+        ]);
+        Ok(der::sequence(&[
+          &der::integer(&[0]),
+          &der::sequence(&[
+            &der::oid(der::OID_EC_PUBLIC_KEY),
+            &der::oid(curve.oid()),
+          ]),
+          &der::octet_string(&ec_key),
+        ]))
+      }
+      _ => Err(not_supported_error(
+        "Only private keys can be exported as pkcs8",
+      )),
+    }
+  }
+
+  pub fn to_spki(
+    &self,
+    named_curve: Option<CryptoNamedCurve>,
+  ) -> Result<Vec<u8>, AnyError> {
+    match self {
+      KeyMaterial::RsaPublic(pkcs1) => Ok(der::sequence(&[
+        &der::sequence(&[&der::oid(der::OID_RSA_ENCRYPTION), &der::null()]),
+        &der::bit_string(pkcs1),
+      ])),
+      KeyMaterial::EcPublic(public) => {
+        let curve = require_named_curve(named_curve)?;
+        Ok(der::sequence(&[
+          &der::sequence(&[
+            &der::oid(der::OID_EC_PUBLIC_KEY),
+            &der::oid(curve.oid()),
+          ]),
+          &der::bit_string(public),
+        ]))
+      }
+      _ => Err(not_supported_error(
+        "Only public keys can be exported as spki",
+      )),
+    }
+  }
+
+  #[allow(clippy::many_single_char_names)]
+  pub fn to_jwk(
+    &self,
+    named_curve: Option<CryptoNamedCurve>,
+  ) -> Result<JsonWebKey, AnyError> {
+    let jwk = match self {
+      KeyMaterial::Secret(secret) => JsonWebKey {
+        kty: "oct".to_string(),
+        k: b64_encode(secret),
+        ..Default::default()
+      },
+      KeyMaterial::RsaPrivate(pkcs1) => {
+        let [n, e, d, p, q, dp, dq, qi] = parse_rsa_private(pkcs1)?;
+        JsonWebKey {
+          kty: "RSA".to_string(),
+          n: b64_encode(n),
+          e: b64_encode(e),
+          d: b64_encode(d),
+          p: b64_encode(p),
+          q: b64_encode(q),
+          dp: b64_encode(dp),
+          dq: b64_encode(dq),
+          qi: b64_encode(qi),
+          ..Default::default()
+        }
+      }
+      KeyMaterial::RsaPublic(pkcs1) => {
+        let (n, e) = parse_rsa_public(pkcs1)?;
+        JsonWebKey {
+          kty: "RSA".to_string(),
+          n: b64_encode(n),
+          e: b64_encode(e),
+          ..Default::default()
+        }
+      }
+      KeyMaterial::EcPrivate { public, .. } | KeyMaterial::EcPublic(public) => {
+        let curve = require_named_curve(named_curve)?;
+        check_ec_point(curve, public)?;
+        let (x, y) = public[1..].split_at(curve.field_len());
+        let d = match self {
+          KeyMaterial::EcPrivate { d, .. } => b64_encode(d),
+          _ => None,
+        };
+        JsonWebKey {
+          kty: "EC".to_string(),
+          crv: Some(curve.name().to_string()),
+          x: b64_encode(x),
+          y: b64_encode(y),
+          d,
+          ..Default::default()
+        }
+      }
+    };
+    Ok(jwk)
+  }
+}
+
+/// The named curve of an EC key, as a data error when it is missing.
+pub fn require_named_curve(
+  named_curve: Option<CryptoNamedCurve>,
+) -> Result<CryptoNamedCurve, AnyError> {
+  named_curve.ok_or_else(|| data_error("EC keys require a named curve"))
+}
+
+fn read_curve(
+  algorithm: &mut der::Reader,
+) -> Result<CryptoNamedCurve, AnyError> {
+  let oid = algorithm.read(der::TAG_OID)?;
+  CryptoNamedCurve::from_oid(oid)
+    .ok_or_else(|| not_supported_error("Unsupported named curve"))
+}
+
+fn check_ec_point(
+  curve: CryptoNamedCurve,
+  point: &[u8],
+) -> Result<(), AnyError> {
+  if point.len() != 1 + 2 * curve.field_len() || point[0] != 4 {
+    return Err(data_error(format!(
+      "Expected an uncompressed {} point",
+      curve.name()
+    )));
+  }
+  Ok(())
+}
+
+/// Parses a PKCS#1 `RSAPrivateKey` into
+/// `[n, e, d, p, q, dp, dq, qi]`.
+pub fn parse_rsa_private(input: &[u8]) -> Result<[&[u8]; 8], AnyError> {
+  let mut key = der::Reader::new(input).read_sequence()?;
+  if key.read_integer()? != [0u8] {
+    return Err(data_error("Unsupported RSA private key version"));
+  }
+  let mut components: [&[u8]; 8] = Default::default();
+  for component in components.iter_mut() {
+    *component = key.read_integer()?;
+  }
+  Ok(components)
+}
+
+/// Parses a PKCS#1 `RSAPublicKey` into `(n, e)`.
+pub fn parse_rsa_public(input: &[u8]) -> Result<(&[u8], &[u8]), AnyError> {
+  let mut key = der::Reader::new(input).read_sequence()?;
+  let n = key.read_integer()?;
+  let e = key.read_integer()?;
+  Ok((n, e))
+}
+
+pub fn encode_rsa_private(components: [&[u8]; 8]) -> Vec<u8> {
+  let mut items = vec![der::integer(&[0])];
+  items.extend(components.iter().map(|c| der::integer(c)));
+  let items: Vec<&[u8]> = items.iter().map(|i| i.as_slice()).collect();
+  der::sequence(&items)
+}
+
+pub fn encode_rsa_public(n: &[u8], e: &[u8]) -> Vec<u8> {
+  der::sequence(&[&der::integer(n), &der::integer(e)])
+}
+
+/// A `CryptoKey` held on the Rust side. JavaScript only sees the rid and the
+/// metadata it keeps in the key's internal slots.
+pub struct CryptoKeyResource {
+  pub algorithm: Algorithm,
+  pub hash: Option<CryptoHash>,
+  pub named_curve: Option<CryptoNamedCurve>,
+  pub material: KeyMaterial,
+}
+
+impl Resource for CryptoKeyResource {
+  fn name(&self) -> Cow<str> {
+    "cryptoKey".into()
+  }
+}
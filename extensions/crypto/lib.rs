@@ -1,24 +1,67 @@
 // Copyright 2018-2021 the Deno authors. All rights reserved. MIT license.
 
+use deno_core::error::bad_resource_id;
+use deno_core::error::custom_error;
 use deno_core::error::null_opbuf;
+use deno_core::error::type_error;
 use deno_core::error::AnyError;
 use deno_core::include_js_files;
 use deno_core::op_async;
 use deno_core::op_sync;
 use deno_core::Extension;
 use deno_core::OpState;
+use deno_core::ResourceId;
 use deno_core::ZeroCopyBuf;
+use serde::Deserialize;
+use serde::Serialize;
+
+use aes::Aes128;
+use aes::Aes192;
+use aes::Aes256;
+use block_modes::block_padding::Pkcs7;
+use block_modes::BlockMode;
+use block_modes::Cbc;
+use ctr::cipher::NewCipher;
+use ctr::cipher::StreamCipher;
+use ctr::Ctr128BE;
+use ctr::Ctr32BE;
+use ctr::Ctr64BE;
 use rand::rngs::StdRng;
 use rand::thread_rng;
 use rand::Rng;
 use rand::SeedableRng;
+use ring::aead;
 use ring::digest;
+use ring::hkdf;
+use ring::hmac;
+use ring::pbkdf2;
+use ring::rand::SystemRandom;
+use ring::signature;
+use ring::signature::EcdsaKeyPair;
+use ring::signature::RsaKeyPair;
+use rsa::BigUint;
+use rsa::PrivateKeyEncoding;
+use rsa::RSAPrivateKey;
 use std::cell::RefCell;
+use std::num::NonZeroU32;
 use std::path::PathBuf;
 use std::rc::Rc;
 
 pub use rand; // Re-export rand
 
+mod der;
+mod key;
+
+use crate::key::require_named_curve;
+pub use crate::key::Algorithm;
+pub use crate::key::CryptoHash;
+pub use crate::key::CryptoKeyResource;
+pub use crate::key::CryptoNamedCurve;
+use crate::key::JsonWebKey;
+pub use crate::key::KeyFormat;
+pub use crate::key::KeyMaterial;
+pub use crate::key::KeyType;
+
 pub fn init(maybe_seed: Option<u64>) -> Extension {
   Extension::builder()
     .js(include_js_files!(
@@ -33,6 +76,14 @@ pub fn init(maybe_seed: Option<u64>) -> Extension {
       ),
       ("op_crypto_subtle_digest", op_async(op_crypto_subtle_digest)),
       ("op_crypto_random_uuid", op_sync(op_crypto_random_uuid)),
+      ("op_crypto_generate_key", op_async(op_crypto_generate_key)),
+      ("op_crypto_import_key", op_sync(op_crypto_import_key)),
+      ("op_crypto_export_key", op_sync(op_crypto_export_key)),
+      ("op_crypto_sign_key", op_async(op_crypto_sign_key)),
+      ("op_crypto_verify_key", op_async(op_crypto_verify_key)),
+      ("op_crypto_encrypt_key", op_async(op_crypto_encrypt_key)),
+      ("op_crypto_decrypt_key", op_async(op_crypto_decrypt_key)),
+      ("op_crypto_derive_bits", op_async(op_crypto_derive_bits)),
     ])
     .state(move |state| {
       if let Some(seed) = maybe_seed {
@@ -43,6 +94,22 @@ pub fn init(maybe_seed: Option<u64>) -> Extension {
     .build()
 }
 
+pub(crate) fn data_error(msg: impl Into<String>) -> AnyError {
+  custom_error("DOMExceptionDataError", msg.into())
+}
+
+pub(crate) fn not_supported_error(msg: impl Into<String>) -> AnyError {
+  custom_error("DOMExceptionNotSupportedError", msg.into())
+}
+
+fn operation_error(msg: impl Into<String>) -> AnyError {
+  custom_error("DOMExceptionOperationError", msg.into())
+}
+
+fn invalid_access_error(msg: impl Into<String>) -> AnyError {
+  custom_error("DOMExceptionInvalidAccessError", msg.into())
+}
+
 pub fn op_crypto_get_random_values(
   state: &mut OpState,
   mut zero_copy: ZeroCopyBuf,
@@ -107,6 +174,904 @@ pub async fn op_crypto_subtle_digest(
   Ok(output)
 }
 
+/// Returns an rng that honors `--seed`, for work moved off the op thread.
+fn detached_rng(state: &mut OpState) -> StdRng {
+  match state.try_borrow_mut::<StdRng>() {
+    Some(seeded_rng) => StdRng::from_seed(seeded_rng.gen()),
+    None => StdRng::from_entropy(),
+  }
+}
+
+/// Metadata about a key resource that the JavaScript side needs to fill in
+/// the `type` and `algorithm` of the `CryptoKey` wrapping it.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyInfo {
+  rid: ResourceId,
+  #[serde(rename = "type")]
+  key_type: KeyType,
+  length: Option<usize>,
+  modulus_length: Option<usize>,
+  public_exponent: Option<ZeroCopyBuf>,
+}
+
+fn add_key(
+  state: &mut OpState,
+  resource: CryptoKeyResource,
+) -> Result<KeyInfo, AnyError> {
+  let key_type = resource.material.key_type();
+  let mut info = KeyInfo {
+    rid: 0,
+    key_type,
+    length: None,
+    modulus_length: None,
+    public_exponent: None,
+  };
+  match &resource.material {
+    KeyMaterial::Secret(secret) => info.length = Some(secret.len() * 8),
+    KeyMaterial::RsaPrivate(_) | KeyMaterial::RsaPublic(_) => {
+      let (n, e) = resource.material.rsa_public_components()?;
+      let leading_zeros = n.first().map_or(0, |b| b.leading_zeros() as usize);
+      info.modulus_length = Some(n.len() * 8 - leading_zeros);
+      info.public_exponent = Some(e.to_vec().into());
+    }
+    _ => {}
+  }
+  info.rid = state.resource_table.add(resource);
+  Ok(info)
+}
+
+fn get_key(
+  state: &OpState,
+  rid: ResourceId,
+  algorithm: Algorithm,
+) -> Result<Rc<CryptoKeyResource>, AnyError> {
+  let key = state
+    .resource_table
+    .get::<CryptoKeyResource>(rid)
+    .ok_or_else(bad_resource_id)?;
+  if key.algorithm != algorithm {
+    return Err(invalid_access_error(
+      "The key is not usable with the requested algorithm",
+    ));
+  }
+  Ok(key)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerateKeyArgs {
+  algorithm: Algorithm,
+  hash: Option<CryptoHash>,
+  length: Option<usize>,
+  named_curve: Option<CryptoNamedCurve>,
+  modulus_length: Option<usize>,
+  public_exponent: Option<ZeroCopyBuf>,
+}
+
+/// Generates a secret key or a key pair. Key pairs are returned as
+/// `[privateKey, publicKey]`.
+pub async fn op_crypto_generate_key(
+  state: Rc<RefCell<OpState>>,
+  args: GenerateKeyArgs,
+  _: (),
+) -> Result<Vec<KeyInfo>, AnyError> {
+  let algorithm = args.algorithm;
+  let hash = args.hash;
+  let named_curve = args.named_curve;
+
+  let material = match algorithm {
+    Algorithm::Hmac => {
+      let hash = hash.ok_or_else(|| type_error("Missing argument hash"))?;
+      let length = args.length.unwrap_or_else(|| hash.block_size());
+      if length == 0 {
+        return Err(operation_error("HMAC key length must not be zero"));
+      }
+      let mut secret = vec![0; (length + 7) / 8];
+      detached_rng(&mut state.borrow_mut()).fill(&mut secret[..]);
+      KeyMaterial::Secret(secret)
+    }
+    Algorithm::AesGcm | Algorithm::AesCbc | Algorithm::AesCtr => {
+      let length = args
+        .length
+        .ok_or_else(|| type_error("Missing argument length"))?;
+      if !matches!(length, 128 | 192 | 256) {
+        return Err(operation_error(
+          "AES key length must be 128, 192 or 256 bits",
+        ));
+      }
+      let mut secret = vec![0; length / 8];
+      detached_rng(&mut state.borrow_mut()).fill(&mut secret[..]);
+      KeyMaterial::Secret(secret)
+    }
+    Algorithm::Ecdsa | Algorithm::Ecdh => {
+      let curve =
+        named_curve.ok_or_else(|| type_error("Missing argument namedCurve"))?;
+      let signing_algorithm = match curve {
+        CryptoNamedCurve::P256 => &signature::ECDSA_P256_SHA256_FIXED_SIGNING,
+        CryptoNamedCurve::P384 => &signature::ECDSA_P384_SHA384_FIXED_SIGNING,
+      };
+      let pkcs8 = tokio::task::spawn_blocking(move || {
+        EcdsaKeyPair::generate_pkcs8(signing_algorithm, &SystemRandom::new())
+      })
+      .await?
+      .map_err(|_| operation_error("Failed to generate EC key pair"))?;
+      KeyMaterial::from_pkcs8(pkcs8.as_ref())?.0
+    }
+    Algorithm::RsassaPkcs1v15 | Algorithm::RsaPss => {
+      let modulus_length = args
+        .modulus_length
+        .ok_or_else(|| type_error("Missing argument modulusLength"))?;
+      let public_exponent = args
+        .public_exponent
+        .ok_or_else(|| type_error("Missing argument publicExponent"))?;
+      let exponent = BigUint::from_bytes_be(&public_exponent);
+      let mut rng = detached_rng(&mut state.borrow_mut());
+      let pkcs1 =
+        tokio::task::spawn_blocking(move || -> Result<Vec<u8>, AnyError> {
+          let private_key =
+            RSAPrivateKey::new_with_exp(&mut rng, modulus_length, &exponent)?;
+          Ok(private_key.to_pkcs1()?)
+        })
+        .await?
+        .map_err(|e| operation_error(e.to_string()))?;
+      KeyMaterial::RsaPrivate(pkcs1)
+    }
+    Algorithm::Pbkdf2 | Algorithm::Hkdf => {
+      return Err(not_supported_error(format!(
+        "{:?} keys can not be generated",
+        algorithm
+      )))
+    }
+  };
+
+  let mut state = state.borrow_mut();
+  let new_key = |material| CryptoKeyResource {
+    algorithm,
+    hash,
+    named_curve,
+    material,
+  };
+  let keys = match material.key_type() {
+    KeyType::Secret => vec![add_key(&mut state, new_key(material))?],
+    _ => {
+      let public = material.public_key()?;
+      vec![
+        add_key(&mut state, new_key(material))?,
+        add_key(&mut state, new_key(public))?,
+      ]
+    }
+  };
+
+  Ok(keys)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportKeyArgs {
+  algorithm: Algorithm,
+  format: KeyFormat,
+  hash: Option<CryptoHash>,
+  named_curve: Option<CryptoNamedCurve>,
+  jwk: Option<JsonWebKey>,
+}
+
+pub fn op_crypto_import_key(
+  state: &mut OpState,
+  args: ImportKeyArgs,
+  zero_copy: Option<ZeroCopyBuf>,
+) -> Result<KeyInfo, AnyError> {
+  let (material, named_curve) = match args.format {
+    KeyFormat::Raw => {
+      let data = zero_copy.ok_or_else(null_opbuf)?.to_vec();
+      match args.algorithm {
+        Algorithm::RsassaPkcs1v15 | Algorithm::RsaPss => {
+          return Err(not_supported_error(
+            "RSA keys can not be imported in raw format",
+          ))
+        }
+        Algorithm::Ecdsa | Algorithm::Ecdh => {
+          (KeyMaterial::EcPublic(data), args.named_curve)
+        }
+        _ => (KeyMaterial::Secret(data), None),
+      }
+    }
+    KeyFormat::Pkcs8 => {
+      KeyMaterial::from_pkcs8(&zero_copy.ok_or_else(null_opbuf)?)?
+    }
+    KeyFormat::Spki => {
+      KeyMaterial::from_spki(&zero_copy.ok_or_else(null_opbuf)?)?
+    }
+    KeyFormat::Jwk => {
+      let jwk = args.jwk.ok_or_else(|| type_error("Missing argument jwk"))?;
+      let material = KeyMaterial::from_jwk(&jwk, args.named_curve)?;
+      (material, args.named_curve)
+    }
+  };
+
+  if !args.algorithm.accepts(&material) {
+    return Err(data_error("The key data does not match the algorithm"));
+  }
+  if named_curve != args.named_curve {
+    return Err(data_error("The key's named curve does not match"));
+  }
+
+  // Let ring reject keys it would refuse to use later on.
+  match (&material, named_curve) {
+    (KeyMaterial::RsaPrivate(pkcs1), _) => {
+      RsaKeyPair::from_der(pkcs1).map_err(|e| data_error(e.to_string()))?;
+    }
+    (KeyMaterial::EcPrivate { d, public }, Some(curve)) => {
+      let algorithm = match curve {
+        CryptoNamedCurve::P256 => &signature::ECDSA_P256_SHA256_FIXED_SIGNING,
+        CryptoNamedCurve::P384 => &signature::ECDSA_P384_SHA384_FIXED_SIGNING,
+      };
+      EcdsaKeyPair::from_private_key_and_public_key(algorithm, d, public)
+        .map_err(|e| data_error(e.to_string()))?;
+    }
+    (KeyMaterial::EcPublic(public), Some(curve)) => {
+      if public.len() != 1 + 2 * curve.field_len() || public[0] != 4 {
+        return Err(data_error("Expected an uncompressed EC point"));
+      }
+    }
+    _ => {}
+  }
+
+  add_key(
+    state,
+    CryptoKeyResource {
+      algorithm: args.algorithm,
+      hash: args.hash,
+      named_curve,
+      material,
+    },
+  )
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportKeyArgs {
+  rid: ResourceId,
+  format: KeyFormat,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportKeyResult {
+  data: Option<ZeroCopyBuf>,
+  jwk: Option<JsonWebKey>,
+}
+
+pub fn op_crypto_export_key(
+  state: &mut OpState,
+  args: ExportKeyArgs,
+  _: (),
+) -> Result<ExportKeyResult, AnyError> {
+  let key = state
+    .resource_table
+    .get::<CryptoKeyResource>(args.rid)
+    .ok_or_else(bad_resource_id)?;
+
+  let data = match args.format {
+    KeyFormat::Raw => match &key.material {
+      KeyMaterial::Secret(data) | KeyMaterial::EcPublic(data) => data.clone(),
+      _ => {
+        return Err(invalid_access_error(
+          "The key can not be exported in raw format",
+        ))
+      }
+    },
+    KeyFormat::Pkcs8 => key.material.to_pkcs8(key.named_curve)?,
+    KeyFormat::Spki => key.material.to_spki(key.named_curve)?,
+    KeyFormat::Jwk => {
+      return Ok(ExportKeyResult {
+        data: None,
+        jwk: Some(key.material.to_jwk(key.named_curve)?),
+      })
+    }
+  };
+
+  Ok(ExportKeyResult {
+    data: Some(data.into()),
+    jwk: None,
+  })
+}
+
+fn rsa_signing_padding(
+  algorithm: Algorithm,
+  hash: CryptoHash,
+) -> Result<&'static dyn signature::RsaEncoding, AnyError> {
+  let padding: &'static dyn signature::RsaEncoding = match (algorithm, hash) {
+    (Algorithm::RsassaPkcs1v15, CryptoHash::Sha256) => {
+      &signature::RSA_PKCS1_SHA256
+    }
+    (Algorithm::RsassaPkcs1v15, CryptoHash::Sha384) => {
+      &signature::RSA_PKCS1_SHA384
+    }
+    (Algorithm::RsassaPkcs1v15, CryptoHash::Sha512) => {
+      &signature::RSA_PKCS1_SHA512
+    }
+    (Algorithm::RsaPss, CryptoHash::Sha256) => &signature::RSA_PSS_SHA256,
+    (Algorithm::RsaPss, CryptoHash::Sha384) => &signature::RSA_PSS_SHA384,
+    (Algorithm::RsaPss, CryptoHash::Sha512) => &signature::RSA_PSS_SHA512,
+    _ => {
+      return Err(not_supported_error(format!(
+        "Signing with {:?} and {:?} is not supported",
+        algorithm, hash
+      )))
+    }
+  };
+  Ok(padding)
+}
+
+fn rsa_verification_parameters(
+  algorithm: Algorithm,
+  hash: CryptoHash,
+) -> Result<&'static signature::RsaParameters, AnyError> {
+  let parameters = match (algorithm, hash) {
+    (Algorithm::RsassaPkcs1v15, CryptoHash::Sha1) => {
+      &signature::RSA_PKCS1_2048_8192_SHA1_FOR_LEGACY_USE_ONLY
+    }
+    (Algorithm::RsassaPkcs1v15, CryptoHash::Sha256) => {
+      &signature::RSA_PKCS1_2048_8192_SHA256
+    }
+    (Algorithm::RsassaPkcs1v15, CryptoHash::Sha384) => {
+      &signature::RSA_PKCS1_2048_8192_SHA384
+    }
+    (Algorithm::RsassaPkcs1v15, CryptoHash::Sha512) => {
+      &signature::RSA_PKCS1_2048_8192_SHA512
+    }
+    (Algorithm::RsaPss, CryptoHash::Sha256) => {
+      &signature::RSA_PSS_2048_8192_SHA256
+    }
+    (Algorithm::RsaPss, CryptoHash::Sha384) => {
+      &signature::RSA_PSS_2048_8192_SHA384
+    }
+    (Algorithm::RsaPss, CryptoHash::Sha512) => {
+      &signature::RSA_PSS_2048_8192_SHA512
+    }
+    _ => {
+      return Err(not_supported_error(format!(
+        "Verifying with {:?} and {:?} is not supported",
+        algorithm, hash
+      )))
+    }
+  };
+  Ok(parameters)
+}
+
+/// ring only implements RSA-PSS with a salt as long as the digest.
+fn check_pss_salt_length(
+  algorithm: Algorithm,
+  hash: CryptoHash,
+  salt_length: Option<usize>,
+) -> Result<(), AnyError> {
+  match (algorithm, salt_length) {
+    (Algorithm::RsaPss, Some(len)) if len != hash.output_len() => {
+      Err(not_supported_error(format!(
+        "RSA-PSS with {:?} requires a saltLength of {}",
+        hash,
+        hash.output_len()
+      )))
+    }
+    (Algorithm::RsaPss, None) => Err(type_error("Missing argument saltLength")),
+    _ => Ok(()),
+  }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignArgs {
+  rid: ResourceId,
+  algorithm: Algorithm,
+  hash: Option<CryptoHash>,
+  salt_length: Option<usize>,
+}
+
+pub async fn op_crypto_sign_key(
+  state: Rc<RefCell<OpState>>,
+  args: SignArgs,
+  data: Option<ZeroCopyBuf>,
+) -> Result<ZeroCopyBuf, AnyError> {
+  let data = data.ok_or_else(null_opbuf)?;
+  let key = get_key(&state.borrow(), args.rid, args.algorithm)?;
+  let (material, key_hash, named_curve) =
+    (key.material.clone(), key.hash, key.named_curve);
+
+  let signature =
+    tokio::task::spawn_blocking(move || -> Result<Vec<u8>, AnyError> {
+      let signature = match (args.algorithm, material) {
+        (Algorithm::Hmac, KeyMaterial::Secret(secret)) => {
+          let hash =
+            key_hash.ok_or_else(|| data_error("HMAC keys require a hash"))?;
+          let key = hmac::Key::new(hash.hmac_algorithm(), &secret);
+          hmac::sign(&key, &data).as_ref().to_vec()
+        }
+        (algorithm, KeyMaterial::RsaPrivate(pkcs1))
+          if matches!(
+            algorithm,
+            Algorithm::RsassaPkcs1v15 | Algorithm::RsaPss
+          ) =>
+        {
+          let hash =
+            key_hash.ok_or_else(|| data_error("RSA keys require a hash"))?;
+          check_pss_salt_length(args.algorithm, hash, args.salt_length)?;
+          let padding = rsa_signing_padding(args.algorithm, hash)?;
+          let key_pair = RsaKeyPair::from_der(&pkcs1)
+            .map_err(|e| data_error(e.to_string()))?;
+          let mut signature = vec![0; key_pair.public_modulus_len()];
+          key_pair
+            .sign(padding, &SystemRandom::new(), &data, &mut signature)
+            .map_err(|_| operation_error("Failed to sign"))?;
+          signature
+        }
+        (Algorithm::Ecdsa, KeyMaterial::EcPrivate { d, public }) => {
+          let hash = args
+            .hash
+            .ok_or_else(|| type_error("Missing argument hash"))?;
+          let curve = require_named_curve(named_curve)?;
+          let key_pair = EcdsaKeyPair::from_private_key_and_public_key(
+            curve.signing_algorithm(hash)?,
+            &d,
+            &public,
+          )
+          .map_err(|e| data_error(e.to_string()))?;
+          key_pair
+            .sign(&SystemRandom::new(), &data)
+            .map_err(|_| operation_error("Failed to sign"))?
+            .as_ref()
+            .to_vec()
+        }
+        _ => {
+          return Err(invalid_access_error(
+            "The key can not be used for signing",
+          ))
+        }
+      };
+      Ok(signature)
+    })
+    .await??;
+
+  Ok(signature.into())
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyArgs {
+  rid: ResourceId,
+  algorithm: Algorithm,
+  hash: Option<CryptoHash>,
+  salt_length: Option<usize>,
+  signature: ZeroCopyBuf,
+}
+
+pub async fn op_crypto_verify_key(
+  state: Rc<RefCell<OpState>>,
+  args: VerifyArgs,
+  data: Option<ZeroCopyBuf>,
+) -> Result<bool, AnyError> {
+  let data = data.ok_or_else(null_opbuf)?;
+  let key = get_key(&state.borrow(), args.rid, args.algorithm)?;
+  let (material, key_hash, named_curve) =
+    (key.material.clone(), key.hash, key.named_curve);
+
+  let verified =
+    tokio::task::spawn_blocking(move || -> Result<bool, AnyError> {
+      let sig = &*args.signature;
+      let verified = match (args.algorithm, material) {
+        (Algorithm::Hmac, KeyMaterial::Secret(secret)) => {
+          let hash =
+            key_hash.ok_or_else(|| data_error("HMAC keys require a hash"))?;
+          let key = hmac::Key::new(hash.hmac_algorithm(), &secret);
+          hmac::verify(&key, &data, sig).is_ok()
+        }
+        (algorithm, KeyMaterial::RsaPublic(pkcs1))
+          if matches!(
+            algorithm,
+            Algorithm::RsassaPkcs1v15 | Algorithm::RsaPss
+          ) =>
+        {
+          let hash =
+            key_hash.ok_or_else(|| data_error("RSA keys require a hash"))?;
+          check_pss_salt_length(args.algorithm, hash, args.salt_length)?;
+          let parameters = rsa_verification_parameters(args.algorithm, hash)?;
+          signature::UnparsedPublicKey::new(parameters, pkcs1)
+            .verify(&data, sig)
+            .is_ok()
+        }
+        (Algorithm::Ecdsa, KeyMaterial::EcPublic(public)) => {
+          let hash = args
+            .hash
+            .ok_or_else(|| type_error("Missing argument hash"))?;
+          let curve = require_named_curve(named_curve)?;
+          signature::UnparsedPublicKey::new(
+            curve.verification_algorithm(hash)?,
+            public,
+          )
+          .verify(&data, sig)
+          .is_ok()
+        }
+        _ => {
+          return Err(invalid_access_error(
+            "The key can not be used for verification",
+          ))
+        }
+      };
+      Ok(verified)
+    })
+    .await??;
+
+  Ok(verified)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptArgs {
+  rid: ResourceId,
+  algorithm: Algorithm,
+  iv: Option<ZeroCopyBuf>,
+  additional_data: Option<ZeroCopyBuf>,
+  tag_length: Option<usize>,
+  counter: Option<ZeroCopyBuf>,
+  length: Option<usize>,
+}
+
+pub async fn op_crypto_encrypt_key(
+  state: Rc<RefCell<OpState>>,
+  args: EncryptArgs,
+  data: Option<ZeroCopyBuf>,
+) -> Result<ZeroCopyBuf, AnyError> {
+  aes_op(state, args, data, true).await
+}
+
+pub async fn op_crypto_decrypt_key(
+  state: Rc<RefCell<OpState>>,
+  args: EncryptArgs,
+  data: Option<ZeroCopyBuf>,
+) -> Result<ZeroCopyBuf, AnyError> {
+  aes_op(state, args, data, false).await
+}
+
+async fn aes_op(
+  state: Rc<RefCell<OpState>>,
+  args: EncryptArgs,
+  data: Option<ZeroCopyBuf>,
+  encrypt: bool,
+) -> Result<ZeroCopyBuf, AnyError> {
+  let data = data.ok_or_else(null_opbuf)?;
+  let key = get_key(&state.borrow(), args.rid, args.algorithm)?;
+  let secret = match &key.material {
+    KeyMaterial::Secret(secret) => secret.clone(),
+    _ => {
+      return Err(invalid_access_error(
+        "The key can not be used for encryption",
+      ))
+    }
+  };
+
+  let output =
+    tokio::task::spawn_blocking(move || -> Result<Vec<u8>, AnyError> {
+      match args.algorithm {
+        Algorithm::AesGcm => {
+          let iv = args.iv.ok_or_else(|| type_error("Missing argument iv"))?;
+          if !matches!(args.tag_length, None | Some(128)) {
+            return Err(not_supported_error(
+              "AES-GCM only supports a tagLength of 128",
+            ));
+          }
+          aes_gcm(
+            &secret,
+            &iv,
+            args.additional_data.as_deref().unwrap_or_default(),
+            &data,
+            encrypt,
+          )
+        }
+        Algorithm::AesCbc => {
+          let iv = args.iv.ok_or_else(|| type_error("Missing argument iv"))?;
+          aes_cbc(&secret, &iv, &data, encrypt)
+        }
+        Algorithm::AesCtr => {
+          let counter = args
+            .counter
+            .ok_or_else(|| type_error("Missing argument counter"))?;
+          let length = args
+            .length
+            .ok_or_else(|| type_error("Missing argument length"))?;
+          // CTR mode is symmetric, encryption and decryption are the same.
+          aes_ctr(&secret, &counter, length, &data)
+        }
+        _ => Err(invalid_access_error(
+          "The key can not be used for encryption",
+        )),
+      }
+    })
+    .await??;
+
+  Ok(output.into())
+}
+
+fn aes_gcm(
+  key: &[u8],
+  iv: &[u8],
+  additional_data: &[u8],
+  data: &[u8],
+  encrypt: bool,
+) -> Result<Vec<u8>, AnyError> {
+  let algorithm = match key.len() {
+    16 => &aead::AES_128_GCM,
+    32 => &aead::AES_256_GCM,
+    _ => {
+      return Err(not_supported_error(
+        "AES-GCM is only supported with 128 and 256 bit keys",
+      ))
+    }
+  };
+  let key = aead::LessSafeKey::new(
+    aead::UnboundKey::new(algorithm, key)
+      .map_err(|_| data_error("Invalid AES key"))?,
+  );
+  let nonce = aead::Nonce::try_assume_unique_for_key(iv)
+    .map_err(|_| not_supported_error("AES-GCM only supports a 96 bit iv"))?;
+  let aad = aead::Aad::from(additional_data);
+
+  let mut in_out = data.to_vec();
+  if encrypt {
+    key
+      .seal_in_place_append_tag(nonce, aad, &mut in_out)
+      .map_err(|_| operation_error("Encryption failed"))?;
+  } else {
+    let len = key
+      .open_in_place(nonce, aad, &mut in_out)
+      .map_err(|_| operation_error("Decryption failed"))?
+      .len();
+    in_out.truncate(len);
+  }
+  Ok(in_out)
+}
+
+type Aes128Cbc = Cbc<Aes128, Pkcs7>;
+type Aes192Cbc = Cbc<Aes192, Pkcs7>;
+type Aes256Cbc = Cbc<Aes256, Pkcs7>;
+
+fn aes_cbc(
+  key: &[u8],
+  iv: &[u8],
+  data: &[u8],
+  encrypt: bool,
+) -> Result<Vec<u8>, AnyError> {
+  let invalid_iv = |_| operation_error("AES-CBC requires a 128 bit iv");
+  let output = match (key.len(), encrypt) {
+    (16, true) => Aes128Cbc::new_from_slices(key, iv)
+      .map_err(invalid_iv)?
+      .encrypt_vec(data),
+    (24, true) => Aes192Cbc::new_from_slices(key, iv)
+      .map_err(invalid_iv)?
+      .encrypt_vec(data),
+    (32, true) => Aes256Cbc::new_from_slices(key, iv)
+      .map_err(invalid_iv)?
+      .encrypt_vec(data),
+    (16, false) => Aes128Cbc::new_from_slices(key, iv)
+      .map_err(invalid_iv)?
+      .decrypt_vec(data)
+      .map_err(|_| operation_error("Decryption failed"))?,
+    (24, false) => Aes192Cbc::new_from_slices(key, iv)
+      .map_err(invalid_iv)?
+      .decrypt_vec(data)
+      .map_err(|_| operation_error("Decryption failed"))?,
+    (32, false) => Aes256Cbc::new_from_slices(key, iv)
+      .map_err(invalid_iv)?
+      .decrypt_vec(data)
+      .map_err(|_| operation_error("Decryption failed"))?,
+    _ => {
+      return Err(data_error(
+        "AES-CBC is only supported with 128, 192 and 256 bit keys",
+      ))
+    }
+  };
+  Ok(output)
+}
+
+fn aes_ctr(
+  key: &[u8],
+  counter: &[u8],
+  length: usize,
+  data: &[u8],
+) -> Result<Vec<u8>, AnyError> {
+  let mut output = data.to_vec();
+  match (key.len(), length) {
+    (16, 32) => apply_keystream::<Ctr32BE<Aes128>>(key, counter, &mut output),
+    (24, 32) => apply_keystream::<Ctr32BE<Aes192>>(key, counter, &mut output),
+    (32, 32) => apply_keystream::<Ctr32BE<Aes256>>(key, counter, &mut output),
+    (16, 64) => apply_keystream::<Ctr64BE<Aes128>>(key, counter, &mut output),
+    (24, 64) => apply_keystream::<Ctr64BE<Aes192>>(key, counter, &mut output),
+    (32, 64) => apply_keystream::<Ctr64BE<Aes256>>(key, counter, &mut output),
+    (16, 128) => apply_keystream::<Ctr128BE<Aes128>>(key, counter, &mut output),
+    (24, 128) => apply_keystream::<Ctr128BE<Aes192>>(key, counter, &mut output),
+    (32, 128) => apply_keystream::<Ctr128BE<Aes256>>(key, counter, &mut output),
+    _ => Err(not_supported_error(
+      "AES-CTR only supports a counter length of 32, 64 or 128 bits",
+    )),
+  }?;
+  Ok(output)
+}
+
+fn apply_keystream<C: NewCipher + StreamCipher>(
+  key: &[u8],
+  counter: &[u8],
+  data: &mut [u8],
+) -> Result<(), AnyError> {
+  let mut cipher = C::new_from_slices(key, counter)
+    .map_err(|_| operation_error("AES-CTR requires a 128 bit counter"))?;
+  cipher
+    .try_apply_keystream(data)
+    .map_err(|_| operation_error("AES-CTR counter would wrap around"))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeriveBitsArgs {
+  rid: ResourceId,
+  algorithm: Algorithm,
+  hash: Option<CryptoHash>,
+  salt: Option<ZeroCopyBuf>,
+  info: Option<ZeroCopyBuf>,
+  iterations: Option<u32>,
+  public_key: Option<ResourceId>,
+  length: Option<usize>,
+}
+
+/// Derives `length` bits from a PBKDF2 or HKDF base key, or computes an ECDH
+/// shared secret between a private key and the public key `public_key`.
+pub async fn op_crypto_derive_bits(
+  state: Rc<RefCell<OpState>>,
+  args: DeriveBitsArgs,
+  _: (),
+) -> Result<ZeroCopyBuf, AnyError> {
+  let (key, public_key) = {
+    let state = state.borrow();
+    let key = get_key(&state, args.rid, args.algorithm)?;
+    let public_key = match args.public_key {
+      Some(rid) => Some(get_key(&state, rid, args.algorithm)?),
+      None => None,
+    };
+    (key, public_key)
+  };
+  let material = key.material.clone();
+  let named_curve = key.named_curve;
+  let public_material = public_key.map(|k| (k.material.clone(), k.named_curve));
+
+  let output =
+    tokio::task::spawn_blocking(move || -> Result<Vec<u8>, AnyError> {
+      match (args.algorithm, material) {
+        (Algorithm::Pbkdf2, KeyMaterial::Secret(secret)) => {
+          let hash = args
+            .hash
+            .ok_or_else(|| type_error("Missing argument hash"))?;
+          let salt = args
+            .salt
+            .ok_or_else(|| type_error("Missing argument salt"))?;
+          let iterations =
+            args.iterations.and_then(NonZeroU32::new).ok_or_else(|| {
+              operation_error("PBKDF2 iterations must not be zero")
+            })?;
+          let mut out = vec![0; derived_len(args.length)?];
+          pbkdf2::derive(
+            hash.pbkdf2_algorithm(),
+            iterations,
+            &salt,
+            &secret,
+            &mut out,
+          );
+          Ok(out)
+        }
+        (Algorithm::Hkdf, KeyMaterial::Secret(secret)) => {
+          let hash = args
+            .hash
+            .ok_or_else(|| type_error("Missing argument hash"))?;
+          let salt = args
+            .salt
+            .ok_or_else(|| type_error("Missing argument salt"))?;
+          let info = args
+            .info
+            .ok_or_else(|| type_error("Missing argument info"))?;
+          let len = derived_len(args.length)?;
+          let prk =
+            hkdf::Salt::new(hash.hkdf_algorithm(), &salt).extract(&secret);
+          let info = [&*info];
+          let okm = prk
+            .expand(&info, HkdfOutputLen(len))
+            .map_err(|_| operation_error("HKDF length is too large"))?;
+          let mut out = vec![0; len];
+          okm
+            .fill(&mut out)
+            .map_err(|_| operation_error("HKDF length is too large"))?;
+          Ok(out)
+        }
+        (Algorithm::Ecdh, KeyMaterial::EcPrivate { d, .. }) => {
+          let curve = require_named_curve(named_curve)?;
+          let public = match public_material {
+            Some((KeyMaterial::EcPublic(public), public_curve))
+              if public_curve == Some(curve) =>
+            {
+              public
+            }
+            _ => {
+              return Err(invalid_access_error(
+                "The public key must be an ECDH public key on the same curve",
+              ))
+            }
+          };
+          let mut secret = ecdh(curve, &d, &public)?;
+          if let Some(length) = args.length {
+            if length > secret.len() * 8 {
+              return Err(operation_error(
+                "Requested length exceeds the size of the shared secret",
+              ));
+            }
+            secret.truncate((length + 7) / 8);
+            if length % 8 != 0 {
+              let last = secret.len() - 1;
+              secret[last] &= 0xff << (8 - length % 8);
+            }
+          }
+          Ok(secret)
+        }
+        _ => Err(invalid_access_error(
+          "The key can not be used for deriving bits",
+        )),
+      }
+    })
+    .await??;
+
+  Ok(output.into())
+}
+
+/// Byte length of PBKDF2 and HKDF output, which must be a non-zero multiple
+/// of 8 bits.
+fn derived_len(length: Option<usize>) -> Result<usize, AnyError> {
+  match length {
+    Some(length) if length != 0 && length % 8 == 0 => Ok(length / 8),
+    _ => Err(operation_error(
+      "Length must be a non-zero multiple of 8 bits",
+    )),
+  }
+}
+
+struct HkdfOutputLen(usize);
+
+impl hkdf::KeyType for HkdfOutputLen {
+  fn len(&self) -> usize {
+    self.0
+  }
+}
+
+fn ecdh(
+  curve: CryptoNamedCurve,
+  d: &[u8],
+  public: &[u8],
+) -> Result<Vec<u8>, AnyError> {
+  match curve {
+    CryptoNamedCurve::P256 => {
+      let secret_key = p256::SecretKey::from_bytes(d)
+        .map_err(|_| data_error("Invalid P-256 private key"))?;
+      let public_key = p256::PublicKey::from_sec1_bytes(public)
+        .map_err(|_| data_error("Invalid P-256 public key"))?;
+      let shared = p256::elliptic_curve::ecdh::diffie_hellman(
+        secret_key.secret_scalar(),
+        public_key.as_affine(),
+      );
+      Ok(shared.as_bytes().to_vec())
+    }
+    // ring only supports ECDH with ephemeral keys, and the RustCrypto P-384
+    // crate does not implement curve arithmetic in a version that builds with
+    // our toolchain. `deriveBits` rejects P-384 keys before calling the op.
+    CryptoNamedCurve::P384 => {
+      Err(not_supported_error("ECDH is not supported with P-384"))
+    }
+  }
+}
+
 pub fn get_declaration() -> PathBuf {
   PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("lib.deno_crypto.d.ts")
 }
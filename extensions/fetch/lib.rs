@@ -79,7 +79,7 @@ pub fn init<P: FetchPermissions + 'static>(
     ])
     .state(move |state| {
       state.put::<reqwest::Client>({
-        create_http_client(user_agent.clone(), ca_data.clone()).unwrap()
+        create_http_client(user_agent.clone(), ca_data.clone(), None).unwrap()
       });
       state.put::<HttpClientDefaults>(HttpClientDefaults {
         ca_data: ca_data.clone(),
@@ -462,6 +462,8 @@ impl HttpClientResource {
 pub struct CreateHttpClientOptions {
   ca_file: Option<String>,
   ca_data: Option<String>,
+  cert_chain: Option<String>,
+  private_key: Option<String>,
 }
 
 pub fn op_create_http_client<FP>(
@@ -481,11 +483,22 @@ where
 
   let cert_data =
     get_cert_data(args.ca_file.as_deref(), args.ca_data.as_deref())?;
+  let identity = match (args.cert_chain, args.private_key) {
+    (Some(cert_chain), Some(private_key)) => {
+      Some(format!("{}\n{}", private_key, cert_chain).into_bytes())
+    }
+    (None, None) => None,
+    _ => {
+      return Err(type_error(
+        "Both certChain and privateKey must be specified together",
+      ))
+    }
+  };
   let client = create_http_client(
     defaults.user_agent.clone(),
     cert_data.or_else(|| defaults.ca_data.clone()),
-  )
-  .unwrap();
+    identity,
+  )?;
 
   let rid = state.resource_table.add(HttpClientResource::new(client));
   Ok(rid)
@@ -507,10 +520,13 @@ fn get_cert_data(
 }
 
 /// Create new instance of async reqwest::Client. This client supports
-/// proxies and doesn't follow redirects.
+/// proxies and doesn't follow redirects. `identity` is a PEM encoded private
+/// key and certificate chain, presented to servers that ask for a client
+/// certificate.
 pub fn create_http_client(
   user_agent: String,
   ca_data: Option<Vec<u8>>,
+  identity: Option<Vec<u8>>,
 ) -> Result<Client, AnyError> {
   let mut headers = HeaderMap::new();
   headers.insert(USER_AGENT, user_agent.parse().unwrap());
@@ -524,6 +540,11 @@ pub fn create_http_client(
     builder = builder.add_root_certificate(cert);
   }
 
+  if let Some(identity) = identity {
+    let identity = reqwest::Identity::from_pem(&identity)?;
+    builder = builder.identity(identity);
+  }
+
   builder
     .build()
     .map_err(|e| generic_error(format!("Unable to build http client: {}", e)))
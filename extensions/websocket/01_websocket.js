@@ -38,6 +38,10 @@
     }
   }
 
+  const _server = Symbol("[[server]]");
+  const _open = Symbol("[[open]]");
+  const _fail = Symbol("[[fail]]");
+
   const handlerSymbol = Symbol("eventHandlers");
   function makeWrappedHandler(handler) {
     function wrappedHandler(...args) {
@@ -81,6 +85,12 @@
       super();
       requiredArguments("WebSocket", arguments.length, 1);
 
+      // Server-side sockets don't connect anywhere, they are opened once the
+      // HTTP connection they were created for has been upgraded.
+      if (url === _server) {
+        return;
+      }
+
       const wsURL = new URL(url);
 
       if (wsURL.protocol !== "ws:" && wsURL.protocol !== "wss:") {
@@ -118,47 +128,55 @@
         url: wsURL.href,
         protocols: protocols.join(", "),
       }).then((create) => {
-        this.#rid = create.rid;
-        this.#extensions = create.extensions;
-        this.#protocol = create.protocol;
-
-        if (this.#readyState === CLOSING) {
-          core.opAsync("op_ws_close", {
-            rid: this.#rid,
-          }).then(() => {
-            this.#readyState = CLOSED;
+        this[_open](create.rid, create.protocol, create.extensions);
+      }).catch((err) => {
+        this[_fail](err);
+      });
+    }
 
-            const errEvent = new ErrorEvent("error");
-            errEvent.target = this;
-            this.dispatchEvent(errEvent);
+    [_open](rid, protocol, extensions) {
+      this.#rid = rid;
+      this.#extensions = extensions;
+      this.#protocol = protocol;
 
-            const event = new CloseEvent("close");
-            event.target = this;
-            this.dispatchEvent(event);
-            tryClose(this.#rid);
-          });
-        } else {
-          this.#readyState = OPEN;
-          const event = new Event("open");
+      if (this.#readyState === CLOSING) {
+        core.opAsync("op_ws_close", {
+          rid: this.#rid,
+        }).then(() => {
+          this.#readyState = CLOSED;
+
+          const errEvent = new ErrorEvent("error");
+          errEvent.target = this;
+          this.dispatchEvent(errEvent);
+
+          const event = new CloseEvent("close");
           event.target = this;
           this.dispatchEvent(event);
+          tryClose(this.#rid);
+        });
+      } else {
+        this.#readyState = OPEN;
+        const event = new Event("open");
+        event.target = this;
+        this.dispatchEvent(event);
 
-          this.#eventLoop();
-        }
-      }).catch((err) => {
-        this.#readyState = CLOSED;
+        this.#eventLoop();
+      }
+    }
 
-        const errorEv = new ErrorEvent(
-          "error",
-          { error: err, message: err.toString() },
-        );
-        errorEv.target = this;
-        this.dispatchEvent(errorEv);
+    [_fail](err) {
+      this.#readyState = CLOSED;
 
-        const closeEv = new CloseEvent("close");
-        closeEv.target = this;
-        this.dispatchEvent(closeEv);
-      });
+      const errorEv = new ErrorEvent(
+        "error",
+        { error: err, message: err.toString() },
+      );
+      errorEv.target = this;
+      this.dispatchEvent(errorEv);
+
+      const closeEv = new CloseEvent("close");
+      closeEv.target = this;
+      this.dispatchEvent(closeEv);
     }
 
     get CONNECTING() {
@@ -385,5 +403,36 @@
   defineEventHandler(WebSocket.prototype, "close");
   defineEventHandler(WebSocket.prototype, "open");
 
-  window.__bootstrap.webSocket = { WebSocket };
+  /**
+   * Creates the server-side end of a WebSocket, which stays in the
+   * `CONNECTING` state until it is opened with `openServerWebSocket`.
+   * @returns {WebSocket}
+   */
+  function createServerWebSocket() {
+    return new WebSocket(_server);
+  }
+
+  /**
+   * @param {WebSocket} ws
+   * @param {number} rid
+   * @param {string} protocol
+   */
+  function openServerWebSocket(ws, rid, protocol) {
+    ws[_open](rid, protocol, "");
+  }
+
+  /**
+   * @param {WebSocket} ws
+   * @param {Error} err
+   */
+  function failServerWebSocket(ws, err) {
+    ws[_fail](err);
+  }
+
+  window.__bootstrap.webSocket = {
+    WebSocket,
+    createServerWebSocket,
+    openServerWebSocket,
+    failServerWebSocket,
+  };
 })(this);
@@ -5,9 +5,9 @@ use deno_core::error::invalid_hostname;
 use deno_core::error::null_opbuf;
 use deno_core::error::type_error;
 use deno_core::error::AnyError;
-use deno_core::futures::stream::SplitSink;
-use deno_core::futures::stream::SplitStream;
+use deno_core::futures::Sink;
 use deno_core::futures::SinkExt;
+use deno_core::futures::Stream;
 use deno_core::futures::StreamExt;
 use deno_core::include_js_files;
 use deno_core::op_async;
@@ -24,6 +24,7 @@ use deno_core::ResourceId;
 use deno_core::ZeroCopyBuf;
 
 use http::{Method, Request, Uri};
+use hyper::upgrade::Upgraded;
 use serde::Deserialize;
 use serde::Serialize;
 use std::borrow::Cow;
@@ -31,13 +32,17 @@ use std::cell::RefCell;
 use std::io::BufReader;
 use std::io::Cursor;
 use std::path::PathBuf;
+use std::pin::Pin;
 use std::rc::Rc;
 use std::sync::Arc;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncWrite;
 use tokio::net::TcpStream;
 use tokio_rustls::{rustls::ClientConfig, TlsConnector};
+use tokio_tungstenite::tungstenite;
 use tokio_tungstenite::tungstenite::{
   handshake::client::Response, protocol::frame::coding::CloseCode,
-  protocol::CloseFrame, Message,
+  protocol::CloseFrame, protocol::Role, Message,
 };
 use tokio_tungstenite::MaybeTlsStream;
 use tokio_tungstenite::{client_async, WebSocketStream};
@@ -64,9 +69,13 @@ impl WebSocketPermissions for NoWebSocketPermissions {
 }
 
 type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+type WsSink = Pin<Box<dyn Sink<Message, Error = tungstenite::Error>>>;
+type WsSource =
+  Pin<Box<dyn Stream<Item = Result<Message, tungstenite::Error>>>>;
+
 struct WsStreamResource {
-  tx: AsyncRefCell<SplitSink<WsStream, Message>>,
-  rx: AsyncRefCell<SplitStream<WsStream>>,
+  tx: AsyncRefCell<WsSink>,
+  rx: AsyncRefCell<WsSource>,
   // When a `WsStreamResource` resource is closed, all pending 'read' ops are
   // canceled, while 'write' ops are allowed to complete. Therefore only
   // 'read' futures are attached to this cancel handle.
@@ -79,7 +88,32 @@ impl Resource for WsStreamResource {
   }
 }
 
-impl WsStreamResource {}
+impl WsStreamResource {
+  fn new<S>(stream: WebSocketStream<S>) -> Self
+  where
+    S: AsyncRead + AsyncWrite + Unpin + 'static,
+  {
+    let (ws_tx, ws_rx) = stream.split();
+    Self {
+      tx: AsyncRefCell::new(Box::pin(ws_tx)),
+      rx: AsyncRefCell::new(Box::pin(ws_rx)),
+      cancel: Default::default(),
+    }
+  }
+}
+
+/// Wraps a connection upgraded by the HTTP server into a server-side
+/// WebSocket stream, sharing the resource and ops of client-side sockets.
+pub async fn ws_create_server_stream(
+  state: &Rc<RefCell<OpState>>,
+  transport: Upgraded,
+) -> Result<ResourceId, AnyError> {
+  let ws_stream =
+    WebSocketStream::from_raw_socket(transport, Role::Server, None).await;
+  let resource = WsStreamResource::new(ws_stream);
+  let rid = state.borrow_mut().resource_table.add(resource);
+  Ok(rid)
+}
 
 // This op is needed because creating a WS instance in JavaScript is a sync
 // operation and should throw error when permissions are not fulfilled,
@@ -182,12 +216,7 @@ where
       ))
     })?;
 
-  let (ws_tx, ws_rx) = stream.split();
-  let resource = WsStreamResource {
-    rx: AsyncRefCell::new(ws_rx),
-    tx: AsyncRefCell::new(ws_tx),
-    cancel: Default::default(),
-  };
+  let resource = WsStreamResource::new(stream);
   let mut state = state.borrow_mut();
   let rid = state.resource_table.add(resource);
 
@@ -3,8 +3,16 @@
 
 ((window) => {
   const { InnerBody } = window.__bootstrap.fetchBody;
-  const { Response, fromInnerRequest, toInnerResponse, newInnerRequest } =
-    window.__bootstrap.fetch;
+  const {
+    Response,
+    fromInnerRequest,
+    fromInnerResponse,
+    toInnerResponse,
+    newInnerRequest,
+    newInnerResponse,
+  } = window.__bootstrap.fetch;
+  const { createServerWebSocket, openServerWebSocket, failServerWebSocket } =
+    window.__bootstrap.webSocket;
   const errors = window.__bootstrap.errors.errors;
   const core = window.Deno.core;
   const { ReadableStream } = window.__bootstrap.streams;
@@ -15,6 +23,7 @@
   }
 
   const connErrorSymbol = Symbol("connError");
+  const _ws = Symbol("[[associated_ws]]");
 
   class HttpConn {
     #rid = 0;
@@ -84,6 +93,55 @@
       core.close(this.#rid);
     }
 
+    /**
+     * Stops accepting new requests and waits for the responses in flight to
+     * be written, closing the connection regardless once `timeout`
+     * milliseconds have passed.
+     * @param {{ timeout?: number }} options
+     * @returns {Promise<void>}
+     */
+    async shutdown(options = {}) {
+      const { timeout } = options;
+      const drained = core.opAsync("op_http_shutdown", this.#rid).catch(
+        (error) => {
+          // The connection was closed before it drained.
+          if (
+            error instanceof errors.BadResource ||
+            error instanceof errors.Interrupted
+          ) {
+            return;
+          }
+          throw error;
+        },
+      );
+
+      let timerId;
+      try {
+        if (timeout === undefined) {
+          await drained;
+        } else {
+          await Promise.race([
+            drained,
+            new Promise((resolve) => {
+              timerId = setTimeout(resolve, timeout);
+            }),
+          ]);
+        }
+      } finally {
+        clearTimeout(timerId);
+      }
+
+      // Responses still in flight after the deadline are dropped.
+      try {
+        core.close(this.#rid);
+      } catch (error) {
+        if (!(error instanceof errors.BadResource)) {
+          throw error;
+        }
+      }
+      await drained;
+    }
+
     [Symbol.asyncIterator]() {
       // deno-lint-ignore no-this-alias
       const httpConn = this;
@@ -119,6 +177,27 @@
 
       const innerResp = toInnerResponse(resp);
 
+      const ws = resp[_ws];
+      if (ws) {
+        let wsRid;
+        try {
+          wsRid = await Deno.core.opAsync("op_http_upgrade_websocket", [
+            responseSenderRid,
+            innerResp.status,
+            innerResp.headerList,
+          ]);
+        } catch (error) {
+          failServerWebSocket(ws, error);
+          throw error;
+        }
+        openServerWebSocket(
+          ws,
+          wsRid,
+          resp.headers.get("sec-websocket-protocol") ?? "",
+        );
+        return;
+      }
+
       // If response body length is known, it will be sent synchronously in a
       // single op, in other case a "response body" resource will be created and
       // we'll be streaming it.
@@ -243,7 +322,69 @@
     });
   }
 
+  /**
+   * @param {Request} request
+   * @param {{ protocol?: string }} options
+   * @returns {{ response: Response, socket: WebSocket }}
+   */
+  function upgradeWebSocket(request, options = {}) {
+    if (request.headers.get("upgrade")?.toLowerCase() !== "websocket") {
+      throw new TypeError(
+        "Invalid Header: 'upgrade' header must be 'websocket'",
+      );
+    }
+
+    const connection = request.headers.get("connection") ?? "";
+    if (
+      !connection.split(",").some((token) =>
+        token.trim().toLowerCase() === "upgrade"
+      )
+    ) {
+      throw new TypeError(
+        "Invalid Header: 'connection' header must contain 'upgrade'",
+      );
+    }
+
+    const websocketKey = request.headers.get("sec-websocket-key");
+    if (websocketKey === null) {
+      throw new TypeError(
+        "Invalid Header: 'sec-websocket-key' header must be set",
+      );
+    }
+
+    const accept = core.opSync(
+      "op_http_websocket_accept_header",
+      websocketKey,
+    );
+
+    const r = newInnerResponse(101);
+    r.headerList = [
+      ["upgrade", "websocket"],
+      ["connection", "Upgrade"],
+      ["sec-websocket-accept", accept],
+    ];
+
+    if (options.protocol !== undefined) {
+      const protocols = (request.headers.get("sec-websocket-protocol") ?? "")
+        .split(",")
+        .map((protocol) => protocol.trim());
+      if (!protocols.includes(options.protocol)) {
+        throw new TypeError(
+          `Protocol '${options.protocol}' not in the request's protocol list (non negotiable)`,
+        );
+      }
+      r.headerList.push(["sec-websocket-protocol", options.protocol]);
+    }
+
+    const response = fromInnerResponse(r, "immutable");
+    const socket = createServerWebSocket();
+    response[_ws] = socket;
+
+    return { response, socket };
+  }
+
   window.__bootstrap.http = {
     serveHttp,
+    upgradeWebSocket,
   };
 })(this);
@@ -97,6 +97,7 @@
     stdout = "inherit",
     stderr = "inherit",
     stdin = "inherit",
+    processGroup = false,
   }) {
     if (cmd[0] != null) {
       cmd[0] = pathFromURL(cmd[0]);
@@ -111,6 +112,7 @@
       stdinRid: isRid(stdin) ? stdin : 0,
       stdoutRid: isRid(stdout) ? stdout : 0,
       stderrRid: isRid(stderr) ? stderr : 0,
+      processGroup,
     });
     return new Process(res);
   }
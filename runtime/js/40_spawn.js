@@ -0,0 +1,274 @@
+// Copyright 2018-2021 the Deno authors. All rights reserved. MIT license.
+"use strict";
+
+((window) => {
+  const core = window.Deno.core;
+  const { File } = window.__bootstrap.files;
+  const { read, readAll, write } = window.__bootstrap.io;
+  const { build } = window.__bootstrap.build;
+  const { signals } = window.__bootstrap.signals;
+  const { pathFromURL } = window.__bootstrap.util;
+
+  const SIGKILL = 9;
+  const DEFAULT_PTY_SIZE = { columns: 80, rows: 24 };
+
+  /** The size of the terminal Deno itself is attached to, if any. */
+  function terminalSize() {
+    try {
+      return core.opSync("op_console_size", 1);
+    } catch {
+      return DEFAULT_PTY_SIZE;
+    }
+  }
+
+  function spawnArgs(command, {
+    args = [],
+    cwd = undefined,
+    clearEnv = false,
+    env = {},
+    uid = undefined,
+    gid = undefined,
+    processGroup = false,
+    pty = false,
+    stdin = "null",
+    stdout = "piped",
+    stderr = "piped",
+  }) {
+    return {
+      cmd: pathFromURL(command),
+      args: args.map(String),
+      cwd: cwd === undefined ? undefined : pathFromURL(cwd),
+      clearEnv,
+      env: Object.entries(env),
+      uid,
+      gid,
+      processGroup,
+      pty: pty === true ? terminalSize() : pty || null,
+      stdio: { stdin, stdout, stderr },
+    };
+  }
+
+  async function readAllAndClose(file) {
+    try {
+      return await readAll(file);
+    } finally {
+      file.close();
+    }
+  }
+
+  class Pty {
+    #rid;
+
+    constructor(rid) {
+      this.#rid = rid;
+    }
+
+    get rid() {
+      return this.#rid;
+    }
+
+    read(p) {
+      return read(this.#rid, p);
+    }
+
+    write(p) {
+      return write(this.#rid, p);
+    }
+
+    resize({ columns, rows }) {
+      core.opSync("op_pty_resize", {
+        rid: this.#rid,
+        size: { columns, rows },
+      });
+    }
+
+    close() {
+      core.close(this.#rid);
+    }
+  }
+
+  class Child {
+    #rid;
+    #pid;
+    #status = null;
+    #stdin = null;
+    #stdout = null;
+    #stderr = null;
+    #pty = null;
+
+    constructor({ rid, pid, stdinRid, stdoutRid, stderrRid, ptyRid }) {
+      this.#rid = rid;
+      this.#pid = pid;
+      if (stdinRid != null) this.#stdin = new File(stdinRid);
+      if (stdoutRid != null) this.#stdout = new File(stdoutRid);
+      if (stderrRid != null) this.#stderr = new File(stderrRid);
+      if (ptyRid != null) this.#pty = new Pty(ptyRid);
+    }
+
+    get rid() {
+      return this.#rid;
+    }
+
+    get pid() {
+      return this.#pid;
+    }
+
+    get stdin() {
+      return this.#stdin;
+    }
+
+    get stdout() {
+      return this.#stdout;
+    }
+
+    get stderr() {
+      return this.#stderr;
+    }
+
+    get pty() {
+      return this.#pty;
+    }
+
+    /** Resolves once the child has exited. Only started on first use, so that
+     * a closed child doesn't leave behind a rejected promise nobody awaits. */
+    get status() {
+      if (this.#status === null) {
+        this.#status = core.opAsync("op_spawn_wait", this.#rid);
+      }
+      return this.#status;
+    }
+
+    async output() {
+      if (this.#stdout === null && this.#stderr === null) {
+        throw new TypeError("Neither stdout nor stderr was piped");
+      }
+      return await collectOutput(this);
+    }
+
+    kill(signo, { group = false } = {}) {
+      core.opSync("op_spawn_kill", { rid: this.#rid, signo, group });
+    }
+
+    /** Releases the child, killing it if it is still running. A child that
+     * leads a process group takes the rest of its group down with it. */
+    close() {
+      core.close(this.#rid);
+    }
+  }
+
+  async function collectOutput(child) {
+    const [status, stdout, stderr] = await Promise.all([
+      child.status,
+      child.stdout === null ? null : readAllAndClose(child.stdout),
+      child.stderr === null ? null : readAllAndClose(child.stderr),
+    ]);
+    return { status, stdout, stderr };
+  }
+
+  function spawnChild(command, options = {}) {
+    const {
+      signal = undefined,
+      timeout = undefined,
+      processGroup = false,
+      pty = false,
+    } = options;
+    if (signal?.aborted) {
+      throw new DOMException("The command was aborted.", "AbortError");
+    }
+    if (
+      pty &&
+      (options.stdin !== undefined || options.stdout !== undefined ||
+        options.stderr !== undefined)
+    ) {
+      throw new TypeError("The stdio of a child attached to a pty is the pty");
+    }
+
+    const child = new Child(
+      core.opSync("op_spawn_child", spawnArgs(command, options)),
+    );
+    // A child attached to a pty leads its own process group.
+    const group = processGroup || Boolean(pty);
+
+    if (pty === true && build.os !== "windows") {
+      // Keep the pty the same size as our own terminal, the child is notified
+      // through SIGWINCH.
+      const resizes = signals.windowChange();
+      (async () => {
+        for await (const _ of resizes) {
+          try {
+            child.pty.resize(terminalSize());
+          } catch {
+            // The pty has already been closed.
+          }
+        }
+      })();
+      const dispose = () => resizes.dispose();
+      child.status.then(dispose, dispose);
+    }
+
+    if (signal !== undefined || timeout !== undefined) {
+      const onAbort = () => {
+        try {
+          child.kill(SIGKILL, { group });
+        } catch {
+          // The child has already exited.
+        }
+      };
+      const timer = timeout === undefined
+        ? undefined
+        : setTimeout(onAbort, timeout);
+      signal?.addEventListener("abort", onAbort);
+      const cleanup = () => {
+        clearTimeout(timer);
+        signal?.removeEventListener("abort", onAbort);
+      };
+      child.status.then(cleanup, cleanup);
+    }
+
+    return child;
+  }
+
+  async function spawn(command, options = {}) {
+    if (options.stdin === "piped") {
+      throw new TypeError(
+        "Piped stdin is not supported by Deno.spawn, use Deno.spawnChild",
+      );
+    }
+    if (options.pty) {
+      throw new TypeError("Deno.spawn can't attach a pty, use Deno.spawnChild");
+    }
+    const child = spawnChild(command, options);
+    try {
+      return await collectOutput(child);
+    } finally {
+      child.close();
+    }
+  }
+
+  function spawnSync(command, options = {}) {
+    if (options.stdin === "piped") {
+      throw new TypeError(
+        "Piped stdin is not supported by Deno.spawnSync, use Deno.spawnChild",
+      );
+    }
+    if (options.pty) {
+      throw new TypeError(
+        "Deno.spawnSync can't attach a pty, use Deno.spawnChild",
+      );
+    }
+    if (options.signal !== undefined || options.timeout !== undefined) {
+      throw new TypeError(
+        "Deno.spawnSync can't be aborted, use Deno.spawn instead",
+      );
+    }
+    return core.opSync("op_spawn_sync", spawnArgs(command, options));
+  }
+
+  window.__bootstrap.spawn = {
+    Child,
+    Pty,
+    spawnChild,
+    spawn,
+    spawnSync,
+  };
+})(this);
@@ -6,19 +6,71 @@
   const { parsePermissions } = window.__bootstrap.worker;
   const { setExitHandler } = window.__bootstrap.os;
   const { Console, inspectArgs } = window.__bootstrap.console;
-  const { metrics } = window.__bootstrap.metrics;
   const { assert } = window.__bootstrap.util;
 
+  const OP_DETAILS = {
+    "op_accept": ["accept a connection on a listener", "closing the listener"],
+    "op_accept_tls": [
+      "accept a TLS connection on a listener",
+      "closing the listener",
+    ],
+    "op_connect": ["connect to a socket", "awaiting `Deno.connect`"],
+    "op_connect_tls": [
+      "connect to a TLS socket",
+      "awaiting `Deno.connectTls`",
+    ],
+    "op_dns_resolve": ["resolve a DNS name", "awaiting `Deno.resolveDns`"],
+    "op_fetch_send": ["send a HTTP request", "awaiting the `fetch` call"],
+    "op_fetch_response_read": [
+      "read a HTTP response body",
+      "consuming or cancelling the response body",
+    ],
+    "op_fs_events_poll": [
+      "get the next file system event",
+      "closing the `Deno.FsWatcher`",
+    ],
+    "op_global_timer": [
+      "sleep for a duration",
+      "cancelling the `setTimeout` or `setInterval` call",
+    ],
+    "op_http_request_next": [
+      "receive the next HTTP request",
+      "closing the `Deno.HttpConn`",
+    ],
+    "op_read_async": ["read from a resource", "awaiting the read"],
+    "op_run_status": [
+      "get the status of a subprocess",
+      "awaiting `process.status()`",
+    ],
+    "op_signal_poll": ["receive a signal", "disposing of the signal stream"],
+    "op_write_async": ["write to a resource", "awaiting the write"],
+  };
+
+  function describeOpLeak(name, count) {
+    const [description, hint] = OP_DETAILS[name] ??
+      [`complete the "${name}" op`, null];
+    const operations = count === 1 ? "operation" : "operations";
+    const was = count === 1 ? "was" : "were";
+    let message = `${count} async ${operations} to ${description} ${was}`;
+    message += count > 0
+      ? " started in this test, but never completed."
+      : " started before this test, but completed during the test.";
+    if (hint && count > 0) {
+      message += ` This is often caused by not ${hint}.`;
+    }
+    return message;
+  }
+
   // Wrap test function in additional assertion that makes sure
   // the test case does not leak async "ops" - ie. number of async
   // completed ops after the test is the same as number of dispatched
-  // ops. Note that "unref" ops are ignored since in nature that are
-  // optional.
+  // ops, for every op. Note that "unref" ops are ignored since in nature
+  // that are optional.
   function assertOps(fn) {
-    return async function asyncOpSanitizer() {
-      const pre = metrics();
+    return async function asyncOpSanitizer(...params) {
+      const pre = core.opSync("op_get_test_pending_ops");
       try {
-        await fn();
+        await fn(...params);
       } finally {
         // Defer until next event loop turn - that way timeouts and intervals
         // cleared can actually be removed from resource table, otherwise
@@ -26,20 +78,22 @@
         await new Promise((resolve) => setTimeout(resolve, 0));
       }
 
-      const post = metrics();
+      const post = core.opSync("op_get_test_pending_ops");
       // We're checking diff because one might spawn HTTP server in the background
       // that will be a pending async op before test starts.
-      const dispatchedDiff = post.opsDispatchedAsync - pre.opsDispatchedAsync;
-      const completedDiff = post.opsCompletedAsync - pre.opsCompletedAsync;
+      const details = [];
+      for (const name of new Set([...Object.keys(pre), ...Object.keys(post)])) {
+        const diff = (post[name] ?? 0) - (pre[name] ?? 0);
+        if (diff !== 0) {
+          details.push(describeOpLeak(name, diff));
+        }
+      }
+
       assert(
-        dispatchedDiff === completedDiff,
+        details.length === 0,
         `Test case is leaking async ops.
-Before:
-  - dispatched: ${pre.opsDispatchedAsync}
-  - completed: ${pre.opsCompletedAsync}
-After:
-  - dispatched: ${post.opsDispatchedAsync}
-  - completed: ${post.opsCompletedAsync}
+
+${details.map((detail) => ` - ${detail}`).join("\n")}
 
 Make sure to await all promises returned from Deno APIs before
 finishing test case.`,
@@ -47,33 +101,86 @@ finishing test case.`,
     };
   }
 
+  const RESOURCE_DETAILS = {
+    "child": ["A child process", "awaiting `process.status()` and closing it"],
+    "childStdin": ["A child process stdin", "closing `process.stdin`"],
+    "childStdout": ["A child process stdout", "closing `process.stdout`"],
+    "childStderr": ["A child process stderr", "closing `process.stderr`"],
+    "fetchResponseBody": [
+      "A fetch response body",
+      "consuming or cancelling the response body",
+    ],
+    "fsEvents": ["A file system watcher", "calling `watcher.close()`"],
+    "fsFile": ["A file", "calling `file.close()`"],
+    "httpClient": ["A HTTP client", "calling `client.close()`"],
+    "httpConnection": ["A HTTP connection", "calling `httpConn.close()`"],
+    "signal": ["A signal listener", "calling `signal.dispose()`"],
+    "tcpListener": ["A TCP listener", "calling `listener.close()`"],
+    "tcpStream": ["A TCP connection", "calling `conn.close()`"],
+    "tlsListener": ["A TLS listener", "calling `listener.close()`"],
+    "tlsStream": ["A TLS connection", "calling `conn.close()`"],
+    "udpSocket": ["A UDP socket", "calling `socket.close()`"],
+    "unixDatagram": ["A Unix datagram socket", "calling `socket.close()`"],
+    "unixListener": ["A Unix socket listener", "calling `listener.close()`"],
+    "unixStream": ["A Unix socket connection", "calling `conn.close()`"],
+    "webSocketStream": ["A WebSocket", "calling `socket.close()`"],
+  };
+
+  // Resources that are closed when the object holding them is garbage
+  // collected, which may happen during any later test.
+  const COLLECTED_RESOURCES = ["cryptoKey"];
+
+  function describeResourceLeak(rid, name, opened) {
+    const [description, hint] = RESOURCE_DETAILS[name] ??
+      [`A "${name}" resource`, null];
+    if (!opened) {
+      return `${description} (rid ${rid}) was closed during the test, but not opened during the test.`;
+    }
+    let message =
+      `${description} (rid ${rid}) was opened during the test, but not closed during the test.`;
+    if (hint) {
+      message += ` Close it by ${hint}.`;
+    }
+    return message;
+  }
+
   // Wrap test function in additional assertion that makes sure
   // the test case does not "leak" resources - ie. resource table after
   // the test has exactly the same contents as before the test.
   function assertResources(
     fn,
   ) {
-    return async function resourceSanitizer() {
+    return async function resourceSanitizer(...params) {
       const pre = core.resources();
-      await fn();
+      await fn(...params);
       const post = core.resources();
 
-      const preStr = JSON.stringify(pre, null, 2);
-      const postStr = JSON.stringify(post, null, 2);
+      const details = [];
+      for (const [rid, name] of Object.entries(post)) {
+        if (pre[rid] !== name && !COLLECTED_RESOURCES.includes(name)) {
+          details.push(describeResourceLeak(rid, name, true));
+        }
+      }
+      for (const [rid, name] of Object.entries(pre)) {
+        if (post[rid] !== name && !COLLECTED_RESOURCES.includes(name)) {
+          details.push(describeResourceLeak(rid, name, false));
+        }
+      }
+
       const msg = `Test case is leaking resources.
-Before: ${preStr}
-After: ${postStr}
+
+${details.map((detail) => ` - ${detail}`).join("\n")}
 
 Make sure to close all open resource handles returned from Deno APIs before
 finishing test case.`;
-      assert(preStr === postStr, msg);
+      assert(details.length === 0, msg);
     };
   }
 
   // Wrap test function in additional assertion that makes sure
   // that the test case does not accidentally exit prematurely.
   function assertExit(fn) {
-    return async function exitSanitizer() {
+    return async function exitSanitizer(...params) {
       setExitHandler((exitCode) => {
         assert(
           false,
@@ -82,7 +189,7 @@ finishing test case.`;
       });
 
       try {
-        await fn();
+        await fn(...params);
       } catch (err) {
         throw err;
       } finally {
@@ -146,6 +253,118 @@ finishing test case.`;
     return core.opSync("op_post_test_message", { message: { kind, data } });
   }
 
+  function createScope(name, level) {
+    return {
+      name,
+      level,
+      finished: false,
+      runningStep: false,
+      failedSteps: 0,
+    };
+  }
+
+  // Runs the function of a test or step with a context for its own steps, and
+  // fails it if any of those steps failed or were left running.
+  async function runInScope(scope, fn) {
+    try {
+      await fn(createTestContext(scope));
+    } finally {
+      scope.finished = true;
+    }
+
+    if (scope.runningStep) {
+      throw new Error(
+        "There were still test steps running after the current scope finished execution. " +
+          "Ensure all steps are awaited (ex. `await t.step(...)`).",
+      );
+    }
+
+    if (scope.failedSteps > 0) {
+      throw new Error(
+        `${scope.failedSteps} test step${
+          scope.failedSteps === 1 ? "" : "s"
+        } failed.`,
+      );
+    }
+  }
+
+  function createTestContext(parent) {
+    return {
+      async step(nameOrTestDefinition, fn) {
+        if (parent.finished) {
+          throw new Error(
+            "Cannot run test step after the parent scope has finished execution. " +
+              "Ensure any `.step(...)` calls are awaited.",
+          );
+        }
+
+        if (parent.runningStep) {
+          throw new Error(
+            "Cannot start a test step while another test step with the same parent is running. " +
+              "Ensure any `.step(...)` calls are awaited.",
+          );
+        }
+
+        let stepDef;
+        if (typeof nameOrTestDefinition === "string") {
+          if (!fn || typeof fn != "function") {
+            throw new TypeError("Missing test step function");
+          }
+          stepDef = { name: nameOrTestDefinition, fn, ignore: false };
+        } else {
+          if (!nameOrTestDefinition?.fn) {
+            throw new TypeError("Missing test step function");
+          }
+          stepDef = { ignore: false, ...nameOrTestDefinition };
+        }
+        if (!stepDef.name) {
+          throw new TypeError("The test step name can't be empty");
+        }
+
+        const scope = createScope(stepDef.name, parent.level + 1);
+        const time = Date.now();
+        let result;
+
+        parent.runningStep = true;
+        postTestMessage("stepWait", {
+          name: scope.name,
+          level: scope.level,
+        });
+
+        try {
+          if (stepDef.ignore) {
+            result = "ignored";
+          } else {
+            await runInScope(scope, stepDef.fn);
+            result = "ok";
+          }
+        } catch (error) {
+          result = {
+            "failed": inspectArgs([error]),
+          };
+          parent.failedSteps++;
+        } finally {
+          parent.runningStep = false;
+        }
+
+        // The parent has already failed if it finished before this step, so
+        // there is nothing left to report the result to.
+        if (parent.finished) {
+          return false;
+        }
+
+        postTestMessage("stepResult", {
+          name: scope.name,
+          level: scope.level,
+          duration: Date.now() - time,
+          result,
+        });
+
+        return result === "ok";
+      },
+    };
+  }
+
   function createTestFilter(filter) {
     return (def) => {
       if (filter) {
@@ -196,7 +415,7 @@ finishing test case.`;
         return;
       }
 
-      await fn();
+      await runInScope(createScope(name, 0), fn);
 
       const duration = Date.now() - time;
       postTestMessage("result", {
@@ -221,9 +440,31 @@ finishing test case.`;
     }
   }
 
+  // A small seeded pseudo random number generator (mulberry32), so that a
+  // shuffled order can be reproduced from its seed.
+  function createSeededRandom(seed) {
+    let state = seed >>> 0;
+    return () => {
+      state = (state + 0x6d2b79f5) >>> 0;
+      let t = state;
+      t = Math.imul(t ^ (t >>> 15), t | 1);
+      t ^= t + Math.imul(t ^ (t >>> 7), t | 61);
+      return ((t ^ (t >>> 14)) >>> 0) / 4294967296;
+    };
+  }
+
+  function shuffle(array, seed) {
+    const random = createSeededRandom(seed);
+    for (let i = array.length - 1; i > 0; i--) {
+      const j = Math.floor(random() * (i + 1));
+      [array[i], array[j]] = [array[j], array[i]];
+    }
+  }
+
   async function runTests({
     disableLog = false,
     filter = null,
+    shuffle: shuffleSeed = null,
   } = {}) {
     const originalConsole = globalThis.console;
     if (disableLog) {
@@ -234,6 +475,10 @@ finishing test case.`;
     const pending = (only.length > 0 ? only : tests).filter(
       createTestFilter(filter),
     );
+    if (shuffleSeed !== null) {
+      shuffle(pending, shuffleSeed);
+    }
+
     postTestMessage("plan", {
       filtered: tests.length - pending.length,
       pending: pending.length,
@@ -249,12 +494,182 @@ finishing test case.`;
     }
   }
 
+  const benches = [];
+
+  // Main bench function provided by Deno, registers a benchmark to be run by
+  // `deno bench`.
+  function bench(
+    b,
+    fn,
+  ) {
+    let benchDef;
+    const defaults = {
+      ignore: false,
+      only: false,
+      warmup: null,
+      n: null,
+    };
+
+    if (typeof b === "string") {
+      if (!fn || typeof fn != "function") {
+        throw new TypeError("Missing bench function");
+      }
+      if (!b) {
+        throw new TypeError("The bench name can't be empty");
+      }
+      benchDef = { fn: fn, name: b, ...defaults };
+    } else {
+      if (!b.fn) {
+        throw new TypeError("Missing bench function");
+      }
+      if (!b.name) {
+        throw new TypeError("The bench name can't be empty");
+      }
+      benchDef = { ...defaults, ...b };
+    }
+
+    for (const option of ["warmup", "n"]) {
+      const value = benchDef[option];
+      if (value !== null && !(Number.isInteger(value) && value > 0)) {
+        throw new TypeError(`The bench "${option}" must be a positive integer`);
+      }
+    }
+
+    benches.push(benchDef);
+  }
+
+  function postBenchMessage(kind, data) {
+    return core.opSync("op_post_bench_message", { message: { kind, data } });
+  }
+
+  function benchNow() {
+    return core.opSync("op_bench_now");
+  }
+
+  // When not set explicitly, a benchmark is warmed up for this long and then
+  // sampled for at least as long as the measure budget.
+  const WARMUP_BUDGET_NS = 100 * 1e6;
+  const MEASURE_BUDGET_NS = 500 * 1e6;
+  const MIN_SAMPLES = 10;
+  const MAX_SAMPLES = 100000;
+  // Timing a single call of a fast function mostly measures the clock, so
+  // calls are timed in batches that take at least this long.
+  const MIN_BATCH_NS = 100 * 1e3;
+  const MAX_BATCH_SIZE = 10000;
+
+  // Returns the mean time of a call of `fn` over a batch of `batchSize` calls.
+  async function measureIteration(fn, batchSize) {
+    const start = benchNow();
+    for (let i = 0; i < batchSize; i++) {
+      const result = fn();
+      if (result instanceof Promise) {
+        await result;
+      }
+    }
+    return (benchNow() - start) / batchSize;
+  }
+
+  function computeStats(samples) {
+    samples.sort((a, b) => a - b);
+    const percentile = (p) =>
+      samples[Math.max(0, Math.ceil(p * samples.length) - 1)];
+    const sum = samples.reduce((sum, sample) => sum + sample, 0);
+    return {
+      samples: samples.length,
+      min: samples[0],
+      max: samples[samples.length - 1],
+      mean: sum / samples.length,
+      p75: percentile(0.75),
+      p99: percentile(0.99),
+    };
+  }
+
+  async function runBench({ name, ignore, fn, warmup, n }) {
+    postBenchMessage("wait", { name });
+
+    if (ignore) {
+      postBenchMessage("result", { name, result: "ignored" });
+      return;
+    }
+
+    try {
+      let warmupTime = 0;
+      let warmupCount = 0;
+      if (warmup === null) {
+        do {
+          warmupTime += await measureIteration(fn, 1);
+          warmupCount++;
+        } while (warmupTime < WARMUP_BUDGET_NS);
+      } else {
+        for (; warmupCount < warmup; warmupCount++) {
+          warmupTime += await measureIteration(fn, 1);
+        }
+      }
+
+      // The batch size follows from the mean time of a warmup call.
+      const batchSize = warmupTime > 0
+        ? Math.min(
+          MAX_BATCH_SIZE,
+          Math.max(1, Math.ceil(MIN_BATCH_NS * warmupCount / warmupTime)),
+        )
+        : 1;
+
+      const samples = [];
+      if (n === null) {
+        let elapsed = 0;
+        while (
+          samples.length < MAX_SAMPLES &&
+          (samples.length < MIN_SAMPLES || elapsed < MEASURE_BUDGET_NS)
+        ) {
+          const sample = await measureIteration(fn, batchSize);
+          samples.push(sample);
+          elapsed += sample * batchSize;
+        }
+      } else {
+        for (let i = 0; i < n; i++) {
+          samples.push(await measureIteration(fn, batchSize));
+        }
+      }
+
+      postBenchMessage("result", {
+        name,
+        result: { "ok": computeStats(samples) },
+      });
+    } catch (error) {
+      postBenchMessage("result", {
+        name,
+        result: { "failed": inspectArgs([error]) },
+      });
+    }
+  }
+
+  async function runBenchmarks({
+    filter = null,
+  } = {}) {
+    const only = benches.filter((bench) => bench.only);
+    const pending = (only.length > 0 ? only : benches).filter(
+      createTestFilter(filter),
+    );
+
+    postBenchMessage("plan", {
+      filtered: benches.length - pending.length,
+      pending: pending.length,
+      only: only.length > 0,
+    });
+
+    for (const bench of pending) {
+      await runBench(bench);
+    }
+  }
+
   window.__bootstrap.internals = {
     ...window.__bootstrap.internals ?? {},
     runTests,
+    runBenchmarks,
   };
 
   window.__bootstrap.testing = {
     test,
+    bench,
   };
 })(this);
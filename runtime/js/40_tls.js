@@ -23,25 +23,68 @@
     return core.opAsync("op_start_tls", args);
   }
 
+  function opTlsHandshake(rid) {
+    return core.opAsync("op_tls_handshake", rid);
+  }
+
+  class TlsConn extends Conn {
+    #alpnProtocol = null;
+
+    constructor(rid, remoteAddr, localAddr, alpnProtocol) {
+      super(rid, remoteAddr, localAddr);
+      this.#alpnProtocol = alpnProtocol ?? null;
+    }
+
+    get alpnProtocol() {
+      return this.#alpnProtocol;
+    }
+
+    // Accepted connections only learn their protocol once the handshake is
+    // awaited here.
+    async handshake() {
+      const info = await opTlsHandshake(this.rid);
+      this.#alpnProtocol = info.alpnProtocol;
+      return info;
+    }
+
+    async peerCertificates() {
+      const { peerCertificates } = await this.handshake();
+      return peerCertificates;
+    }
+  }
+
   async function connectTls({
     port,
     hostname = "127.0.0.1",
     transport = "tcp",
     certFile = undefined,
+    certChain = undefined,
+    privateKey = undefined,
+    alpnProtocols,
   }) {
     const res = await opConnectTls({
       port,
       hostname,
       transport,
       certFile,
+      certChain,
+      privateKey,
+      alpnProtocols,
     });
-    return new Conn(res.rid, res.remoteAddr, res.localAddr);
+    return new TlsConn(
+      res.rid,
+      res.remoteAddr,
+      res.localAddr,
+      res.alpnProtocol,
+    );
   }
 
   class TLSListener extends Listener {
     async accept() {
       const res = await opAcceptTLS(this.rid);
-      return new Conn(res.rid, res.remoteAddr, res.localAddr);
+      // The handshake of an accepted connection hasn't happened yet, see
+      // `TlsConn.handshake()`.
+      return new TlsConn(res.rid, res.remoteAddr, res.localAddr);
     }
   }
 
@@ -52,6 +95,8 @@
     hostname = "0.0.0.0",
     transport = "tcp",
     alpnProtocols,
+    requireClientCert = false,
+    clientCaFile = undefined,
   }) {
     const res = opListenTls({
       port,
@@ -60,20 +105,36 @@
       hostname,
       transport,
       alpnProtocols,
+      requireClientCert,
+      clientCaFile,
     });
     return new TLSListener(res.rid, res.localAddr);
   }
 
   async function startTls(
     conn,
-    { hostname = "127.0.0.1", certFile } = {},
+    {
+      hostname = "127.0.0.1",
+      certFile,
+      certChain,
+      privateKey,
+      alpnProtocols,
+    } = {},
   ) {
     const res = await opStartTls({
       rid: conn.rid,
       hostname,
       certFile,
+      certChain,
+      privateKey,
+      alpnProtocols,
     });
-    return new Conn(res.rid, res.remoteAddr, res.localAddr);
+    return new TlsConn(
+      res.rid,
+      res.remoteAddr,
+      res.localAddr,
+      res.alpnProtocol,
+    );
   }
 
   window.__bootstrap.tls = {
@@ -81,5 +142,6 @@
     listenTls,
     connectTls,
     TLSListener,
+    TlsConn,
   };
 })(this);
@@ -5,6 +5,7 @@
   const __bootstrap = window.__bootstrap;
   __bootstrap.denoNs = {
     test: __bootstrap.testing.test,
+    bench: __bootstrap.testing.bench,
     metrics: __bootstrap.metrics.metrics,
     Process: __bootstrap.process.Process,
     run: __bootstrap.process.run,
@@ -109,6 +110,11 @@
     emit: __bootstrap.compilerApi.emit,
     openPlugin: __bootstrap.plugins.openPlugin,
     kill: __bootstrap.process.kill,
+    spawn: __bootstrap.spawn.spawn,
+    spawnChild: __bootstrap.spawn.spawnChild,
+    spawnSync: __bootstrap.spawn.spawnSync,
+    Child: __bootstrap.spawn.Child,
+    Pty: __bootstrap.spawn.Pty,
     setRaw: __bootstrap.tty.setRaw,
     consoleSize: __bootstrap.tty.consoleSize,
     DiagnosticCategory: __bootstrap.diagnostics.DiagnosticCategory,
@@ -125,6 +131,7 @@
     connect: __bootstrap.netUnstable.connect,
     listenDatagram: __bootstrap.netUnstable.listenDatagram,
     serveHttp: __bootstrap.http.serveHttp,
+    upgradeWebSocket: __bootstrap.http.upgradeWebSocket,
     startTls: __bootstrap.tls.startTls,
     umask: __bootstrap.fs.umask,
     futime: __bootstrap.fs.futime,
@@ -206,6 +206,18 @@ delete Object.prototype.__proto__;
         return new DOMException(msg, "InvalidCharacterError");
       },
     );
+    core.registerErrorBuilder(
+      "DOMExceptionDataError",
+      function DOMExceptionDataError(msg) {
+        return new DOMException(msg, "DataError");
+      },
+    );
+    core.registerErrorBuilder(
+      "DOMExceptionInvalidAccessError",
+      function DOMExceptionInvalidAccessError(msg) {
+        return new DOMException(msg, "InvalidAccessError");
+      },
+    );
   }
 
   class Navigator {
@@ -314,6 +326,7 @@ delete Object.prototype.__proto__;
     ),
     crypto: util.readOnly(crypto.crypto),
     Crypto: util.nonEnumerable(crypto.Crypto),
+    CryptoKey: util.nonEnumerable(crypto.CryptoKey),
     SubtleCrypto: util.nonEnumerable(crypto.SubtleCrypto),
     fetch: util.writable(fetch.fetch),
     performance: util.writable(performance.performance),
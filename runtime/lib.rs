@@ -20,6 +20,7 @@ pub mod inspector_server;
 pub mod js;
 pub mod metrics;
 pub mod ops;
+pub mod permission_log;
 pub mod permissions;
 pub mod resolve_addr;
 pub mod tokio_util;
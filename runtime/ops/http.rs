@@ -2,12 +2,16 @@
 
 use crate::ops::io::TcpStreamResource;
 use crate::ops::io::TlsStreamResource;
-use crate::ops::tls::TlsStream;
+use async_compression::tokio::bufread;
+use async_compression::tokio::write;
+use async_compression::Level;
 use deno_core::error::bad_resource_id;
 use deno_core::error::null_opbuf;
 use deno_core::error::type_error;
 use deno_core::error::AnyError;
+use deno_core::futures::executor::block_on;
 use deno_core::futures::future::poll_fn;
+use deno_core::futures::future::LocalBoxFuture;
 use deno_core::futures::FutureExt;
 use deno_core::futures::Stream;
 use deno_core::futures::StreamExt;
@@ -22,17 +26,21 @@ use deno_core::RcRef;
 use deno_core::Resource;
 use deno_core::ResourceId;
 use deno_core::ZeroCopyBuf;
+use deno_websocket::tokio_tungstenite::tungstenite::handshake::derive_accept_key;
+use deno_websocket::ws_create_server_stream;
 use hyper::body::HttpBody;
 use hyper::http;
 use hyper::server::conn::Connection;
 use hyper::server::conn::Http;
 use hyper::service::Service as HyperService;
+use hyper::upgrade::OnUpgrade;
 use hyper::Body;
 use hyper::Request;
 use hyper::Response;
 use serde::Deserialize;
 use serde::Serialize;
 use std::borrow::Cow;
+use std::cell::Cell;
 use std::cell::RefCell;
 use std::future::Future;
 use std::net::SocketAddr;
@@ -40,9 +48,12 @@ use std::pin::Pin;
 use std::rc::Rc;
 use std::task::Context;
 use std::task::Poll;
+use tokio::io::AsyncRead;
 use tokio::io::AsyncReadExt;
-use tokio::net::TcpStream;
+use tokio::io::AsyncWrite;
+use tokio::io::AsyncWriteExt;
 use tokio::sync::oneshot;
+use tokio_util::io::ReaderStream;
 use tokio_util::io::StreamReader;
 
 pub fn init() -> Extension {
@@ -54,6 +65,15 @@ pub fn init() -> Extension {
       ("op_http_response", op_async(op_http_response)),
       ("op_http_response_write", op_async(op_http_response_write)),
       ("op_http_response_close", op_async(op_http_response_close)),
+      ("op_http_shutdown", op_async(op_http_shutdown)),
+      (
+        "op_http_websocket_accept_header",
+        op_sync(op_http_websocket_accept_header),
+      ),
+      (
+        "op_http_upgrade_websocket",
+        op_async(op_http_upgrade_websocket),
+      ),
     ])
     .build()
 }
@@ -97,9 +117,36 @@ impl HyperService<Request<Body>> for Service {
   }
 }
 
+/// A connection served with upgrades. hyper doesn't export the type of those,
+/// so the connection is boxed, and a graceful shutdown is requested by
+/// sending on the channel passed to `serve_with_upgrades`.
+type HttpConnection = LocalBoxFuture<'static, Result<(), hyper::Error>>;
+
+fn serve_with_upgrades<I>(
+  connection: Connection<I, Service, LocalExecutor>,
+  shutdown_rx: oneshot::Receiver<()>,
+) -> HttpConnection
+where
+  I: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+  let mut connection = connection.with_upgrades();
+  let mut shutdown_rx = Some(shutdown_rx);
+  poll_fn(move |cx| {
+    let shutdown = shutdown_rx
+      .as_mut()
+      .map_or(false, |rx| rx.poll_unpin(cx).is_ready());
+    if shutdown {
+      shutdown_rx = None;
+      Pin::new(&mut connection).graceful_shutdown();
+    }
+    connection.poll_unpin(cx)
+  })
+  .boxed_local()
+}
+
 enum ConnType {
-  Tcp(Rc<RefCell<Connection<TcpStream, Service, LocalExecutor>>>),
-  Tls(Rc<RefCell<Connection<TlsStream, Service, LocalExecutor>>>),
+  Tcp(Rc<RefCell<HttpConnection>>),
+  Tls(Rc<RefCell<HttpConnection>>),
 }
 
 struct ConnResource {
@@ -107,16 +154,43 @@ struct ConnResource {
   deno_service: Service,
   addr: SocketAddr,
   cancel: CancelHandle,
+  shutdown_tx: RefCell<Option<oneshot::Sender<()>>>,
+  // An upgraded connection has handed its IO over and must not be polled
+  // again, so completion is remembered here.
+  done: Cell<bool>,
 }
 
 impl ConnResource {
   // TODO(ry) impl Future for ConnResource?
   fn poll(&self, cx: &mut Context<'_>) -> Poll<Result<(), AnyError>> {
-    match &self.hyper_connection {
+    if self.done.get() {
+      return Poll::Ready(Ok(()));
+    }
+
+    let poll = match &self.hyper_connection {
       ConnType::Tcp(c) => c.borrow_mut().poll_unpin(cx),
       ConnType::Tls(c) => c.borrow_mut().poll_unpin(cx),
     }
-    .map_err(AnyError::from)
+    .map_err(AnyError::from);
+
+    if poll.is_ready() {
+      self.done.set(true);
+    }
+
+    poll
+  }
+
+  /// Stops reading new requests from the connection, which then completes
+  /// once the responses in flight have been written. HTTP/2 peers are told to
+  /// stop opening streams with a GOAWAY frame.
+  fn graceful_shutdown(&self) {
+    if self.done.get() {
+      return;
+    }
+
+    if let Some(shutdown_tx) = self.shutdown_tx.borrow_mut().take() {
+      let _ = shutdown_tx.send(());
+    }
   }
 }
 
@@ -163,12 +237,12 @@ async fn op_http_request_next(
     let connection_closed = match conn_resource.poll(cx) {
       Poll::Pending => false,
       Poll::Ready(Ok(())) => {
-        // close ConnResource
+        // close ConnResource, unless it was already released by
+        // `op_http_upgrade_websocket`
         state
           .borrow_mut()
           .resource_table
-          .take::<ConnResource>(conn_rid)
-          .unwrap();
+          .take::<ConnResource>(conn_rid);
         true
       }
       Poll::Ready(Err(e)) => {
@@ -193,7 +267,13 @@ async fn op_http_request_next(
       conn_resource.deno_service.inner.borrow_mut().take()
     {
       let tx = request_resource.response_tx;
-      let req = request_resource.request;
+      let mut req = request_resource.request;
+      let upgrade = hyper::upgrade::on(&mut req);
+      let accept_encoding = req
+        .headers()
+        .get(hyper::header::ACCEPT_ENCODING)
+        .and_then(|value| value.to_str().ok())
+        .map_or(Encoding::Identity, preferred_encoding);
       let method = req.method().to_string();
 
       let mut headers = Vec::with_capacity(req.headers().len());
@@ -247,6 +327,8 @@ async fn op_http_request_next(
       let response_sender_rid =
         state.resource_table.add(ResponseSenderResource {
           sender: tx,
+          upgrade,
+          accept_encoding,
           conn_rid,
         });
 
@@ -298,14 +380,20 @@ fn op_http_start(
     let (read_half, write_half) = resource.into_inner();
     let tcp_stream = read_half.reunite(write_half)?;
     let addr = tcp_stream.local_addr()?;
-    let hyper_connection = Http::new()
-      .with_executor(LocalExecutor)
-      .serve_connection(tcp_stream, deno_service.clone());
+    let (shutdown_tx, shutdown_rx) = oneshot::channel();
+    let hyper_connection = serve_with_upgrades(
+      Http::new()
+        .with_executor(LocalExecutor)
+        .serve_connection(tcp_stream, deno_service.clone()),
+      shutdown_rx,
+    );
     let conn_resource = ConnResource {
       hyper_connection: ConnType::Tcp(Rc::new(RefCell::new(hyper_connection))),
       deno_service,
       addr,
       cancel: CancelHandle::default(),
+      shutdown_tx: RefCell::new(Some(shutdown_tx)),
+      done: Cell::new(false),
     };
     let rid = state.resource_table.add(conn_resource);
     return Ok(rid);
@@ -318,17 +406,35 @@ fn op_http_start(
     let resource = Rc::try_unwrap(resource_rc)
       .expect("Only a single use of this resource should happen");
     let (read_half, write_half) = resource.into_inner();
-    let tls_stream = read_half.reunite(write_half);
+    let mut tls_stream = read_half.reunite(write_half);
     let addr = tls_stream.get_ref().0.local_addr()?;
-
-    let hyper_connection = Http::new()
-      .with_executor(LocalExecutor)
-      .serve_connection(tls_stream, deno_service.clone());
+    let (shutdown_tx, shutdown_rx) = oneshot::channel();
+    let service = deno_service.clone();
+    // Accepted connections haven't done their handshake yet, it has to settle
+    // ALPN before the protocol is chosen. A failed handshake is left for
+    // hyper to run into when it reads from the stream.
+    let hyper_connection = async move {
+      let _ = tls_stream.handshake().await;
+      let alpn_h2 =
+        tls_stream.get_ref().1.get_alpn_protocol() == Some(&b"h2"[..]);
+      let mut http = Http::new();
+      http.http2_only(alpn_h2);
+      serve_with_upgrades(
+        http
+          .with_executor(LocalExecutor)
+          .serve_connection(tls_stream, service),
+        shutdown_rx,
+      )
+      .await
+    }
+    .boxed_local();
     let conn_resource = ConnResource {
       hyper_connection: ConnType::Tls(Rc::new(RefCell::new(hyper_connection))),
       deno_service,
       addr,
       cancel: CancelHandle::default(),
+      shutdown_tx: RefCell::new(Some(shutdown_tx)),
+      done: Cell::new(false),
     };
     let rid = state.resource_table.add(conn_resource);
     return Ok(rid);
@@ -348,12 +454,208 @@ struct RespondArgs(
   Vec<(String, String)>,
 );
 
+/// A content coding the server can compress responses with.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Encoding {
+  Identity,
+  Gzip,
+  Brotli,
+}
+
+/// Static bodies smaller than this are not worth compressing.
+const MIN_COMPRESSIBLE_BODY_SIZE: usize = 64;
+
+/// Static bodies larger than this are compressed on the blocking thread pool,
+/// so that they don't hold up the other tasks of the isolate.
+const MAX_INLINE_COMPRESSION_SIZE: usize = 64 * 1024;
+
+/// Responses are compressed while they are being served, so brotli, whose
+/// default is its densest and slowest quality, uses a fast one instead. The
+/// default level of gzip is fast enough.
+fn compression_level(encoding: Encoding) -> Level {
+  match encoding {
+    Encoding::Brotli => Level::Precise(4),
+    _ => Level::Default,
+  }
+}
+
+/// Picks the encoding preferred by an `Accept-Encoding` header, favouring
+/// brotli over gzip when the client weighs them equally.
+fn preferred_encoding(accept_encoding: &str) -> Encoding {
+  let mut brotli = None;
+  let mut gzip = None;
+  let mut wildcard = None;
+
+  for directive in accept_encoding.split(',') {
+    let mut parts = directive.split(';');
+    let coding = parts.next().unwrap_or("").trim().to_ascii_lowercase();
+    let quality = parts
+      .find_map(|param| param.trim().strip_prefix("q=")?.parse::<f32>().ok())
+      .unwrap_or(1.0);
+
+    match coding.as_str() {
+      "br" => brotli = Some(quality),
+      "gzip" | "x-gzip" => gzip = Some(quality),
+      "*" => wildcard = Some(quality),
+      _ => {}
+    }
+  }
+
+  let brotli = brotli.or(wildcard).unwrap_or(0.0);
+  let gzip = gzip.or(wildcard).unwrap_or(0.0);
+  if brotli > 0.0 && brotli >= gzip {
+    Encoding::Brotli
+  } else if gzip > 0.0 {
+    Encoding::Gzip
+  } else {
+    Encoding::Identity
+  }
+}
+
+/// Whether a response of the given content type benefits from compression.
+fn is_compressible(content_type: &str) -> bool {
+  let essence = content_type
+    .split(';')
+    .next()
+    .unwrap_or("")
+    .trim()
+    .to_ascii_lowercase();
+
+  essence.starts_with("text/")
+    || essence.ends_with("+json")
+    || essence.ends_with("+xml")
+    || matches!(
+      essence.as_str(),
+      "application/javascript"
+        | "application/json"
+        | "application/wasm"
+        | "application/xml"
+    )
+}
+
+/// Decides how a response is encoded and updates its headers to match.
+/// Responses that are already encoded, that opt out with
+/// `Cache-Control: no-transform` or that aren't of a compressible type are
+/// sent as they are.
+fn negotiate_encoding(
+  status: u16,
+  headers: &mut Vec<(String, String)>,
+  accept_encoding: Encoding,
+  body_len: Option<usize>,
+) -> Encoding {
+  let header = |name: &str| {
+    headers
+      .iter()
+      .find(|(key, _)| key.eq_ignore_ascii_case(name))
+      .map(|(_, value)| value.as_str())
+  };
+
+  if status < 200 || status == 204 || status == 206 || status == 304 {
+    return Encoding::Identity;
+  }
+
+  if header("content-encoding").is_some() || header("content-range").is_some() {
+    return Encoding::Identity;
+  }
+
+  let no_transform = header("cache-control").map_or(false, |value| {
+    value
+      .split(',')
+      .any(|directive| directive.trim().eq_ignore_ascii_case("no-transform"))
+  });
+  if no_transform || !header("content-type").map_or(false, is_compressible) {
+    return Encoding::Identity;
+  }
+
+  // From here on the response varies with the request's `Accept-Encoding`,
+  // whether or not it ends up being compressed.
+  match headers
+    .iter_mut()
+    .find(|(key, _)| key.eq_ignore_ascii_case("vary"))
+  {
+    Some((_, value)) => {
+      let varies = value.split(',').any(|field| {
+        let field = field.trim();
+        field == "*" || field.eq_ignore_ascii_case("accept-encoding")
+      });
+      if !varies {
+        value.push_str(", Accept-Encoding");
+      }
+    }
+    None => headers.push(("vary".to_string(), "Accept-Encoding".to_string())),
+  }
+
+  if accept_encoding == Encoding::Identity
+    || body_len.map_or(false, |len| len < MIN_COMPRESSIBLE_BODY_SIZE)
+  {
+    return Encoding::Identity;
+  }
+
+  // The length changes and the representation is no longer byte-for-byte the
+  // same, so a strong validator would be wrong.
+  headers.retain(|(key, _)| !key.eq_ignore_ascii_case("content-length"));
+  for (key, value) in headers.iter_mut() {
+    if key.eq_ignore_ascii_case("etag") && !value.starts_with("W/") {
+      *value = format!("W/{}", value);
+    }
+  }
+
+  let coding = match accept_encoding {
+    Encoding::Gzip => "gzip",
+    Encoding::Brotli => "br",
+    Encoding::Identity => unreachable!(),
+  };
+  headers.push(("content-encoding".to_string(), coding.to_string()));
+
+  accept_encoding
+}
+
+async fn compress_bytes(
+  encoding: Encoding,
+  data: &[u8],
+) -> Result<Vec<u8>, AnyError> {
+  let level = compression_level(encoding);
+  let mut compressed = Vec::new();
+  match encoding {
+    Encoding::Identity => compressed.extend_from_slice(data),
+    Encoding::Gzip => {
+      bufread::GzipEncoder::with_quality(data, level)
+        .read_to_end(&mut compressed)
+        .await?;
+    }
+    Encoding::Brotli => {
+      bufread::BrotliEncoder::with_quality(data, level)
+        .read_to_end(&mut compressed)
+        .await?;
+    }
+  }
+
+  Ok(compressed)
+}
+
+async fn compress_body(
+  encoding: Encoding,
+  data: &[u8],
+) -> Result<Vec<u8>, AnyError> {
+  if encoding == Encoding::Identity || data.len() <= MAX_INLINE_COMPRESSION_SIZE
+  {
+    return compress_bytes(encoding, data).await;
+  }
+
+  // Reading from a slice never waits, so the encoder runs to completion on
+  // the blocking thread.
+  let data = data.to_vec();
+  tokio::task::spawn_blocking(move || block_on(compress_bytes(encoding, &data)))
+    .await
+    .unwrap()
+}
+
 async fn op_http_response(
   state: Rc<RefCell<OpState>>,
   args: RespondArgs,
   data: Option<ZeroCopyBuf>,
 ) -> Result<Option<ResourceId>, AnyError> {
-  let RespondArgs(rid, status, headers) = args;
+  let RespondArgs(rid, status, mut headers) = args;
 
   let response_sender = state
     .borrow_mut()
@@ -370,6 +672,13 @@ async fn op_http_response(
     .get::<ConnResource>(response_sender.conn_rid)
     .ok_or_else(bad_resource_id)?;
 
+  let encoding = negotiate_encoding(
+    status,
+    &mut headers,
+    response_sender.accept_encoding,
+    data.as_ref().map(|d| d.len()),
+  );
+
   let mut builder = Response::builder().status(status);
 
   builder.headers_mut().unwrap().reserve(headers.len());
@@ -380,16 +689,38 @@ async fn op_http_response(
   let res;
   let maybe_response_body_rid = if let Some(d) = data {
     // If a body is passed, we use it, and don't return a body for streaming.
-    res = builder.body(Vec::from(&*d).into())?;
+    res = builder.body(compress_body(encoding, &*d).await?.into())?;
     None
   } else {
     // If no body is passed, we return a writer for streaming the body.
-    let (sender, body) = Body::channel();
-    res = builder.body(body)?;
+    let body = match encoding {
+      Encoding::Identity => {
+        let (sender, body) = Body::channel();
+        res = builder.body(body)?;
+        ResponseBody::Uncompressed(sender)
+      }
+      Encoding::Gzip | Encoding::Brotli => {
+        // The encoder writes into one end of a pipe, and hyper streams the
+        // compressed bytes from the other one.
+        let (writer, reader) = tokio::io::duplex(64 * 1024);
+        res = builder.body(Body::wrap_stream(ReaderStream::new(reader)))?;
+        let encoder: Box<dyn AsyncWrite + Unpin> = match encoding {
+          Encoding::Gzip => Box::new(write::GzipEncoder::with_quality(
+            writer,
+            compression_level(encoding),
+          )),
+          _ => Box::new(write::BrotliEncoder::with_quality(
+            writer,
+            compression_level(encoding),
+          )),
+        };
+        ResponseBody::Compressed(encoder)
+      }
+    };
 
     let response_body_rid =
       state.borrow_mut().resource_table.add(ResponseBodyResource {
-        body: AsyncRefCell::new(sender),
+        body: AsyncRefCell::new(body),
         conn_rid: response_sender.conn_rid,
       });
 
@@ -428,6 +759,21 @@ async fn op_http_response_close(
     .resource_table
     .get::<ConnResource>(resource.conn_rid)
     .ok_or_else(bad_resource_id)?;
+
+  // A compressed body only ends once the encoder has written its trailer.
+  let mut body = RcRef::map(&resource, |r| &r.body).borrow_mut().await;
+  if let ResponseBody::Compressed(encoder) = &mut *body {
+    let mut shutdown_fut = encoder.shutdown().boxed_local();
+    poll_fn(|cx| {
+      if let Poll::Ready(Err(e)) = conn_resource.poll(cx) {
+        return Poll::Ready(Err(e));
+      }
+
+      shutdown_fut.poll_unpin(cx).map_err(AnyError::from)
+    })
+    .await?;
+  }
+  drop(body);
   drop(resource);
 
   poll_fn(|cx| match conn_resource.poll(cx) {
@@ -493,7 +839,22 @@ async fn op_http_response_write(
 
   let mut body = RcRef::map(&resource, |r| &r.body).borrow_mut().await;
 
-  let mut send_data_fut = body.send_data(Vec::from(&*buf).into()).boxed_local();
+  let mut send_data_fut: LocalBoxFuture<Result<(), AnyError>> = match &mut *body
+  {
+    ResponseBody::Uncompressed(sender) => async move {
+      sender.send_data(Vec::from(&*buf).into()).await?;
+      Ok(())
+    }
+    .boxed_local(),
+    // Flushing after every chunk keeps streamed responses flowing instead of
+    // holding data back until the encoder's buffer fills up.
+    ResponseBody::Compressed(encoder) => async move {
+      encoder.write_all(&buf).await?;
+      encoder.flush().await?;
+      Ok(())
+    }
+    .boxed_local(),
+  };
 
   poll_fn(|cx| {
     if let Poll::Ready(Err(e)) = conn_resource.poll(cx) {
@@ -503,13 +864,107 @@ async fn op_http_response_write(
       return Poll::Ready(Err(e));
     }
 
-    send_data_fut.poll_unpin(cx).map_err(AnyError::from)
+    send_data_fut.poll_unpin(cx)
   })
   .await?;
 
   Ok(())
 }
 
+async fn op_http_shutdown(
+  state: Rc<RefCell<OpState>>,
+  rid: ResourceId,
+  _: (),
+) -> Result<(), AnyError> {
+  let conn_resource = state
+    .borrow()
+    .resource_table
+    .get::<ConnResource>(rid)
+    .ok_or_else(bad_resource_id)?;
+
+  conn_resource.graceful_shutdown();
+
+  // Drive the connection until it has drained, unless it gets closed first.
+  let cancel = RcRef::map(conn_resource.clone(), |r| &r.cancel);
+  let result = poll_fn(|cx| conn_resource.poll(cx))
+    .try_or_cancel(cancel)
+    .await;
+
+  // The connection is done either way, so end any pending
+  // `op_http_request_next` and release it.
+  conn_resource.cancel.cancel();
+  state.borrow_mut().resource_table.take::<ConnResource>(rid);
+
+  match result {
+    Err(e) if should_ignore_error(&e) => Ok(()),
+    result => result,
+  }
+}
+
+fn op_http_websocket_accept_header(
+  _state: &mut OpState,
+  key: String,
+  _: (),
+) -> Result<String, AnyError> {
+  Ok(derive_accept_key(key.as_bytes()))
+}
+
+async fn op_http_upgrade_websocket(
+  state: Rc<RefCell<OpState>>,
+  args: RespondArgs,
+  _: (),
+) -> Result<ResourceId, AnyError> {
+  let RespondArgs(rid, status, headers) = args;
+
+  let response_sender = state
+    .borrow_mut()
+    .resource_table
+    .take::<ResponseSenderResource>(rid)
+    .ok_or_else(bad_resource_id)?;
+  let response_sender = Rc::try_unwrap(response_sender)
+    .ok()
+    .expect("multiple op_http_respond ongoing");
+
+  let conn_rid = response_sender.conn_rid;
+  let conn_resource = state
+    .borrow()
+    .resource_table
+    .get::<ConnResource>(conn_rid)
+    .ok_or_else(bad_resource_id)?;
+
+  let mut builder = Response::builder().status(status);
+  for (key, value) in &headers {
+    builder = builder.header(key, value);
+  }
+  let res = builder.body(Body::empty())?;
+
+  if response_sender.sender.send(res).is_err() {
+    return Err(type_error("internal communication error"));
+  }
+
+  // Hyper only hands the IO over once the connection has written the
+  // response, so it has to keep being polled until then.
+  let mut upgrade = response_sender.upgrade;
+  let transport = poll_fn(|cx| {
+    if let Poll::Ready(Err(e)) = conn_resource.poll(cx) {
+      return Poll::Ready(Err(e));
+    }
+
+    upgrade.poll_unpin(cx).map_err(AnyError::from)
+  })
+  .await?;
+
+  // No more requests will be read from the upgraded connection, so end any
+  // pending `op_http_request_next` and release the connection.
+  conn_resource.cancel.cancel();
+  state
+    .borrow_mut()
+    .resource_table
+    .take::<ConnResource>(conn_rid);
+
+  ws_create_server_stream(&state, transport).await
+}
+
 type BytesStream =
   Pin<Box<dyn Stream<Item = std::io::Result<bytes::Bytes>> + Unpin>>;
 
@@ -531,6 +986,8 @@ impl Resource for RequestBodyResource {
 
 struct ResponseSenderResource {
   sender: oneshot::Sender<Response<Body>>,
+  upgrade: OnUpgrade,
+  accept_encoding: Encoding,
   conn_rid: ResourceId,
 }
 
@@ -540,8 +997,13 @@ impl Resource for ResponseSenderResource {
   }
 }
 
+enum ResponseBody {
+  Uncompressed(hyper::body::Sender),
+  Compressed(Box<dyn AsyncWrite + Unpin>),
+}
+
 struct ResponseBodyResource {
-  body: AsyncRefCell<hyper::body::Sender>,
+  body: AsyncRefCell<ResponseBody>,
   conn_rid: ResourceId,
 }
 
@@ -564,3 +1026,86 @@ where
     tokio::task::spawn_local(fut);
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_preferred_encoding() {
+    assert_eq!(preferred_encoding("gzip, deflate, br"), Encoding::Brotli);
+    assert_eq!(preferred_encoding("gzip"), Encoding::Gzip);
+    assert_eq!(preferred_encoding("br;q=0.5, gzip"), Encoding::Gzip);
+    assert_eq!(preferred_encoding("br;q=0, gzip;q=0"), Encoding::Identity);
+    assert_eq!(preferred_encoding("*"), Encoding::Brotli);
+    assert_eq!(preferred_encoding("br;q=0, *"), Encoding::Gzip);
+    assert_eq!(preferred_encoding("identity"), Encoding::Identity);
+    assert_eq!(preferred_encoding(""), Encoding::Identity);
+  }
+
+  #[test]
+  fn test_is_compressible() {
+    assert!(is_compressible("text/html; charset=utf-8"));
+    assert!(is_compressible("application/json"));
+    assert!(is_compressible("application/ld+json"));
+    assert!(is_compressible("image/svg+xml"));
+    assert!(!is_compressible("image/png"));
+    assert!(!is_compressible("application/octet-stream"));
+  }
+
+  #[test]
+  fn test_negotiate_encoding() {
+    let mut headers = vec![
+      ("content-type".to_string(), "text/plain".to_string()),
+      ("content-length".to_string(), "100".to_string()),
+      ("etag".to_string(), "\"abc\"".to_string()),
+    ];
+    let encoding =
+      negotiate_encoding(200, &mut headers, Encoding::Gzip, Some(100));
+    assert_eq!(encoding, Encoding::Gzip);
+    assert_eq!(
+      headers,
+      vec![
+        ("content-type".to_string(), "text/plain".to_string()),
+        ("etag".to_string(), "W/\"abc\"".to_string()),
+        ("vary".to_string(), "Accept-Encoding".to_string()),
+        ("content-encoding".to_string(), "gzip".to_string()),
+      ]
+    );
+
+    // Small bodies are left alone, but still vary with the request.
+    let mut headers = vec![
+      ("content-type".to_string(), "text/plain".to_string()),
+      ("vary".to_string(), "Origin".to_string()),
+    ];
+    let encoding =
+      negotiate_encoding(200, &mut headers, Encoding::Brotli, Some(5));
+    assert_eq!(encoding, Encoding::Identity);
+    assert_eq!(headers[1].1, "Origin, Accept-Encoding");
+
+    let mut headers = vec![
+      ("content-type".to_string(), "text/plain".to_string()),
+      (
+        "cache-control".to_string(),
+        "public, no-transform".to_string(),
+      ),
+    ];
+    let encoding =
+      negotiate_encoding(200, &mut headers, Encoding::Brotli, None);
+    assert_eq!(encoding, Encoding::Identity);
+    assert_eq!(headers.len(), 2);
+
+    let mut headers =
+      vec![("content-type".to_string(), "image/png".to_string())];
+    let encoding =
+      negotiate_encoding(200, &mut headers, Encoding::Brotli, None);
+    assert_eq!(encoding, Encoding::Identity);
+    assert_eq!(headers.len(), 1);
+
+    let mut headers =
+      vec![("content-type".to_string(), "text/plain".to_string())];
+    let encoding =
+      negotiate_encoding(304, &mut headers, Encoding::Brotli, None);
+    assert_eq!(encoding, Encoding::Identity);
+  }
+}
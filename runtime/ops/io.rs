@@ -1,6 +1,7 @@
 // Copyright 2018-2021 the Deno authors. All rights reserved. MIT license.
 
 use crate::ops::tls;
+use crate::ops::tty::PtyResource;
 use deno_core::error::null_opbuf;
 use deno_core::error::resource_unavailable;
 use deno_core::error::AnyError;
@@ -548,6 +549,8 @@ async fn op_read_async(
     s.read(buf).await?
   } else if let Some(s) = resource.downcast_rc::<StdFileResource>() {
     s.read(buf).await?
+  } else if let Some(s) = resource.downcast_rc::<PtyResource>() {
+    s.read(buf).await?
   } else {
     return Err(not_supported());
   };
@@ -590,6 +593,8 @@ async fn op_write_async(
     s.write(buf).await?
   } else if let Some(s) = resource.downcast_rc::<StdFileResource>() {
     s.write(buf).await?
+  } else if let Some(s) = resource.downcast_rc::<PtyResource>() {
+    s.write(buf).await?
   } else {
     return Err(not_supported());
   };
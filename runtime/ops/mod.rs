@@ -13,6 +13,7 @@ pub mod plugin;
 pub mod process;
 pub mod runtime;
 pub mod signal;
+pub mod spawn;
 pub mod tls;
 pub mod tty;
 mod utils;
@@ -1,5 +1,6 @@
 // Copyright 2018-2021 the Deno authors. All rights reserved. MIT license.
 
+use super::process::ChildProcesses;
 use super::utils::into_string;
 use crate::permissions::Permissions;
 use deno_core::error::{type_error, AnyError};
@@ -108,7 +109,9 @@ fn op_delete_env(
   Ok(())
 }
 
-fn op_exit(_state: &mut OpState, code: i32, _: ()) -> Result<(), AnyError> {
+fn op_exit(state: &mut OpState, code: i32, _: ()) -> Result<(), AnyError> {
+  // Exiting skips the teardown of the worker, which would kill its children.
+  state.borrow::<ChildProcesses>().kill_all();
   std::process::exit(code)
 }
 
@@ -20,7 +20,9 @@ use deno_core::ResourceId;
 use serde::Deserialize;
 use serde::Serialize;
 use std::borrow::Cow;
+use std::cell::Cell;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::rc::Rc;
 use tokio::process::Command;
 
@@ -34,9 +36,87 @@ pub fn init() -> Extension {
       ("op_run_status", op_async(op_run_status)),
       ("op_kill", op_sync(op_kill)),
     ])
+    .state(|state| {
+      state.put(ChildProcesses::default());
+      Ok(())
+    })
     .build()
 }
 
+pub const SIGKILL: i32 = 9;
+
+/// The children spawned by a worker that haven't been waited for yet, keyed
+/// by pid. The value records whether the child leads its own process group.
+#[derive(Clone, Default)]
+pub struct ChildProcesses(Rc<RefCell<HashMap<u32, bool>>>);
+
+impl ChildProcesses {
+  pub fn insert(&self, pid: u32, process_group: bool) {
+    self.0.borrow_mut().insert(pid, process_group);
+  }
+
+  pub fn remove(&self, pid: u32) {
+    self.0.borrow_mut().remove(&pid);
+  }
+
+  /// Kills every child that may still be running. Children that lead a
+  /// process group take the rest of their group down with them.
+  pub fn kill_all(&self) {
+    for (pid, process_group) in self.0.borrow_mut().drain() {
+      // Errors are expected for children that have already exited.
+      let _ = kill_child(pid, process_group, SIGKILL);
+    }
+  }
+}
+
+/// Signals a child, or its whole process group if it leads one.
+pub fn kill_child(
+  pid: u32,
+  process_group: bool,
+  signo: i32,
+) -> Result<(), AnyError> {
+  if process_group {
+    kill_process_group(pid, signo)
+  } else {
+    kill(pid as i32, signo)
+  }
+}
+
+#[cfg(unix)]
+fn kill_process_group(pgid: u32, signo: i32) -> Result<(), AnyError> {
+  kill(-(pgid as i32), signo)
+}
+
+#[cfg(not(unix))]
+fn kill_process_group(pgid: u32, signo: i32) -> Result<(), AnyError> {
+  // There is no way to signal a whole process group, settle for its leader.
+  kill(pgid as i32, signo)
+}
+
+/// Makes the child the leader of a new process group, so that it and all of
+/// its descendants can be signaled at once.
+#[cfg(unix)]
+pub fn set_process_group(command: &mut std::process::Command) {
+  use std::os::unix::process::CommandExt;
+  // SAFETY: setpgid() is async-signal-safe, so it may be called between fork
+  // and exec.
+  unsafe {
+    command.pre_exec(|| {
+      if libc::setpgid(0, 0) == -1 {
+        return Err(std::io::Error::last_os_error());
+      }
+      Ok(())
+    });
+  }
+}
+
+#[cfg(not(unix))]
+pub fn set_process_group(command: &mut std::process::Command) {
+  use std::os::windows::process::CommandExt;
+  use winapi::um::winbase::CREATE_NEW_PROCESS_GROUP;
+  command.creation_flags(CREATE_NEW_PROCESS_GROUP);
+}
+
 fn clone_file(
   state: &mut OpState,
   rid: ResourceId,
@@ -68,10 +148,30 @@ pub struct RunArgs {
   stdin_rid: ResourceId,
   stdout_rid: ResourceId,
   stderr_rid: ResourceId,
+  #[serde(default)]
+  process_group: bool,
 }
 
 struct ChildResource {
   child: AsyncRefCell<tokio::process::Child>,
+  // Cleared once the child has been waited for, so that a recycled pid is
+  // never signaled. The same goes for the process group it leads: once all
+  // of its members have exited too, its id may be taken by another group.
+  pid: Cell<Option<u32>>,
+  process_group: bool,
+  children: ChildProcesses,
+}
+
+impl Drop for ChildResource {
+  fn drop(&mut self) {
+    if let Some(pid) = self.pid.get() {
+      self.children.remove(pid);
+      // The child itself is killed on drop, but the rest of its group isn't.
+      if self.process_group {
+        let _ = kill_child(pid, true, SIGKILL);
+      }
+    }
+  }
 }
 
 impl Resource for ChildResource {
@@ -107,7 +207,7 @@ fn op_run(
   let env = run_args.env;
   let cwd = run_args.cwd;
 
-  let mut c = Command::new(args.get(0).unwrap());
+  let mut c = std::process::Command::new(args.get(0).unwrap());
   (1..args.len()).for_each(|i| {
     let arg = args.get(i).unwrap();
     c.arg(arg);
@@ -139,7 +239,13 @@ fn op_run(
     c.stderr(file);
   }
 
+  if run_args.process_group {
+    super::check_unstable(state, "Deno.run#process_group");
+    set_process_group(&mut c);
+  }
+
   // We want to kill child when it's closed
+  let mut c = Command::from(c);
   c.kill_on_drop(true);
 
   // Spawn the command.
@@ -176,8 +282,15 @@ fn op_run(
     None => None,
   };
 
+  let children = state.borrow::<ChildProcesses>().clone();
+  if let Some(pid) = pid {
+    children.insert(pid, run_args.process_group);
+  }
   let child_resource = ChildResource {
     child: AsyncRefCell::new(child),
+    pid: Cell::new(pid),
+    process_group: run_args.process_group,
+    children,
   };
   let child_rid = state.resource_table.add(child_resource);
 
@@ -208,8 +321,11 @@ async fn op_run_status(
     .resource_table
     .get::<ChildResource>(rid)
     .ok_or_else(bad_resource_id)?;
-  let mut child = resource.borrow_mut().await;
+  let mut child = resource.clone().borrow_mut().await;
   let run_status = child.wait().await?;
+  if let Some(pid) = resource.pid.take() {
+    resource.children.remove(pid);
+  }
   let code = run_status.code();
 
   #[cfg(unix)]
@@ -0,0 +1,372 @@
+// Copyright 2018-2021 the Deno authors. All rights reserved. MIT license.
+
+use super::io::ChildStderrResource;
+use super::io::ChildStdinResource;
+use super::io::ChildStdoutResource;
+use super::process::kill_child;
+use super::process::set_process_group;
+use super::process::ChildProcesses;
+use super::process::SIGKILL;
+use super::tty::attach_pty;
+use super::tty::PtySize;
+use crate::permissions::Permissions;
+use deno_core::error::bad_resource_id;
+use deno_core::error::type_error;
+use deno_core::error::AnyError;
+use deno_core::op_async;
+use deno_core::op_sync;
+use deno_core::AsyncRefCell;
+use deno_core::CancelFuture;
+use deno_core::CancelHandle;
+use deno_core::Extension;
+use deno_core::OpState;
+use deno_core::RcRef;
+use deno_core::Resource;
+use deno_core::ResourceId;
+use deno_core::ZeroCopyBuf;
+use serde::Deserialize;
+use serde::Serialize;
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::process::ExitStatus;
+use std::rc::Rc;
+
+#[cfg(unix)]
+use std::os::unix::process::CommandExt;
+#[cfg(unix)]
+use std::os::unix::process::ExitStatusExt;
+
+pub fn init() -> Extension {
+  Extension::builder()
+    .ops(vec![
+      ("op_spawn_child", op_sync(op_spawn_child)),
+      ("op_spawn_wait", op_async(op_spawn_wait)),
+      ("op_spawn_kill", op_sync(op_spawn_kill)),
+      ("op_spawn_sync", op_sync(op_spawn_sync)),
+    ])
+    .build()
+}
+
+/// A spawned child process. Closing the resource kills the child if it is
+/// still running, along with the rest of its process group if it leads one.
+struct ChildResource {
+  child: AsyncRefCell<tokio::process::Child>,
+  // Cleared once the child has been waited for, so that a recycled pid is
+  // never signaled. The same goes for the process group it leads: once all
+  // of its members have exited too, its id may be taken by another group.
+  pid: Cell<Option<u32>>,
+  process_group: bool,
+  children: ChildProcesses,
+  cancel: CancelHandle,
+}
+
+impl Drop for ChildResource {
+  fn drop(&mut self) {
+    if let Some(pid) = self.pid.get() {
+      self.children.remove(pid);
+      // The child itself is killed on drop, but the rest of its group isn't.
+      if self.process_group {
+        let _ = kill_child(pid, true, SIGKILL);
+      }
+    }
+  }
+}
+
+impl Resource for ChildResource {
+  fn name(&self) -> Cow<str> {
+    "child".into()
+  }
+
+  fn close(self: Rc<Self>) {
+    // A pending `op_spawn_wait` keeps the resource alive; once it is canceled
+    // the child is dropped, which kills it.
+    self.cancel.cancel();
+  }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpawnArgs {
+  cmd: String,
+  args: Vec<String>,
+  cwd: Option<String>,
+  clear_env: bool,
+  env: Vec<(String, String)>,
+  uid: Option<u32>,
+  gid: Option<u32>,
+  process_group: bool,
+  pty: Option<PtySize>,
+  stdio: ChildStdio,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChildStdio {
+  stdin: Stdio,
+  stdout: Stdio,
+  stderr: Stdio,
+}
+
+#[derive(Copy, Clone, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum Stdio {
+  Inherit,
+  Piped,
+  Null,
+}
+
+impl Stdio {
+  fn as_stdio(&self) -> std::process::Stdio {
+    match self {
+      Stdio::Inherit => std::process::Stdio::inherit(),
+      Stdio::Piped => std::process::Stdio::piped(),
+      Stdio::Null => std::process::Stdio::null(),
+    }
+  }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChildStatus {
+  success: bool,
+  code: i32,
+  signal: Option<i32>,
+}
+
+impl From<ExitStatus> for ChildStatus {
+  fn from(status: ExitStatus) -> Self {
+    let code = status.code();
+    #[cfg(unix)]
+    let signal = status.signal();
+    #[cfg(not(unix))]
+    let signal: Option<i32> = None;
+
+    if let Some(signal) = signal {
+      ChildStatus {
+        success: false,
+        code: 128 + signal,
+        signal: Some(signal),
+      }
+    } else {
+      let code = code.expect("Should have either an exit code or a signal.");
+      ChildStatus {
+        success: code == 0,
+        code,
+        signal: None,
+      }
+    }
+  }
+}
+
+/// Builds the command described by `args`, after checking that it may be run.
+pub fn create_command(
+  state: &mut OpState,
+  args: &SpawnArgs,
+) -> Result<std::process::Command, AnyError> {
+  state.borrow_mut::<Permissions>().run.check(&args.cmd)?;
+
+  let mut command = std::process::Command::new(&args.cmd);
+  command.args(&args.args);
+
+  if let Some(cwd) = &args.cwd {
+    command.current_dir(cwd);
+  }
+
+  if args.clear_env {
+    command.env_clear();
+  }
+  for (key, value) in &args.env {
+    command.env(key, value);
+  }
+
+  set_ids(&mut command, args.uid, args.gid)?;
+  // A child attached to a pty leads a new session, and with it a process
+  // group of its own.
+  if args.process_group && args.pty.is_none() {
+    set_process_group(&mut command);
+  }
+
+  command.stdin(args.stdio.stdin.as_stdio());
+  command.stdout(args.stdio.stdout.as_stdio());
+  command.stderr(args.stdio.stderr.as_stdio());
+
+  Ok(command)
+}
+
+#[cfg(unix)]
+fn set_ids(
+  command: &mut std::process::Command,
+  uid: Option<u32>,
+  gid: Option<u32>,
+) -> Result<(), AnyError> {
+  if let Some(uid) = uid {
+    command.uid(uid);
+  }
+  if let Some(gid) = gid {
+    command.gid(gid);
+  }
+  Ok(())
+}
+
+#[cfg(not(unix))]
+fn set_ids(
+  _command: &mut std::process::Command,
+  uid: Option<u32>,
+  gid: Option<u32>,
+) -> Result<(), AnyError> {
+  if uid.is_some() || gid.is_some() {
+    return Err(type_error("uid and gid are only supported on Unix"));
+  }
+  Ok(())
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Child {
+  rid: ResourceId,
+  pid: Option<u32>,
+  stdin_rid: Option<ResourceId>,
+  stdout_rid: Option<ResourceId>,
+  stderr_rid: Option<ResourceId>,
+  pty_rid: Option<ResourceId>,
+}
+
+fn op_spawn_child(
+  state: &mut OpState,
+  args: SpawnArgs,
+  _: (),
+) -> Result<Child, AnyError> {
+  super::check_unstable(state, "Deno.spawnChild");
+  let mut command = create_command(state, &args)?;
+  let pty = match &args.pty {
+    Some(size) => Some(attach_pty(&mut command, size)?),
+    None => None,
+  };
+  let process_group = args.process_group || pty.is_some();
+
+  let mut command = tokio::process::Command::from(command);
+  // The child must not outlive its resource.
+  command.kill_on_drop(true);
+
+  let mut child = command.spawn()?;
+  // Drop our copies of the pty's slave side, so that reading from the master
+  // ends once the child is gone.
+  drop(command);
+  let pid = child.id();
+
+  let stdin_rid = child
+    .stdin
+    .take()
+    .map(|stdin| state.resource_table.add(ChildStdinResource::from(stdin)));
+  let stdout_rid = child
+    .stdout
+    .take()
+    .map(|stdout| state.resource_table.add(ChildStdoutResource::from(stdout)));
+  let stderr_rid = child
+    .stderr
+    .take()
+    .map(|stderr| state.resource_table.add(ChildStderrResource::from(stderr)));
+  let pty_rid = pty.map(|pty| state.resource_table.add(pty));
+
+  let children = state.borrow::<ChildProcesses>().clone();
+  if let Some(pid) = pid {
+    children.insert(pid, process_group);
+  }
+  let rid = state.resource_table.add(ChildResource {
+    child: AsyncRefCell::new(child),
+    pid: Cell::new(pid),
+    process_group,
+    children,
+    cancel: CancelHandle::default(),
+  });
+
+  Ok(Child {
+    rid,
+    pid,
+    stdin_rid,
+    stdout_rid,
+    stderr_rid,
+    pty_rid,
+  })
+}
+
+async fn op_spawn_wait(
+  state: Rc<RefCell<OpState>>,
+  rid: ResourceId,
+  _: (),
+) -> Result<ChildStatus, AnyError> {
+  let resource = state
+    .borrow()
+    .resource_table
+    .get::<ChildResource>(rid)
+    .ok_or_else(bad_resource_id)?;
+  let cancel = RcRef::map(&resource, |r| &r.cancel);
+  let mut child = RcRef::map(&resource, |r| &r.child).borrow_mut().await;
+  let status = child.wait().or_cancel(cancel).await??;
+  if let Some(pid) = resource.pid.take() {
+    resource.children.remove(pid);
+  }
+  Ok(status.into())
+}
+
+#[derive(Deserialize)]
+struct SpawnKillArgs {
+  rid: ResourceId,
+  signo: i32,
+  group: bool,
+}
+
+fn op_spawn_kill(
+  state: &mut OpState,
+  args: SpawnKillArgs,
+  _: (),
+) -> Result<(), AnyError> {
+  let resource = state
+    .resource_table
+    .get::<ChildResource>(args.rid)
+    .ok_or_else(bad_resource_id)?;
+  if args.group && !resource.process_group {
+    return Err(type_error("Child was not started in its own process group"));
+  }
+  let pid = resource
+    .pid
+    .get()
+    .ok_or_else(|| type_error("Child process has already exited"))?;
+  kill_child(pid, args.group, args.signo)
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SpawnOutput {
+  status: ChildStatus,
+  stdout: Option<ZeroCopyBuf>,
+  stderr: Option<ZeroCopyBuf>,
+}
+
+fn op_spawn_sync(
+  state: &mut OpState,
+  args: SpawnArgs,
+  _: (),
+) -> Result<SpawnOutput, AnyError> {
+  super::check_unstable(state, "Deno.spawnSync");
+  if args.stdio.stdin == Stdio::Piped {
+    return Err(type_error("Piped stdin is not supported by Deno.spawnSync"));
+  }
+  if args.pty.is_some() {
+    return Err(type_error("Deno.spawnSync can't attach a pty"));
+  }
+  let std::process::Output {
+    status,
+    stdout,
+    stderr,
+  } = create_command(state, &args)?.output()?;
+  let stdout_piped = args.stdio.stdout == Stdio::Piped;
+  let stderr_piped = args.stdio.stderr == Stdio::Piped;
+
+  Ok(SpawnOutput {
+    status: status.into(),
+    stdout: stdout_piped.then(|| stdout.into()),
+    stderr: stderr_piped.then(|| stderr.into()),
+  })
+}
@@ -6,19 +6,26 @@ use deno_core::error::not_supported;
 use deno_core::error::resource_unavailable;
 use deno_core::error::AnyError;
 use deno_core::op_sync;
+use deno_core::CancelHandle;
+use deno_core::CancelTryFuture;
 use deno_core::Extension;
 use deno_core::OpState;
 use deno_core::RcRef;
+use deno_core::Resource;
 use deno_core::ResourceId;
 use serde::Deserialize;
 use serde::Serialize;
+use std::borrow::Cow;
 use std::io::Error;
+use std::rc::Rc;
 
 #[cfg(unix)]
 use nix::sys::termios;
 
 #[cfg(windows)]
 use deno_core::error::custom_error;
+#[cfg(not(unix))]
+use deno_core::error::type_error;
 #[cfg(windows)]
 use winapi::shared::minwindef::DWORD;
 #[cfg(windows)]
@@ -50,6 +57,7 @@ pub fn init() -> Extension {
       ("op_set_raw", op_sync(op_set_raw)),
       ("op_isatty", op_sync(op_isatty)),
       ("op_console_size", op_sync(op_console_size)),
+      ("op_pty_resize", op_sync(op_pty_resize)),
     ])
     .build()
 }
@@ -308,3 +316,195 @@ fn op_console_size(
 
   Ok(size)
 }
+
+#[derive(Deserialize)]
+pub struct PtySize {
+  columns: u16,
+  rows: u16,
+}
+
+#[cfg(unix)]
+impl From<&PtySize> for libc::winsize {
+  fn from(size: &PtySize) -> Self {
+    libc::winsize {
+      ws_row: size.rows,
+      ws_col: size.columns,
+      ws_xpixel: 0,
+      ws_ypixel: 0,
+    }
+  }
+}
+
+/// The master side of a pseudo-terminal that a child process is attached to.
+/// The descriptor is non-blocking and polled by the reactor, so reads and
+/// writes wait for it independently of each other.
+pub struct PtyResource {
+  #[cfg(unix)]
+  master: tokio::io::unix::AsyncFd<std::fs::File>,
+  cancel: CancelHandle,
+}
+
+impl Resource for PtyResource {
+  fn name(&self) -> Cow<str> {
+    "pty".into()
+  }
+
+  fn close(self: Rc<Self>) {
+    self.cancel.cancel();
+  }
+}
+
+#[cfg(unix)]
+impl PtyResource {
+  /// `master` must be in non-blocking mode.
+  fn new(master: std::fs::File) -> Result<Self, AnyError> {
+    Ok(Self {
+      master: tokio::io::unix::AsyncFd::new(master)?,
+      cancel: Default::default(),
+    })
+  }
+
+  pub async fn read(
+    self: &Rc<Self>,
+    buf: &mut [u8],
+  ) -> Result<usize, AnyError> {
+    use std::io::Read;
+
+    let cancel = RcRef::map(self, |r| &r.cancel);
+    let read = async {
+      loop {
+        let mut guard = self.master.readable().await?;
+        if let Ok(result) = guard.try_io(|master| master.get_ref().read(buf)) {
+          return result;
+        }
+      }
+    };
+    match read.try_or_cancel(cancel).await {
+      // Once the slave side has been closed by every process, reading from
+      // the master fails with EIO instead of reporting EOF.
+      Err(err) if err.raw_os_error() == Some(libc::EIO) => Ok(0),
+      result => Ok(result?),
+    }
+  }
+
+  pub async fn write(self: &Rc<Self>, buf: &[u8]) -> Result<usize, AnyError> {
+    use std::io::Write;
+
+    let cancel = RcRef::map(self, |r| &r.cancel);
+    let write = async {
+      loop {
+        let mut guard = self.master.writable().await?;
+        if let Ok(result) = guard.try_io(|master| master.get_ref().write(buf)) {
+          return result;
+        }
+      }
+    };
+    Ok(write.try_or_cancel(cancel).await?)
+  }
+
+  /// Resizing the pty delivers SIGWINCH to its foreground process group.
+  fn resize(&self, size: &PtySize) -> Result<(), AnyError> {
+    use std::os::unix::io::AsRawFd;
+
+    let winsize = libc::winsize::from(size);
+    let fd = self.master.as_raw_fd();
+    if unsafe { libc::ioctl(fd, libc::TIOCSWINSZ, &winsize as *const _) } != 0 {
+      return Err(Error::last_os_error().into());
+    }
+    Ok(())
+  }
+}
+
+// Pseudo-terminals can't be opened on other platforms, see `attach_pty()`.
+#[cfg(not(unix))]
+impl PtyResource {
+  pub async fn read(
+    self: &Rc<Self>,
+    _buf: &mut [u8],
+  ) -> Result<usize, AnyError> {
+    Err(not_supported())
+  }
+
+  pub async fn write(self: &Rc<Self>, _buf: &[u8]) -> Result<usize, AnyError> {
+    Err(not_supported())
+  }
+}
+
+/// Opens a new pty of the given size and sets up `command` to run attached
+/// to it: the child leads a new session, with the pty as its controlling
+/// terminal and as its stdin, stdout and stderr.
+#[cfg(unix)]
+pub fn attach_pty(
+  command: &mut std::process::Command,
+  size: &PtySize,
+) -> Result<PtyResource, AnyError> {
+  use nix::fcntl::fcntl;
+  use nix::fcntl::FcntlArg;
+  use nix::fcntl::FdFlag;
+  use nix::fcntl::OFlag;
+  use nix::pty::openpty;
+  use std::os::unix::io::FromRawFd;
+  use std::os::unix::process::CommandExt;
+
+  let pty = openpty(Some(&libc::winsize::from(size)), None)?;
+  // SAFETY: openpty() hands over ownership of both descriptors.
+  let master = unsafe { std::fs::File::from_raw_fd(pty.master) };
+  let slave = unsafe { std::fs::File::from_raw_fd(pty.slave) };
+  // Neither side may leak into children other than through their stdio.
+  for fd in &[pty.master, pty.slave] {
+    fcntl(*fd, FcntlArg::F_SETFD(FdFlag::FD_CLOEXEC))?;
+  }
+  // Only the master is polled, the child gets a blocking slave.
+  let flags = OFlag::from_bits_truncate(fcntl(pty.master, FcntlArg::F_GETFL)?);
+  fcntl(pty.master, FcntlArg::F_SETFL(flags | OFlag::O_NONBLOCK))?;
+
+  command.stdin(slave.try_clone()?);
+  command.stdout(slave.try_clone()?);
+  command.stderr(slave);
+  // SAFETY: setsid() and ioctl() are async-signal-safe, so they may be called
+  // between fork and exec.
+  unsafe {
+    command.pre_exec(|| {
+      if libc::setsid() == -1 || libc::ioctl(0, libc::TIOCSCTTY as _, 0) == -1 {
+        return Err(Error::last_os_error());
+      }
+      Ok(())
+    });
+  }
+
+  PtyResource::new(master)
+}
+
+#[cfg(not(unix))]
+pub fn attach_pty(
+  _command: &mut std::process::Command,
+  _size: &PtySize,
+) -> Result<PtyResource, AnyError> {
+  Err(type_error("Pseudo-terminals are only supported on Unix"))
+}
+
+#[derive(Deserialize)]
+pub struct PtyResizeArgs {
+  rid: ResourceId,
+  size: PtySize,
+}
+
+fn op_pty_resize(
+  state: &mut OpState,
+  args: PtyResizeArgs,
+  _: (),
+) -> Result<(), AnyError> {
+  let resource = state
+    .resource_table
+    .get::<PtyResource>(args.rid)
+    .ok_or_else(bad_resource_id)?;
+  #[cfg(unix)]
+  {
+    resource.resize(&args.size)
+  }
+  #[cfg(not(unix))]
+  {
+    let _ = (resource, args.size);
+    Err(not_supported())
+  }
+}
@@ -0,0 +1,187 @@
+// Copyright 2018-2021 the Deno authors. All rights reserved. MIT license.
+
+//! Records every permission check as a line of JSON, for `--permission-log`.
+//! Each entry carries the JavaScript call site of the op that made the check,
+//! which is tracked by the op middleware of the extension returned by
+//! `init()`.
+
+use deno_core::error::AnyError;
+use deno_core::futures::FutureExt;
+use deno_core::serde_json;
+use deno_core::Extension;
+use deno_core::Op;
+use deno_core::OpAsyncFuture;
+use deno_core::OpFn;
+use deno_core::OpPayload;
+use deno_core::OpResult;
+use deno_core::PromiseId;
+use serde::Serialize;
+use std::cell::RefCell;
+use std::fs::File;
+use std::future::Future;
+use std::io::Write;
+use std::path::Path;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+use std::task::Context;
+use std::task::Poll;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+lazy_static::lazy_static! {
+  static ref LOG_FILE: Mutex<Option<File>> = Mutex::new(None);
+}
+
+thread_local! {
+  static CALL_SITE: RefCell<Option<Rc<CallSite>>> = RefCell::new(None);
+}
+
+/// Starts logging permission checks to the file at `path`, replacing its
+/// contents. Must be called before any worker is created, so that their ops
+/// record where they are called from.
+pub fn enable(path: &Path) -> Result<(), AnyError> {
+  let file = File::create(path)?;
+  *LOG_FILE.lock().unwrap() = Some(file);
+  ENABLED.store(true, Ordering::SeqCst);
+  Ok(())
+}
+
+fn is_enabled() -> bool {
+  ENABLED.load(Ordering::Relaxed)
+}
+
+pub fn init() -> Extension {
+  Extension::builder().middleware(call_site_op).build()
+}
+
+/// Where in user code an op was called from.
+struct CallSite {
+  /// The module of the innermost frame that isn't part of the runtime.
+  specifier: Option<String>,
+  /// The frames outside of the runtime, innermost first.
+  stack: Vec<String>,
+}
+
+impl CallSite {
+  fn capture(payload: &mut OpPayload) -> Self {
+    let stack: Vec<_> = payload
+      .stack_trace()
+      .into_iter()
+      .filter(|frame| match &frame.file_name {
+        Some(file_name) => !file_name.starts_with("deno:"),
+        None => false,
+      })
+      .collect();
+    Self {
+      specifier: stack.first().and_then(|frame| frame.file_name.clone()),
+      stack: stack
+        .iter()
+        .map(|frame| {
+          let location = format!(
+            "{}:{}:{}",
+            frame.file_name.as_deref().unwrap_or_default(),
+            frame.line_number.unwrap_or_default(),
+            frame.column_number.unwrap_or_default()
+          );
+          match &frame.function_name {
+            Some(function_name) => format!("{} ({})", function_name, location),
+            None => location,
+          }
+        })
+        .collect(),
+    }
+  }
+}
+
+/// Runs `f` with `call_site` as the current call site.
+fn with_call_site<R>(call_site: &Rc<CallSite>, f: impl FnOnce() -> R) -> R {
+  let previous =
+    CALL_SITE.with(|current| current.replace(Some(call_site.clone())));
+  let result = f();
+  CALL_SITE.with(|current| *current.borrow_mut() = previous);
+  result
+}
+
+/// Async ops may check permissions whenever they are polled, so the call site
+/// is restored around every poll.
+struct WithCallSite {
+  fut: OpAsyncFuture,
+  call_site: Rc<CallSite>,
+}
+
+impl Future for WithCallSite {
+  type Output = (PromiseId, OpResult);
+
+  fn poll(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+  ) -> Poll<Self::Output> {
+    let WithCallSite { fut, call_site } = &mut *self;
+    with_call_site(call_site, || fut.poll_unpin(cx))
+  }
+}
+
+fn call_site_op(_name: &'static str, op_fn: Box<OpFn>) -> Box<OpFn> {
+  // Capturing the stack of every op call is expensive, leave ops untouched
+  // unless the log was asked for.
+  if !is_enabled() {
+    return op_fn;
+  }
+  Box::new(move |op_state, mut payload| -> Op {
+    let call_site = Rc::new(CallSite::capture(&mut payload));
+    match with_call_site(&call_site, || (op_fn)(op_state, payload)) {
+      Op::Async(fut) => {
+        Op::Async(WithCallSite { fut, call_site }.boxed_local())
+      }
+      Op::AsyncUnref(fut) => {
+        Op::AsyncUnref(WithCallSite { fut, call_site }.boxed_local())
+      }
+      op => op,
+    }
+  })
+}
+
+#[derive(Serialize)]
+struct PermissionLogEntry<'a> {
+  kind: &'a str,
+  descriptor: Option<&'a str>,
+  granted: bool,
+  specifier: Option<&'a str>,
+  stack: &'a [String],
+}
+
+/// Records the outcome of a check of the `kind` permission. The descriptor is
+/// the one shown in permission prompts.
+pub(crate) fn log_check(kind: &str, descriptor: Option<&str>, granted: bool) {
+  if !is_enabled() {
+    return;
+  }
+  // Descriptors are quoted for messages, the log has them bare.
+  let descriptor = descriptor.map(|descriptor| {
+    descriptor
+      .strip_prefix('"')
+      .and_then(|descriptor| descriptor.strip_suffix('"'))
+      .unwrap_or(descriptor)
+  });
+  let call_site = CALL_SITE.with(|current| current.borrow().clone());
+  let entry = PermissionLogEntry {
+    kind,
+    descriptor,
+    granted,
+    specifier: call_site
+      .as_ref()
+      .and_then(|call_site| call_site.specifier.as_deref()),
+    stack: call_site
+      .as_ref()
+      .map_or(&[][..], |call_site| call_site.stack.as_slice()),
+  };
+  let mut line = serde_json::to_string(&entry).unwrap();
+  line.push('\n');
+  if let Some(file) = LOG_FILE.lock().unwrap().as_mut() {
+    // A failing audit log must not take the program down with it.
+    let _ = file.write_all(line.as_bytes());
+  }
+}
@@ -2,6 +2,7 @@
 
 use crate::colors;
 use crate::fs_util::resolve_from_cwd;
+use crate::permission_log;
 use deno_core::error::custom_error;
 use deno_core::error::uri_error;
 use deno_core::error::AnyError;
@@ -73,7 +74,7 @@ impl PermissionState {
     info: Option<&str>,
     prompt: bool,
   ) -> (Result<(), AnyError>, bool) {
-    match self {
+    let (result, prompted) = match self {
       PermissionState::Granted => {
         Self::log_perm_access(name, info);
         (Ok(()), false)
@@ -88,7 +89,9 @@ impl PermissionState {
         }
       }
       _ => (Err(Self::error(name, info)), false),
-    }
+    };
+    permission_log::log_check(name, info, result.is_ok());
+    (result, prompted)
   }
 }
 
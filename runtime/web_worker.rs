@@ -4,6 +4,7 @@ use crate::inspector_server::InspectorServer;
 use crate::js;
 use crate::metrics;
 use crate::ops;
+use crate::permission_log;
 use crate::permissions::Permissions;
 use crate::tokio_util::create_basic_runtime;
 use deno_broadcast_channel::InMemoryBroadcastChannel;
@@ -277,6 +278,7 @@ impl WebWorker {
       deno_timers::init::<Permissions>(),
       // Metrics
       metrics::init(),
+      permission_log::init(),
       // Permissions ext (worker specific state)
       perm_ext,
     ];
@@ -300,6 +302,7 @@ impl WebWorker {
         ops::permissions::init(),
         ops::plugin::init(),
         ops::process::init(),
+        ops::spawn::init(),
         ops::signal::init(),
         ops::tls::init(),
         ops::tty::init(),
@@ -4,6 +4,7 @@ use crate::inspector_server::InspectorServer;
 use crate::js;
 use crate::metrics;
 use crate::ops;
+use crate::permission_log;
 use crate::permissions::Permissions;
 use deno_broadcast_channel::InMemoryBroadcastChannel;
 use deno_core::error::AnyError;
@@ -116,6 +117,7 @@ impl MainWorker {
       deno_timers::init::<Permissions>(),
       // Metrics
       metrics::init(),
+      permission_log::init(),
       // Runtime ops
       ops::runtime::init(main_module),
       ops::worker_host::init(options.create_web_worker_cb.clone()),
@@ -129,6 +131,7 @@ impl MainWorker {
       ops::permissions::init(),
       ops::plugin::init(),
       ops::process::init(),
+      ops::spawn::init(),
       ops::signal::init(),
       ops::tls::init(),
       ops::tty::init(),
@@ -279,6 +282,15 @@ impl MainWorker {
   }
 }
 
+impl Drop for MainWorker {
+  fn drop(&mut self) {
+    // Don't leave behind children that are still running.
+    let op_state = self.js_runtime.op_state();
+    let op_state = op_state.borrow();
+    op_state.borrow::<ops::process::ChildProcesses>().kill_all();
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;